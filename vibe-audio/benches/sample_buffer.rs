@@ -0,0 +1,193 @@
+//! Compares the [SampleBuffer] with the previous design: a `Mutex` around a buffer
+//! which gets shifted with each push.
+//!
+//! Run with `cargo bench -p vibe-audio`.
+use criterion::{criterion_group, criterion_main, Bencher, Criterion, Throughput};
+use std::{
+    hint::black_box,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+use vibe_audio::fetcher::SampleBuffer;
+
+/// Common window length * amount of channels.
+const CAPACITY: usize = 4096 * 2;
+/// The size of a common audio callback (256 stereo frames).
+const CHUNK_LEN: usize = 512;
+/// The time between two audio callbacks which isn't measured.
+const CALLBACK_PAUSE: Duration = Duration::from_micros(2);
+
+/// The previous design of the sample buffer.
+struct ShiftBuffer {
+    buffer: Box<[f32]>,
+}
+
+impl ShiftBuffer {
+    fn new() -> Self {
+        Self {
+            buffer: vec![0f32; CAPACITY].into_boxed_slice(),
+        }
+    }
+
+    fn push_before(&mut self, data: &[f32]) {
+        let buffer_len = self.buffer.len();
+        let split_point = buffer_len.min(data.len());
+
+        self.buffer
+            .copy_within(..split_point, buffer_len - split_point);
+        self.buffer[..split_point].copy_from_slice(&data[..split_point]);
+    }
+}
+
+/// Simulates the render thread which processes the whole buffer over and over again.
+struct Reader {
+    is_running: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl Reader {
+    fn spawn(mut process: impl FnMut(&mut [f32]) + Send + 'static) -> Self {
+        let is_running = Arc::new(AtomicBool::new(true));
+
+        let handle = std::thread::spawn({
+            let is_running = is_running.clone();
+
+            move || {
+                let mut fft_in = vec![0f32; CAPACITY];
+                while is_running.load(Ordering::Relaxed) {
+                    process(&mut fft_in);
+                    black_box(&fft_in);
+                }
+            }
+        });
+
+        Self { is_running, handle }
+    }
+
+    fn stop(self) {
+        self.is_running.store(false, Ordering::Relaxed);
+        self.handle.join().unwrap();
+    }
+}
+
+/// The work of the render thread per sample: applying the window function.
+fn apply_window(samples: &[f32], fft_in: &mut [f32]) {
+    for (idx, (sample, out)) in samples.iter().zip(fft_in).enumerate() {
+        let window = (std::f32::consts::PI * idx as f32 / CAPACITY as f32).sin();
+        *out = sample * window * window;
+    }
+}
+
+/// Measures `push` like an audio callback: with a pause between two calls,
+/// so the render thread gets the chance to take the buffer.
+fn push_periodically(b: &mut Bencher, mut push: impl FnMut()) {
+    b.iter_custom(|iters| {
+        let mut elapsed = Duration::ZERO;
+
+        for _ in 0..iters {
+            let pause = Instant::now();
+            while pause.elapsed() < CALLBACK_PAUSE {
+                std::hint::spin_loop();
+            }
+
+            let start = Instant::now();
+            push();
+            elapsed += start.elapsed();
+        }
+
+        elapsed
+    })
+}
+
+fn ring_buffer() -> SampleBuffer {
+    let sample_buffer = SampleBuffer::new(44_100);
+    sample_buffer.set_capacity(CAPACITY);
+    sample_buffer
+}
+
+/// The work of the audio callback without any reader.
+fn push(c: &mut Criterion) {
+    let chunk = vec![0.5f32; CHUNK_LEN];
+
+    let mut group = c.benchmark_group("push");
+    group.throughput(Throughput::Elements(CHUNK_LEN as u64));
+
+    let shift_buffer = Mutex::new(ShiftBuffer::new());
+    group.bench_function("mutex_shift", |b| {
+        b.iter(|| shift_buffer.lock().unwrap().push_before(black_box(&chunk)))
+    });
+
+    let sample_buffer = ring_buffer();
+    group.bench_function("ring", |b| b.iter(|| sample_buffer.push(black_box(&chunk))));
+
+    group.finish();
+}
+
+/// The work of the audio callback while the render thread keeps processing the buffer.
+///
+/// This is where the audio callback used to wait: the render thread held the lock
+/// until it processed all samples.
+fn push_while_processing(c: &mut Criterion) {
+    let chunk = vec![0.5f32; CHUNK_LEN];
+
+    let mut group = c.benchmark_group("push_while_processing");
+    group.throughput(Throughput::Elements(CHUNK_LEN as u64));
+
+    let shift_buffer = Arc::new(Mutex::new(ShiftBuffer::new()));
+    let reader = Reader::spawn({
+        let shift_buffer = shift_buffer.clone();
+        move |fft_in| apply_window(&shift_buffer.lock().unwrap().buffer, fft_in)
+    });
+    group.bench_function("mutex_shift", |b| {
+        push_periodically(b, || {
+            shift_buffer.lock().unwrap().push_before(black_box(&chunk))
+        })
+    });
+    reader.stop();
+
+    let sample_buffer = ring_buffer();
+    let reader = Reader::spawn({
+        let sample_buffer = sample_buffer.clone();
+        let mut snapshot = vec![0f32; CAPACITY];
+        move |fft_in| {
+            sample_buffer.snapshot(&mut snapshot);
+            apply_window(&snapshot, fft_in);
+        }
+    });
+    group.bench_function("ring", |b| {
+        push_periodically(b, || sample_buffer.push(black_box(&chunk)))
+    });
+    reader.stop();
+
+    group.finish();
+}
+
+/// The work of the render thread.
+fn read(c: &mut Criterion) {
+    let chunk = vec![0.5f32; CAPACITY];
+    let mut snapshot = vec![0f32; CAPACITY];
+
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Elements(CAPACITY as u64));
+
+    let shift_buffer = Mutex::new(ShiftBuffer::new());
+    shift_buffer.lock().unwrap().push_before(&chunk);
+    group.bench_function("mutex_shift", |b| {
+        b.iter(|| snapshot.copy_from_slice(&shift_buffer.lock().unwrap().buffer))
+    });
+
+    let sample_buffer = ring_buffer();
+    sample_buffer.push(&chunk);
+    group.bench_function("ring", |b| {
+        b.iter(|| sample_buffer.snapshot(black_box(&mut snapshot)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, push, push_while_processing, read);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use vibe_audio::{
+    fetcher::{FileFetcher, FileFetcherDescriptor, FilePacing},
+    BarProcessor, BarProcessorConfig, CubicSplineInterpolation, SampleProcessor,
+};
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .expect("Path to an audio file is given as the first argument");
+
+    let descriptor = FileFetcherDescriptor {
+        path,
+        pacing: FilePacing::RealTime,
+        looping: true,
+    };
+
+    let mut processor = SampleProcessor::new(FileFetcher::new(&descriptor).unwrap());
+    let mut bar_processor: BarProcessor<CubicSplineInterpolation> =
+        BarProcessor::new(&processor, BarProcessorConfig::default());
+
+    for _ in 0..100 {
+        processor.process_next_samples();
+        println!("{:?}", bar_processor.process_bars(&processor)[0]);
+
+        std::thread::sleep(std::time::Duration::from_millis(16));
+    }
+}
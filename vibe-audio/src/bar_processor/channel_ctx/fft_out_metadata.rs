@@ -1,17 +1,21 @@
+use super::frequency_bands::{self, FrequencyScale};
 use crate::{
-    bar_processor::config::BarDistribution, interpolation::SupportingPoint, MAX_HUMAN_FREQUENCY,
-    MIN_HUMAN_FREQUENCY,
+    bar_processor::config::BarDistribution, interpolation::SupportingPoint,
+    sample_processor::BinLayout, BarFrequencies, MAX_HUMAN_FREQUENCY, MIN_HUMAN_FREQUENCY,
 };
-use cpal::SampleRate;
 use std::{num::NonZero, ops::Range};
 use tracing::debug;
 
+/// The amount of bins which a supporting point needs at least within a coarser resolution to use it.
+/// A single bin would smear the neighbouring frequencies into the supporting point.
+const MIN_BINS_PER_RESOLUTION: usize = 2;
+
 #[derive(Debug)]
 pub struct FftOutMetadataDescriptor {
     pub amount_bars: NonZero<u16>,
-    pub sample_rate: SampleRate,
-    pub fft_size: usize,
+    pub bin_layout: BinLayout,
     pub freq_range: Range<NonZero<u16>>,
+    pub distribution: BarDistribution,
 }
 
 /// Stores information which is extracted of the context for the fft.
@@ -20,14 +24,94 @@ pub struct FftOutMetadata {
     /// The supporting points which can then be interpolated later to create a smooth audio
     /// wave.
     pub supporting_points: Box<[SupportingPoint]>,
-    /// The index ranges within the fft output for each supporting point.
+    /// The index ranges within the spectrum for each supporting point.
     pub supporting_points_fft_ranges: Box<[Range<usize>]>,
+    /// The index of the resolution (see [FftOutMetadata::pick_resolutions]) whose spectrum
+    /// the range of each supporting point refers to.
+    pub supporting_points_resolutions: Box<[usize]>,
+    /// The frequencies of each bar.
+    pub bar_frequencies: Box<[BarFrequencies]>,
+
+    amount_bars: NonZero<u16>,
+    bin_layout: BinLayout,
 }
 
 impl FftOutMetadata {
     /// Given the fft-context (input size, sample rate, etc.), it computes the required
     /// preparations to process the fft-output.
     pub fn interpret_fft_context(desc: FftOutMetadataDescriptor) -> Self {
+        let freq_range = desc.freq_range.start.get() as f32..desc.freq_range.end.get() as f32;
+
+        let bands = match desc.distribution {
+            BarDistribution::Uniform | BarDistribution::Natural => {
+                return match desc.bin_layout {
+                    BinLayout::Linear { .. } => Self::interpret_with_exp_fun(desc),
+                    // the bins are already spaced like the notes
+                    BinLayout::Logarithmic { .. } => Self::interpret_evenly(desc),
+                };
+            }
+            BarDistribution::Mel => frequency_bands::scale_bands(
+                FrequencyScale::Mel,
+                desc.amount_bars.get() as usize,
+                freq_range,
+            ),
+            BarDistribution::Bark => frequency_bands::scale_bands(
+                FrequencyScale::Bark,
+                desc.amount_bars.get() as usize,
+                freq_range,
+            ),
+            BarDistribution::Erb => frequency_bands::scale_bands(
+                FrequencyScale::Erb,
+                desc.amount_bars.get() as usize,
+                freq_range,
+            ),
+            BarDistribution::Octave(fraction) => {
+                let mut bands = frequency_bands::octave_bands(fraction, freq_range);
+                bands.truncate(u16::MAX as usize);
+                bands
+            }
+        };
+        debug!("Bands: {:?}", bands);
+
+        let bin_layout = desc.bin_layout;
+        let fft_out_size = bin_layout.amount_bins();
+        let bin_of =
+            |freq: f32| (bin_layout.bin_of(freq).round().max(0.) as usize).min(fft_out_size);
+
+        // == supporting points
+        let mut supporting_points = Vec::new();
+        let mut supporting_points_fft_ranges = Vec::new();
+
+        let mut start = bin_of(bands[0].lower).clamp(bin_layout.first_bin(), fft_out_size - 1);
+        for (bar_idx, band) in bands.iter().enumerate() {
+            let mut end = bin_of(band.upper);
+
+            // Bands which are narrower than a bin get interpolated, except the first and the last one,
+            // because the interpolation needs them.
+            let is_first_or_last_bar = bar_idx == 0 || bar_idx + 1 == bands.len();
+            if end <= start && is_first_or_last_bar {
+                end = (start + 1).min(fft_out_size);
+            }
+
+            let new_fft_range = start..end;
+            if !new_fft_range.is_empty() {
+                supporting_points.push(SupportingPoint { x: bar_idx, y: 0. });
+                supporting_points_fft_ranges.push(new_fft_range);
+                start = end;
+            }
+        }
+
+        Self {
+            supporting_points_resolutions: vec![0; supporting_points.len()].into_boxed_slice(),
+            supporting_points: supporting_points.into_boxed_slice(),
+            supporting_points_fft_ranges: supporting_points_fft_ranges.into_boxed_slice(),
+            amount_bars: NonZero::new(bands.len() as u16).unwrap(),
+            bar_frequencies: bands.into_boxed_slice(),
+            bin_layout,
+        }
+    }
+
+    fn interpret_with_exp_fun(desc: FftOutMetadataDescriptor) -> Self {
         // == preparations
         let weights = {
             let amount_bars = desc.amount_bars.get() as u32;
@@ -38,10 +122,15 @@ impl FftOutMetadata {
         };
         debug!("Weights: {:?}", weights);
 
-        let amount_bins = {
-            let freq_resolution = desc.sample_rate as f32 / desc.fft_size as f32;
-            debug!("Freq resolution: {}", freq_resolution);
+        let BinLayout::Linear {
+            freq_resolution, ..
+        } = desc.bin_layout
+        else {
+            unreachable!("The exp function expects the bins of the fft output.");
+        };
+        debug!("Freq resolution: {}", freq_resolution);
 
+        let amount_bins = {
             // the relevant index range of the fft output which we should use for the bars
             let bin_range = Range {
                 start: ((desc.freq_range.start.get() as f32 / freq_resolution) as usize).max(1),
@@ -80,13 +169,75 @@ impl FftOutMetadata {
         );
 
         Self {
+            supporting_points_resolutions: vec![0; supporting_points.len()].into_boxed_slice(),
             supporting_points: supporting_points.into_boxed_slice(),
             supporting_points_fft_ranges: supporting_points_fft_ranges.into_boxed_slice(),
+            // computed after the supporting points got redistributed
+            bar_frequencies: Box::new([]),
+            amount_bars: desc.amount_bars,
+            bin_layout: desc.bin_layout,
         }
     }
 
-    /// Ensures that all supporting points cover the given amount of bars.
-    pub fn fillup(mut self, amount_bars: NonZero<u16>) -> Self {
+    /// Splits the bins within the frequency range evenly among the bars.
+    fn interpret_evenly(desc: FftOutMetadataDescriptor) -> Self {
+        let bin_layout = desc.bin_layout;
+        let amount_bars = desc.amount_bars.get() as usize;
+
+        let bin_range = {
+            let start = (bin_layout
+                .bin_of(desc.freq_range.start.get() as f32)
+                .ceil()
+                .max(0.) as usize)
+                .min(bin_layout.amount_bins() - 1);
+            let end = (bin_layout
+                .bin_of(desc.freq_range.end.get() as f32)
+                .floor()
+                .max(0.) as usize
+                + 1)
+            .clamp(start + 1, bin_layout.amount_bins());
+
+            start..end
+        };
+        debug!("Bin range: {:?}", bin_range);
+
+        let mut supporting_points = Vec::new();
+        let mut supporting_points_fft_ranges = Vec::new();
+
+        let mut start = bin_range.start;
+        for bar_idx in 0..amount_bars {
+            let end = bin_range.start + ((bar_idx + 1) * bin_range.len()).div_ceil(amount_bars);
+
+            // bars without an own bin get interpolated
+            if end > start {
+                supporting_points.push(SupportingPoint { x: bar_idx, y: 0. });
+                supporting_points_fft_ranges.push(start..end);
+                start = end;
+            }
+        }
+
+        Self {
+            supporting_points_resolutions: vec![0; supporting_points.len()].into_boxed_slice(),
+            supporting_points: supporting_points.into_boxed_slice(),
+            supporting_points_fft_ranges: supporting_points_fft_ranges.into_boxed_slice(),
+            // computed after the supporting points got redistributed
+            bar_frequencies: Box::new([]),
+            amount_bars: desc.amount_bars,
+            bin_layout,
+        }
+    }
+
+    /// Returns the amount of bars (without padding) which the supporting points should cover.
+    ///
+    /// Differs from the configured amount of bars for [BarDistribution::Octave].
+    pub fn amount_bars(&self) -> NonZero<u16> {
+        self.amount_bars
+    }
+
+    /// Ensures that all supporting points cover the amount of bars.
+    pub fn fillup(mut self) -> Self {
+        let amount_bars = self.amount_bars;
+
         // It could happen that we don't have enough supporting points yet to have the given amount of bars set in `config.amount_bars`.
         // So just add a supporting point in the end.
         let last_x = self.supporting_points.last().unwrap().x + 1;
@@ -126,12 +277,120 @@ impl FftOutMetadata {
                     supporting_point.x = (idx as f32 * step) as usize;
                 }
             }
-            BarDistribution::Natural => {}
+            BarDistribution::Natural
+            | BarDistribution::Mel
+            | BarDistribution::Bark
+            | BarDistribution::Erb
+            | BarDistribution::Octave(_) => {}
+        }
+
+        if self.bar_frequencies.is_empty() {
+            self.bar_frequencies = self.approximate_bar_frequencies();
         }
 
         self
     }
 
+    /// Moves the supporting points to the coarsest resolution whose bins are still narrower than their frequency range,
+    /// so the treble can be computed with the shorter windows.
+    ///
+    /// `bin_layouts` are the layouts of all resolutions, ordered from the finest to the coarsest bins,
+    /// where the first one is the layout which the metadata got computed with.
+    pub fn pick_resolutions(mut self, bin_layouts: &[BinLayout]) -> Self {
+        debug_assert_eq!(bin_layouts.first(), Some(&self.bin_layout));
+
+        for (range, resolution) in self
+            .supporting_points_fft_ranges
+            .iter_mut()
+            .zip(self.supporting_points_resolutions.iter_mut())
+        {
+            let lower = self.bin_layout.freq_of(range.start as f32);
+            let upper = self.bin_layout.freq_of(range.end as f32);
+
+            for (resolution_idx, bin_layout) in bin_layouts.iter().enumerate().skip(1).rev() {
+                let (start, end) = (bin_layout.bin_of(lower), bin_layout.bin_of(upper));
+                if end - start < MIN_BINS_PER_RESOLUTION as f32 {
+                    continue;
+                }
+
+                let start = (start.round().max(0.) as usize).max(bin_layout.first_bin());
+                let end = (end.round().max(0.) as usize).min(bin_layout.amount_bins());
+                if end > start {
+                    *range = start..end;
+                    *resolution = resolution_idx;
+                    break;
+                }
+            }
+        }
+        debug!("Resolutions: {:?}", self.supporting_points_resolutions);
+
+        self
+    }
+
+    /// Estimates the frequencies of the bars by interpolating the frequencies of the supporting points.
+    fn approximate_bar_frequencies(&self) -> Box<[BarFrequencies]> {
+        let ranges = &self.supporting_points_fft_ranges;
+        let lowest = self
+            .bin_layout
+            .freq_of(ranges.first().unwrap().start as f32);
+        let highest = self.bin_layout.freq_of(ranges.last().unwrap().end as f32);
+
+        // (bar index, center frequency) of each supporting point
+        let centers = self
+            .supporting_points
+            .iter()
+            .enumerate()
+            .map(|(sup_idx, point)| {
+                let center = match ranges.get(sup_idx) {
+                    Some(range) => self
+                        .bin_layout
+                        .freq_of((range.start + range.end) as f32 / 2.),
+                    // the supporting point which got added by `fillup`
+                    None => highest,
+                };
+
+                (point.x, center)
+            })
+            .collect::<Vec<(usize, f32)>>();
+
+        let bar_centers = (0..self.covered_amount_bars())
+            .map(|bar_idx| {
+                let right = centers
+                    .iter()
+                    .position(|&(x, _)| x >= bar_idx)
+                    .unwrap_or(centers.len() - 1);
+                let left = right.saturating_sub(1);
+
+                let (left_x, left_center) = centers[left];
+                let (right_x, right_center) = centers[right];
+                if right_x == left_x {
+                    return right_center;
+                }
+
+                let t = (bar_idx as f32 - left_x as f32) / (right_x - left_x) as f32;
+                left_center + t * (right_center - left_center)
+            })
+            .collect::<Vec<f32>>();
+
+        (0..bar_centers.len())
+            .map(|bar_idx| {
+                let center = bar_centers[bar_idx];
+
+                BarFrequencies {
+                    lower: match bar_idx.checked_sub(1) {
+                        Some(prev) => (bar_centers[prev] + center) / 2.,
+                        None => lowest.min(center),
+                    },
+                    center,
+                    upper: match bar_centers.get(bar_idx + 1) {
+                        Some(next) => (center + next) / 2.,
+                        None => highest.max(center),
+                    },
+                }
+            })
+            .collect()
+    }
+
     /// Returns the amount of bars which the supporting points cover up.
     ///
     /// The first and last supporting point are the first and last bar.
@@ -0,0 +1,164 @@
+use crate::{BarFrequencies, OctaveFraction};
+use std::ops::Range;
+
+/// The reference frequency of the fractional-octave bands (see IEC 61260).
+const OCTAVE_REFERENCE_FREQUENCY: f32 = 1_000.;
+
+/// A frequency scale where equal distances sound roughly equally far apart.
+#[derive(Debug, Clone, Copy)]
+pub enum FrequencyScale {
+    Mel,
+    Bark,
+    Erb,
+}
+
+impl FrequencyScale {
+    /// Maps the given frequency (in Hz) onto the scale.
+    pub fn hz_to_scale(self, freq: f32) -> f32 {
+        match self {
+            Self::Mel => 2595. * (1. + freq / 700.).log10(),
+            // Traunmüller (1990)
+            Self::Bark => 26.81 * freq / (1960. + freq) - 0.53,
+            // Glasberg and Moore (1990)
+            Self::Erb => 21.4 * (1. + 0.00437 * freq).log10(),
+        }
+    }
+
+    /// The inverse of [FrequencyScale::hz_to_scale].
+    pub fn scale_to_hz(self, value: f32) -> f32 {
+        match self {
+            Self::Mel => 700. * (10f32.powf(value / 2595.) - 1.),
+            Self::Bark => 1960. * (value + 0.53) / (26.28 - value),
+            Self::Erb => (10f32.powf(value / 21.4) - 1.) / 0.00437,
+        }
+    }
+}
+
+/// Splits the frequency range into `amount_bands` bands which are equally wide on the given scale.
+pub fn scale_bands(
+    scale: FrequencyScale,
+    amount_bands: usize,
+    freq_range: Range<f32>,
+) -> Vec<BarFrequencies> {
+    let start = scale.hz_to_scale(freq_range.start);
+    let step = (scale.hz_to_scale(freq_range.end) - start) / amount_bands as f32;
+
+    (0..amount_bands)
+        .map(|band_idx| {
+            let edge = |idx: f32| scale.scale_to_hz(start + idx * step);
+
+            BarFrequencies {
+                lower: edge(band_idx as f32),
+                center: edge(band_idx as f32 + 0.5),
+                upper: edge((band_idx + 1) as f32),
+            }
+        })
+        .collect()
+}
+
+/// Returns the fractional-octave bands whose center frequency is within the given frequency range.
+///
+/// The bands are computed with the base-10 octave ratio of IEC 61260, so the center frequencies
+/// match the nominal frequencies (for example 31.5 Hz, 40 Hz, 50 Hz, 63 Hz, ... for 1/3 octave bands).
+/// At least one band is returned.
+pub fn octave_bands(fraction: OctaveFraction, freq_range: Range<f32>) -> Vec<BarFrequencies> {
+    let bands_per_octave = fraction.bands_per_octave() as f32;
+    let octave_ratio = 10f32.powf(0.3);
+
+    // the index of the band relative to the reference frequency
+    let band_idx =
+        |freq: f32| bands_per_octave * (freq / OCTAVE_REFERENCE_FREQUENCY).log(octave_ratio);
+
+    let mut first = band_idx(freq_range.start).ceil() as i32;
+    let mut last = band_idx(freq_range.end).floor() as i32;
+    if first > last {
+        // the range is too narrow => take the band which is the closest one
+        first = band_idx((freq_range.start * freq_range.end).sqrt()).round() as i32;
+        last = first;
+    }
+
+    let half_bandwidth = octave_ratio.powf(1. / (2. * bands_per_octave));
+    (first..=last)
+        .map(|idx| {
+            let center =
+                OCTAVE_REFERENCE_FREQUENCY * octave_ratio.powf(idx as f32 / bands_per_octave);
+
+            BarFrequencies {
+                lower: center / half_bandwidth,
+                center,
+                upper: center * half_bandwidth,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_are_invertible() {
+        for scale in [
+            FrequencyScale::Mel,
+            FrequencyScale::Bark,
+            FrequencyScale::Erb,
+        ] {
+            for freq in [20., 100., 1_000., 10_000., 20_000.] {
+                let value = scale.scale_to_hz(scale.hz_to_scale(freq));
+                assert!((value - freq).abs() / freq < 1e-3, "{:?}: {}", scale, value);
+            }
+        }
+    }
+
+    #[test]
+    fn scale_bands_are_contiguous() {
+        let bands = scale_bands(FrequencyScale::Bark, 24, 50.0..10_000.);
+
+        assert_eq!(bands.len(), 24);
+        assert!((bands[0].lower - 50.).abs() < 0.01);
+        assert!((bands[23].upper - 10_000.).abs() < 1.);
+        for pair in bands.windows(2) {
+            assert_eq!(pair[0].upper, pair[1].lower);
+            assert!(pair[0].lower < pair[0].center && pair[0].center < pair[0].upper);
+        }
+    }
+
+    #[test]
+    fn third_octave_bands_match_the_nominal_frequencies() {
+        const NOMINAL: [f32; 24] = [
+            50., 63., 80., 100., 125., 160., 200., 250., 315., 400., 500., 630., 800., 1_000.,
+            1_250., 1_600., 2_000., 2_500., 3_150., 4_000., 5_000., 6_300., 8_000., 10_000.,
+        ];
+
+        let bands = octave_bands(OctaveFraction::Third, 45.0..11_000.);
+
+        assert_eq!(bands.len(), NOMINAL.len());
+        for (band, nominal) in bands.iter().zip(NOMINAL) {
+            assert!(
+                (band.center - nominal).abs() / nominal < 0.02,
+                "{:?} != {}",
+                band,
+                nominal
+            );
+        }
+        for pair in bands.windows(2) {
+            assert!((pair[0].upper - pair[1].lower).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn twelfth_octave_bands_per_octave() {
+        let bands = octave_bands(OctaveFraction::Twelfth, 1_000.0..1_990.);
+
+        assert_eq!(bands.len(), 12);
+        assert_eq!(bands[0].center, 1_000.);
+    }
+
+    #[test]
+    fn at_least_one_octave_band() {
+        let bands = octave_bands(OctaveFraction::Third, 1_010.0..1_020.);
+
+        assert_eq!(bands.len(), 1);
+        assert_eq!(bands[0].center, 1_000.);
+    }
+}
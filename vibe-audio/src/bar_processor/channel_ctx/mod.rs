@@ -1,11 +1,12 @@
 mod fft_out_metadata;
+mod frequency_bands;
 mod padding;
 
 use crate::{
     interpolation::{Interpolater, InterpolatorDescriptor},
-    BarProcessorConfig, PaddingSize,
+    sample_processor::BinLayout,
+    BarFrequencies, BarProcessorConfig, PaddingSize,
 };
-use cpal::SampleRate;
 use fft_out_metadata::{FftOutMetadata, FftOutMetadataDescriptor};
 use padding::PaddingCtx;
 use realfft::num_complex::Complex32;
@@ -18,8 +19,14 @@ const MIN_MAGNITUDE: f32 = 1e-16;
 pub struct ChannelCtx<I: Interpolater> {
     // The interpolation strategy for this channel
     interpolator: I,
-    // Contains the index range for each supporting point within the fft output for each supporting point
+    // Contains the index range for each supporting point within the spectrum for each supporting point
     fft_out_ranges: Box<[Range<usize>]>,
+    // The resolution of the spectrum which the index range of each supporting point refers to
+    fft_out_resolutions: Box<[usize]>,
+    // The frequencies of each (unpadded) bar
+    bar_frequencies: Box<[BarFrequencies]>,
+    // The gain of the weighting curve for each bin of the spectrum of each resolution
+    bin_gains: Box<[Box<[f32]>]>,
 
     padding: Option<PaddingCtx>,
 
@@ -41,15 +48,17 @@ pub struct ChannelCtx<I: Interpolater> {
 
 /// Construction relevant methods
 impl<I: Interpolater> ChannelCtx<I> {
-    pub fn new(config: &BarProcessorConfig, sample_rate: SampleRate, fft_size: usize) -> Self {
+    /// `bin_layouts` are the layouts of the resolutions of the spectrum, ordered from the finest to the coarsest bins.
+    pub fn new(config: &BarProcessorConfig, bin_layouts: &[BinLayout]) -> Self {
         let mut data = FftOutMetadata::interpret_fft_context(FftOutMetadataDescriptor {
             amount_bars: config.amount_bars,
-            sample_rate,
-            fft_size,
+            bin_layout: bin_layouts[0],
             freq_range: config.freq_range.clone(),
+            distribution: config.bar_distribution,
         })
-        .fillup(config.amount_bars)
-        .redistribute(config.bar_distribution);
+        .fillup()
+        .redistribute(config.bar_distribution)
+        .pick_resolutions(bin_layouts);
 
         let padding = config.padding.as_ref().map(|conf| {
             let size = match conf.size {
@@ -64,7 +73,7 @@ impl<I: Interpolater> ChannelCtx<I> {
                             DEFAULT_PADDING_SIZE
                         }
                     }
-                    crate::BarDistribution::Natural => DEFAULT_PADDING_SIZE,
+                    _ => DEFAULT_PADDING_SIZE,
                 },
                 PaddingSize::Custom(size) => size.get().into(),
             };
@@ -74,12 +83,28 @@ impl<I: Interpolater> ChannelCtx<I> {
             ctx
         });
 
+        let amount_bars = data.amount_bars();
         let FftOutMetadata {
             supporting_points,
             supporting_points_fft_ranges,
+            supporting_points_resolutions,
+            bar_frequencies,
+            ..
         } = data;
 
-        let interpolator = I::new(InterpolatorDescriptor { supporting_points });
+        let bin_gains = bin_layouts
+            .iter()
+            .map(|bin_layout| {
+                (0..bin_layout.amount_bins())
+                    .map(|bin| config.weighting.gain(bin_layout.freq_of(bin as f32)))
+                    .collect()
+            })
+            .collect();
+
+        let interpolator = I::new(InterpolatorDescriptor {
+            supporting_points,
+            variant: config.interpolation,
+        });
         let covered_bar_range = interpolator.covered_bar_range();
 
         let peak = vec![0f32; covered_bar_range.len()].into_boxed_slice();
@@ -90,6 +115,9 @@ impl<I: Interpolater> ChannelCtx<I> {
         let ctx = Self {
             interpolator,
             fft_out_ranges: supporting_points_fft_ranges,
+            fft_out_resolutions: supporting_points_resolutions,
+            bar_frequencies,
+            bin_gains,
             padding: padding.clone(),
 
             normalize_factor: config.init_norm_factor.0,
@@ -105,7 +133,7 @@ impl<I: Interpolater> ChannelCtx<I> {
         };
 
         assert!(ctx.total_amount_bars() <= (u16::MAX as usize),
-            "The configured amount of bars ({}) and the padding size ({}) exceeds the limit of {} bars (total amount bars: {})", config.amount_bars.get(), padding.as_ref().map(|ctx| ctx.amount_bars()).unwrap_or(0), u16::MAX, ctx.total_amount_bars());
+            "The configured amount of bars ({}) and the padding size ({}) exceeds the limit of {} bars (total amount bars: {})", amount_bars.get(), padding.as_ref().map(|ctx| ctx.amount_bars()).unwrap_or(0), u16::MAX, ctx.total_amount_bars());
 
         ctx
     }
@@ -113,17 +141,19 @@ impl<I: Interpolater> ChannelCtx<I> {
 
 /// Processing relevant methods
 impl<I: Interpolater> ChannelCtx<I> {
-    pub fn update_supporting_points(&mut self, fft_out: &[Complex32]) {
+    /// `spectrum` returns the spectrum of the given resolution.
+    pub fn update_supporting_points<'a>(&mut self, spectrum: impl Fn(usize) -> &'a [Complex32]) {
         let mut overshoot = false;
         let mut is_silent = true;
 
         let amount_bars = self.prev.len();
 
-        for (sup_idx, (supporting_point, fft_range)) in self
+        for (sup_idx, ((supporting_point, fft_range), &resolution)) in self
             .interpolator
             .supporting_points_mut()
             .iter_mut()
             .zip(self.fft_out_ranges.iter())
+            .zip(self.fft_out_resolutions.iter())
             .enumerate()
         {
             let normalized_x = supporting_point.x as f32 / amount_bars as f32;
@@ -131,10 +161,11 @@ impl<I: Interpolater> ChannelCtx<I> {
             let amount_bins = fft_range.len() as f32;
             let prev_magnitude = supporting_point.y;
             let mut next_magnitude = {
-                let raw_bar_val = fft_out[fft_range.clone()]
+                let raw_bar_val = spectrum(resolution)[fft_range.clone()]
                     .iter()
-                    .map(|out| {
-                        let mag = out.norm();
+                    .zip(self.bin_gains[resolution][fft_range.clone()].iter())
+                    .map(|(out, gain)| {
+                        let mag = out.norm() * gain;
                         if mag > MIN_MAGNITUDE {
                             is_silent = false;
                         }
@@ -208,6 +239,30 @@ impl<I: Interpolater> ChannelCtx<I> {
 
         unpadded_amount + padding_size
     }
+
+    /// Returns the frequencies of each bar (without the padded bars).
+    pub fn bar_frequencies(&self) -> &[BarFrequencies] {
+        &self.bar_frequencies
+    }
+
+    /// Returns the index of the bar whose value is computed with the given bin of the spectrum
+    /// of the finest resolution (if any).
+    #[cfg(test)]
+    pub fn bar_of_fft_bin(&mut self, bin: usize) -> Option<usize> {
+        let sup_idx = self
+            .fft_out_ranges
+            .iter()
+            .zip(self.fft_out_resolutions.iter())
+            .position(|(range, &resolution)| resolution == 0 && range.contains(&bin))?;
+
+        Some(self.interpolator.supporting_points_mut()[sup_idx].x)
+    }
+
+    /// Returns the resolution which each supporting point uses.
+    #[cfg(test)]
+    pub fn resolutions(&self) -> &[usize] {
+        &self.fft_out_resolutions
+    }
 }
 
 #[cfg(test)]
@@ -229,8 +284,10 @@ mod tests {
                     amount_bars: NonZero::new(1).unwrap(),
                     ..Default::default()
                 },
-                crate::DEFAULT_SAMPLE_RATE,
-                DUMMY_FFT_SIZE,
+                &[BinLayout::linear(
+                    crate::DEFAULT_SAMPLE_RATE,
+                    DUMMY_FFT_SIZE,
+                )],
             );
 
             assert_eq!(ctx.total_amount_bars(), 1);
@@ -247,8 +304,10 @@ mod tests {
                     }),
                     ..Default::default()
                 },
-                crate::DEFAULT_SAMPLE_RATE,
-                DUMMY_FFT_SIZE,
+                &[BinLayout::linear(
+                    crate::DEFAULT_SAMPLE_RATE,
+                    DUMMY_FFT_SIZE,
+                )],
             );
 
             assert_eq!(ctx.total_amount_bars(), 11);
@@ -265,8 +324,10 @@ mod tests {
                     }),
                     ..Default::default()
                 },
-                crate::DEFAULT_SAMPLE_RATE,
-                DUMMY_FFT_SIZE,
+                &[BinLayout::linear(
+                    crate::DEFAULT_SAMPLE_RATE,
+                    DUMMY_FFT_SIZE,
+                )],
             );
 
             assert_eq!(ctx.total_amount_bars(), 21);
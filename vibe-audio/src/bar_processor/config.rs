@@ -1,7 +1,7 @@
-use std::{num::NonZero, ops::Range};
+use std::{num::NonZero, ops::Range, time::Duration};
 
 /// Decides which interpolation strategy for the bars.
-#[derive(Debug, Clone, Copy, Hash)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
 pub enum InterpolationVariant {
     /// No interpolation strategy should be used.
     ///
@@ -13,7 +13,21 @@ pub enum InterpolationVariant {
     Linear,
 
     /// Use the cubic spline interpolation (recommended since it's the smoothest).
+    ///
+    /// It might overshoot between the supporting points (for example below zero next to a peak).
+    #[default]
     CubicSpline,
+
+    /// Use the monotone cubic interpolation (PCHIP, Fritsch-Carlson).
+    ///
+    /// Smooth as well but it never overshoots: Each interpolated value stays between its neighbouring supporting points.
+    Pchip,
+
+    /// Use the Akima interpolation.
+    ///
+    /// Only depends on the nearby supporting points so it overshoots way less than [InterpolationVariant::CubicSpline]
+    /// and doesn't flatten the peaks like [InterpolationVariant::Pchip].
+    Akima,
 }
 
 /// Set the distribution of the bars.
@@ -28,6 +42,69 @@ pub enum BarDistribution {
     /// Don't readjust the frequency bars so that it looks "natural" to us but
     /// physically correct.
     Natural,
+
+    /// Each bar covers the same range on the [mel scale](https://en.wikipedia.org/wiki/Mel_scale).
+    Mel,
+
+    /// Each bar covers the same range on the [bark scale](https://en.wikipedia.org/wiki/Bark_scale).
+    Bark,
+
+    /// Each bar covers the same range on the
+    /// [ERB-rate scale](https://en.wikipedia.org/wiki/Equivalent_rectangular_bandwidth).
+    Erb,
+
+    /// Each bar is a fractional-octave band as they are used by equalizers (see IEC 61260).
+    ///
+    /// The bands are aligned to 1 kHz, so there's one bar for each band whose center frequency
+    /// is within [BarProcessorConfig::freq_range]. As a result, [BarProcessorConfig::amount_bars] is ignored.
+    Octave(OctaveFraction),
+}
+
+/// The bandwidth of a band of [BarDistribution::Octave].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum OctaveFraction {
+    /// 1/3 octave bands (31.5 Hz, 40 Hz, 50 Hz, 63 Hz, ...)
+    Third,
+    /// 1/6 octave bands
+    Sixth,
+    /// 1/12 octave bands (roughly one band per semitone)
+    Twelfth,
+}
+
+impl OctaveFraction {
+    /// Returns the amount of bands per octave.
+    pub fn bands_per_octave(&self) -> u8 {
+        match self {
+            Self::Third => 3,
+            Self::Sixth => 6,
+            Self::Twelfth => 12,
+        }
+    }
+}
+
+/// A frequency weighting curve which is applied to the magnitudes of the fft output
+/// before they are turned into bars.
+///
+/// Since the bars are normalized afterwards, only the shape of the curve matters and not its absolute gain.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Weighting {
+    /// Use the raw magnitudes.
+    #[default]
+    None,
+
+    /// A-weighting (IEC 61672): Follows the sensitivity of the human ear for quiet sounds,
+    /// so the bass gets strongly attenuated.
+    A,
+
+    /// C-weighting (IEC 61672): Nearly flat, only the very low and very high frequencies get attenuated.
+    C,
+
+    /// ITU-R 468 noise weighting: Boosts the frequencies around 6 kHz.
+    Itu468,
+
+    /// Tilts the spectrum around 1 kHz by the given amount of dB per octave.
+    /// Positive values boost the treble.
+    Tilt { db_per_octave: f32 },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -67,6 +144,25 @@ pub struct PaddingConfig {
     pub size: PaddingSize,
 }
 
+/// Configures the falling peaks of the bars (see [crate::BarProcessor::peaks]).
+#[derive(Debug, Clone)]
+pub struct PeakHoldConfig {
+    /// How long a peak stays at its height before it starts to fall.
+    pub hold_time: Duration,
+
+    /// The acceleration of a falling peak in bar heights per second².
+    pub gravity: f32,
+}
+
+impl Default for PeakHoldConfig {
+    fn default() -> Self {
+        Self {
+            hold_time: Duration::from_millis(500),
+            gravity: 2.,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct InitNormFactor(pub f32);
 
@@ -86,8 +182,11 @@ pub struct BarProcessorConfig {
     /// Set the frequency range which the bar processor should consider.
     pub freq_range: Range<NonZero<u16>>,
 
-    // /// Decide how the bar values should be interpolated.
-    // pub interpolation: InterpolationVariant,
+    /// Decide how the bar values should be interpolated.
+    ///
+    /// Only used by [crate::BarProcessor]s with [crate::DynamicInterpolation].
+    pub interpolation: InterpolationVariant,
+
     /// Control how fast the bars should adjust to a peak.
     /// The lower the value, the "faster" the bars adjust to the new height.
     pub up: f32,
@@ -104,6 +203,12 @@ pub struct BarProcessorConfig {
 
     pub padding: Option<PaddingConfig>,
 
+    /// Set the frequency weighting which should be applied to each fft bin.
+    pub weighting: Weighting,
+
+    /// Set how the peaks of the bars should be held.
+    pub peak_hold: PeakHoldConfig,
+
     /// Set the initial normalization factor.
     /// If you set this to a low value, the frequency values will slowly increase.
     pub init_norm_factor: InitNormFactor,
@@ -112,7 +217,7 @@ pub struct BarProcessorConfig {
 impl Default for BarProcessorConfig {
     fn default() -> Self {
         Self {
-            // interpolation: InterpolationVariant::CubicSpline,
+            interpolation: InterpolationVariant::CubicSpline,
             amount_bars: NonZero::new(30).unwrap(),
             freq_range: default_freq_range(),
             up: default_up(),
@@ -121,6 +226,8 @@ impl Default for BarProcessorConfig {
 
             bar_distribution: BarDistribution::Uniform,
             padding: None,
+            weighting: Weighting::None,
+            peak_hold: PeakHoldConfig::default(),
             init_norm_factor: Default::default(),
         }
     }
@@ -1,13 +1,19 @@
 mod channel_ctx;
 mod config;
+mod peak_hold;
+mod weighting;
 
-use crate::{fetcher::Fetcher, interpolation::Interpolater, SampleProcessor};
+use crate::{
+    fetcher::Fetcher, interpolation::Interpolater, sample_processor::BinLayout, SampleProcessor,
+};
 use channel_ctx::ChannelCtx;
 use cpal::SampleRate;
+use peak_hold::PeakHold;
 use std::num::NonZero;
 
 pub use crate::interpolation::{
-    CubicSplineInterpolation, LinearInterpolation, NothingInterpolation,
+    AkimaInterpolation, CubicSplineInterpolation, DynamicInterpolation, LinearInterpolation,
+    NothingInterpolation, PchipInterpolation,
 };
 pub use config::*;
 
@@ -15,6 +21,17 @@ pub use config::*;
 type Channels<I> = Box<[ChannelCtx<I>]>;
 type BarValues = Box<[Box<[f32]>]>;
 
+/// The frequency range (in Hz) which a bar represents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarFrequencies {
+    /// The lower edge of the bar.
+    pub lower: f32,
+    /// The center frequency of the bar.
+    pub center: f32,
+    /// The upper edge of the bar.
+    pub upper: f32,
+}
+
 /// The struct which computes the bar values of the samples of the fetcher.
 pub struct BarProcessor<I: Interpolater> {
     // The final bar values.
@@ -23,13 +40,15 @@ pub struct BarProcessor<I: Interpolater> {
     // - 1st Index: Channel
     // - 2nd index: Bar value
     bar_values: BarValues,
+    // The peak-hold values of the bars with the same layout as `bar_values`.
+    peak_hold: PeakHold,
 
     // ctx[i] = channel context of the i-th channel
     ctx: Channels<I>,
 
     config: BarProcessorConfig,
     sample_rate: SampleRate,
-    sample_len: usize,
+    bin_layouts: Box<[BinLayout]>,
 }
 
 impl<I: Interpolater> BarProcessor<I> {
@@ -38,19 +57,21 @@ impl<I: Interpolater> BarProcessor<I> {
     /// See the examples of this crate to see it's usage.
     pub fn new<F: Fetcher>(processor: &SampleProcessor<F>, config: BarProcessorConfig) -> Self {
         let sample_rate = processor.sample_rate();
-        let sample_len = processor.fft_size();
+        let bin_layouts = processor.bin_layouts();
         let amount_channels = processor.amount_channels();
 
         let (channels, bar_values) =
-            Self::get_channels_and_bar_values(&config, amount_channels, sample_rate, sample_len);
+            Self::get_channels_and_bar_values(&config, amount_channels, &bin_layouts);
+        let peak_hold = PeakHold::new(bar_values.len(), bar_values[0].len());
 
         Self {
             config,
             ctx: channels,
             bar_values,
+            peak_hold,
 
             sample_rate,
-            sample_len,
+            bin_layouts,
         }
     }
 
@@ -59,16 +80,17 @@ impl<I: Interpolater> BarProcessor<I> {
     /// If you access the returned value like this: `bar_processor.process_bars(&processor)[i][j]` then this would mean:
     /// You are accessing the `j`th bar value of the `i`th audio channel.
     pub fn process_bars<F: Fetcher>(&mut self, processor: &SampleProcessor<F>) -> &[Box<[f32]>] {
-        for ((channel_idx, channel), fft_ctx) in self
-            .ctx
-            .iter_mut()
-            .enumerate()
-            .zip(processor.fft_out().iter())
-        {
-            channel.update_supporting_points(&fft_ctx.fft_out);
+        for (channel_idx, channel) in self.ctx.iter_mut().enumerate() {
+            channel.update_supporting_points(|resolution_idx| {
+                processor.spectrum(channel_idx, resolution_idx)
+            });
             channel.interpolate(&mut self.bar_values[channel_idx]);
         }
 
+        let elapsed = processor.amount_new_frames() as f32 / self.sample_rate as f32;
+        self.peak_hold
+            .update(&self.bar_values, elapsed, &self.config.peak_hold);
+
         &self.bar_values
     }
 
@@ -76,6 +98,64 @@ impl<I: Interpolater> BarProcessor<I> {
         &self.bar_values
     }
 
+    /// Returns the peak-hold values of the bars with the same layout as [BarProcessor::bars].
+    ///
+    /// A peak stays at the highest value of its bar for [PeakHoldConfig::hold_time] and falls down
+    /// with an increasing speed afterwards (see [PeakHoldConfig::gravity]) until its bar pushes it up again.
+    /// Useful to draw the falling caps of classic spectrum analyzers.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vibe_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, fetcher::DummyFetcher, NothingInterpolation};
+    ///
+    /// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+    /// let mut bar_processor: BarProcessor<NothingInterpolation> =
+    ///     BarProcessor::new(&sample_processor, BarProcessorConfig::default());
+    ///
+    /// sample_processor.process_next_samples();
+    /// bar_processor.process_bars(&sample_processor);
+    ///
+    /// let peaks = bar_processor.peaks();
+    /// assert_eq!(peaks.len(), 2);
+    /// assert_eq!(peaks[0].len(), bar_processor.bars()[0].len());
+    /// // the peaks are never below their bars
+    /// assert!(peaks[0].iter().zip(bar_processor.bars()[0].iter()).all(|(peak, bar)| peak >= bar));
+    /// ```
+    pub fn peaks(&self) -> &[Box<[f32]>] {
+        self.peak_hold.peaks()
+    }
+
+    /// Returns the frequencies of each bar.
+    ///
+    /// The padded bars aren't included, so the `i`-th entry belongs to the `i`-th bar after the left padding.
+    /// For [BarDistribution::Uniform] and [BarDistribution::Natural] the frequencies are estimated
+    /// from the fft bins which are used for the bars.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vibe_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, BarDistribution, OctaveFraction, fetcher::DummyFetcher, NothingInterpolation};
+    /// use std::num::NonZero;
+    ///
+    /// let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+    /// let bar_processor: BarProcessor<NothingInterpolation> = BarProcessor::new(
+    ///     &sample_processor,
+    ///     BarProcessorConfig {
+    ///         freq_range: NonZero::new(900).unwrap()..NonZero::new(2_100).unwrap(),
+    ///         bar_distribution: BarDistribution::Octave(OctaveFraction::Third),
+    ///         ..Default::default()
+    ///     }
+    /// );
+    ///
+    /// // 1 kHz, 1.25 kHz, 1.6 kHz and 2 kHz
+    /// let bars = bar_processor.bar_frequencies();
+    /// assert_eq!(bars.len(), 4);
+    /// assert_eq!(bars[0].center, 1_000.);
+    /// assert_eq!(bar_processor.total_amount_bars_per_channel(), 4);
+    /// ```
+    pub fn bar_frequencies(&self) -> &[BarFrequencies] {
+        self.ctx[0].bar_frequencies()
+    }
+
     /// Returns the current config of the bar processor.
     pub fn config(&self) -> &BarProcessorConfig {
         &self.config
@@ -113,13 +193,10 @@ impl<I: Interpolater> BarProcessor<I> {
         self.config.amount_bars = amount_bars;
         let amount_channels = self.amount_channels();
 
-        let (channels, bar_values) = Self::get_channels_and_bar_values(
-            &self.config,
-            amount_channels,
-            self.sample_rate,
-            self.sample_len,
-        );
+        let (channels, bar_values) =
+            Self::get_channels_and_bar_values(&self.config, amount_channels, &self.bin_layouts);
 
+        self.peak_hold = PeakHold::new(bar_values.len(), bar_values[0].len());
         self.ctx = channels;
         self.bar_values = bar_values;
     }
@@ -130,15 +207,14 @@ impl<I: Interpolater> BarProcessor<I> {
     fn get_channels_and_bar_values(
         config: &BarProcessorConfig,
         amount_channels: NonZero<u8>,
-        sample_rate: SampleRate,
-        sample_len: usize,
+        bin_layouts: &[BinLayout],
     ) -> (Channels<I>, BarValues) {
         let amount_channels = amount_channels.get() as usize;
         let channels = {
             let mut channels = Vec::with_capacity(amount_channels);
 
             for _ in 0..amount_channels {
-                channels.push(ChannelCtx::new(config, sample_rate, sample_len));
+                channels.push(ChannelCtx::new(config, bin_layouts));
             }
 
             channels
@@ -171,10 +247,316 @@ impl<I: Interpolater> BarProcessor<I> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{fetcher::DummyFetcher, interpolation::NothingInterpolation};
+    use crate::{
+        fetcher::{DummyFetcher, Signal, SignalFetcher},
+        interpolation::NothingInterpolation,
+        test_util::{signal_fetcher, SAMPLE_RATE},
+        ConstantQConfig, MultiResolutionConfig, SampleProcessorConfig, SpectralBackend,
+    };
 
     use super::*;
 
+    /// Returns the index of the bar with the highest value.
+    fn loudest_bar(values: &[f32]) -> usize {
+        values
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(idx, _)| idx)
+            .unwrap()
+    }
+
+    /// Returns the index of the bar whose frequency range contains the given frequency.
+    fn bar_of_frequency(
+        bar_processor: &BarProcessor<NothingInterpolation>,
+        frequency: f32,
+    ) -> usize {
+        bar_processor
+            .bar_frequencies()
+            .iter()
+            .position(|bar| bar.lower <= frequency && frequency < bar.upper)
+            .unwrap()
+    }
+
+    /// Feeds the given signals for `amount_steps` steps into a bar processor with the given configs.
+    fn process_signals(
+        signals: Vec<Signal>,
+        frames_per_step: usize,
+        processor_config: SampleProcessorConfig,
+        bar_config: BarProcessorConfig,
+        amount_steps: usize,
+    ) -> (
+        SampleProcessor<SignalFetcher>,
+        BarProcessor<NothingInterpolation>,
+    ) {
+        let mut processor = SampleProcessor::with_config(
+            signal_fetcher(signals, SAMPLE_RATE, 1, frames_per_step),
+            processor_config,
+        );
+        let mut bar_processor = BarProcessor::new(&processor, bar_config);
+
+        for _ in 0..amount_steps {
+            processor.process_next_samples();
+            bar_processor.process_bars(&processor);
+        }
+
+        (processor, bar_processor)
+    }
+
+    fn sine(frequency: f32) -> Signal {
+        Signal::Sine {
+            frequency,
+            amplitude: 0.5,
+        }
+    }
+
+    #[test]
+    fn sine_tone_lands_in_its_bar() {
+        let (processor, mut bar_processor) = process_signals(
+            vec![sine(1_000.)],
+            512,
+            SampleProcessorConfig::default(),
+            BarProcessorConfig {
+                amount_bars: NonZero::new(30).unwrap(),
+                bar_distribution: BarDistribution::Natural,
+                ..Default::default()
+            },
+            50,
+        );
+
+        let freq_resolution = SAMPLE_RATE as f32 / processor.fft_size() as f32;
+        let bin = (1_000. / freq_resolution).round() as usize;
+        let expected_bar = bar_processor.ctx[0].bar_of_fft_bin(bin).unwrap();
+
+        let bars = &bar_processor.bars()[0];
+        assert_eq!(loudest_bar(bars), expected_bar, "Bars: {:?}", bars);
+    }
+
+    #[test]
+    fn sine_tone_lands_in_its_third_octave_band() {
+        let (_, bar_processor) = process_signals(
+            vec![sine(1_000.)],
+            4096,
+            SampleProcessorConfig {
+                window_len: Some(NonZero::new(4096).unwrap()),
+                ..Default::default()
+            },
+            BarProcessorConfig {
+                bar_distribution: BarDistribution::Octave(OctaveFraction::Third),
+                ..Default::default()
+            },
+            50,
+        );
+
+        // 50 Hz, 63 Hz, ..., 10 kHz
+        assert_eq!(bar_processor.total_amount_bars_per_channel(), 24);
+        let expected_bar = bar_of_frequency(&bar_processor, 1_000.);
+        assert_eq!(bar_processor.bar_frequencies()[expected_bar].center, 1_000.);
+
+        let bars = &bar_processor.bars()[0];
+        assert_eq!(loudest_bar(bars), expected_bar, "Bars: {:?}", bars);
+    }
+
+    #[test]
+    fn sine_tone_lands_in_its_constant_q_bar() {
+        for bar_distribution in [
+            BarDistribution::Natural,
+            BarDistribution::Octave(OctaveFraction::Third),
+        ] {
+            let (_, bar_processor) = process_signals(
+                vec![sine(1_000.)],
+                1024,
+                SampleProcessorConfig {
+                    window_len: Some(NonZero::new(4096).unwrap()),
+                    backend: SpectralBackend::ConstantQ(ConstantQConfig::default()),
+                    ..Default::default()
+                },
+                BarProcessorConfig {
+                    amount_bars: NonZero::new(48).unwrap(),
+                    bar_distribution,
+                    ..Default::default()
+                },
+                10,
+            );
+
+            let bar_frequencies = bar_processor.bar_frequencies();
+            for pair in bar_frequencies.windows(2) {
+                assert!(pair[0].center < pair[1].center, "{:?}", bar_frequencies);
+            }
+            let expected_bar = bar_of_frequency(&bar_processor, 1_000.);
+
+            let bars = &bar_processor.bars()[0];
+            let loudest_bar = loudest_bar(bars);
+
+            assert!(
+                loudest_bar.abs_diff(expected_bar) <= 1,
+                "{:?}: {} {} {:?}",
+                bar_distribution,
+                loudest_bar,
+                expected_bar,
+                bars
+            );
+        }
+    }
+
+    #[test]
+    fn constant_q_bars_are_musically_even() {
+        let processor = SampleProcessor::with_config(
+            DummyFetcher::new(1),
+            SampleProcessorConfig {
+                backend: SpectralBackend::ConstantQ(ConstantQConfig::default()),
+                ..Default::default()
+            },
+        );
+        let bar_processor: BarProcessor<NothingInterpolation> = BarProcessor::new(
+            &processor,
+            BarProcessorConfig {
+                amount_bars: NonZero::new(16).unwrap(),
+                bar_distribution: BarDistribution::Natural,
+                ..Default::default()
+            },
+        );
+
+        // each bar covers the same musical interval
+        let ratios = bar_processor
+            .bar_frequencies()
+            .windows(2)
+            .map(|pair| pair[1].center / pair[0].center)
+            .collect::<Vec<f32>>();
+        for ratio in ratios.iter() {
+            assert!((ratio - ratios[0]).abs() < 0.05, "{:?}", ratios);
+        }
+    }
+
+    #[test]
+    fn multi_resolution_uses_short_windows_for_the_treble() {
+        let processor = SampleProcessor::with_config(
+            DummyFetcher::new(1),
+            SampleProcessorConfig {
+                window_len: Some(NonZero::new(8192).unwrap()),
+                backend: SpectralBackend::MultiResolution(MultiResolutionConfig::default()),
+                ..Default::default()
+            },
+        );
+        let bar_processor: BarProcessor<NothingInterpolation> = BarProcessor::new(
+            &processor,
+            BarProcessorConfig {
+                bar_distribution: BarDistribution::Octave(OctaveFraction::Third),
+                ..Default::default()
+            },
+        );
+
+        let resolutions = bar_processor.ctx[0].resolutions();
+        assert_eq!(resolutions.first(), Some(&0));
+        assert_eq!(resolutions.last(), Some(&2));
+        assert!(
+            resolutions.windows(2).all(|pair| pair[0] <= pair[1]),
+            "{:?}",
+            resolutions
+        );
+    }
+
+    #[test]
+    fn sine_tone_lands_in_its_multi_resolution_bar() {
+        for frequency in [100., 5_000.] {
+            let (_, bar_processor) = process_signals(
+                vec![sine(frequency)],
+                1024,
+                SampleProcessorConfig {
+                    window_len: Some(NonZero::new(8192).unwrap()),
+                    backend: SpectralBackend::MultiResolution(MultiResolutionConfig::default()),
+                    ..Default::default()
+                },
+                BarProcessorConfig {
+                    bar_distribution: BarDistribution::Octave(OctaveFraction::Third),
+                    ..Default::default()
+                },
+                20,
+            );
+
+            let expected_bar = bar_of_frequency(&bar_processor, frequency);
+
+            let bars = &bar_processor.bars()[0];
+            assert_eq!(loudest_bar(bars), expected_bar, "{}: {:?}", frequency, bars);
+        }
+    }
+
+    #[test]
+    fn a_weighting_attenuates_the_bass() {
+        // returns the ratio of the bar of the bass tone to the bar of the 1 kHz tone
+        let bass_ratio = |weighting: Weighting| {
+            let (_, bar_processor) = process_signals(
+                vec![
+                    Signal::Sine {
+                        frequency: 100.,
+                        amplitude: 0.3,
+                    },
+                    Signal::Sine {
+                        frequency: 1_000.,
+                        amplitude: 0.3,
+                    },
+                ],
+                1024,
+                SampleProcessorConfig::default(),
+                BarProcessorConfig {
+                    bar_distribution: BarDistribution::Octave(OctaveFraction::Third),
+                    weighting,
+                    ..Default::default()
+                },
+                50,
+            );
+
+            let bars = &bar_processor.bars()[0];
+            bars[bar_of_frequency(&bar_processor, 100.)]
+                / bars[bar_of_frequency(&bar_processor, 1_000.)]
+        };
+
+        let unweighted = bass_ratio(Weighting::None);
+        let weighted = bass_ratio(Weighting::A);
+
+        // A-weighting: -19.1 dB at 100 Hz
+        assert!(weighted < unweighted * 0.5, "{} {}", weighted, unweighted);
+    }
+
+    #[test]
+    fn bar_frequencies_are_ascending() {
+        let processor = SampleProcessor::new(DummyFetcher::new(2));
+
+        for bar_distribution in [
+            BarDistribution::Uniform,
+            BarDistribution::Natural,
+            BarDistribution::Mel,
+            BarDistribution::Bark,
+            BarDistribution::Erb,
+            BarDistribution::Octave(OctaveFraction::Twelfth),
+        ] {
+            let bar_processor: BarProcessor<NothingInterpolation> = BarProcessor::new(
+                &processor,
+                BarProcessorConfig {
+                    amount_bars: NonZero::new(40).unwrap(),
+                    bar_distribution,
+                    padding: Some(PaddingConfig {
+                        side: PaddingSide::Both,
+                        size: PaddingSize::Auto,
+                    }),
+                    ..Default::default()
+                },
+            );
+
+            let bars = bar_processor.bar_frequencies();
+            if !matches!(bar_distribution, BarDistribution::Octave(_)) {
+                assert_eq!(bars.len(), 40, "{:?}", bar_distribution);
+            }
+
+            for bar in bars {
+                assert!(bar.lower <= bar.center && bar.center <= bar.upper);
+            }
+            for pair in bars.windows(2) {
+                assert!(pair[0].center < pair[1].center, "{:?}", bar_distribution);
+            }
+        }
+    }
+
     #[test]
     fn one_channel_u16_max_bars() {
         let processor = SampleProcessor::new(DummyFetcher::new(1));
@@ -0,0 +1,123 @@
+use crate::PeakHoldConfig;
+
+/// Tracks the peaks of the bar values of each channel.
+pub struct PeakHold {
+    // Same layout as the bar values: `peaks[channel][bar]`
+    peaks: Box<[Box<[f32]>]>,
+    states: Box<[Box<[PeakState]>]>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PeakState {
+    // the time (in seconds) since the peak got reached
+    held_for: f32,
+    // the current falling speed in bar heights per second
+    velocity: f32,
+}
+
+impl PeakHold {
+    pub fn new(amount_channels: usize, amount_bars: usize) -> Self {
+        Self {
+            peaks: vec![vec![0f32; amount_bars].into_boxed_slice(); amount_channels]
+                .into_boxed_slice(),
+            states: vec![
+                vec![PeakState::default(); amount_bars].into_boxed_slice();
+                amount_channels
+            ]
+            .into_boxed_slice(),
+        }
+    }
+
+    /// Updates the peaks with the new bar values.
+    ///
+    /// `elapsed` is the time (in seconds) since the previous update.
+    pub fn update(&mut self, bar_values: &[Box<[f32]>], elapsed: f32, config: &PeakHoldConfig) {
+        let hold_time = config.hold_time.as_secs_f32();
+
+        for ((bars, peaks), states) in bar_values
+            .iter()
+            .zip(self.peaks.iter_mut())
+            .zip(self.states.iter_mut())
+        {
+            for ((&value, peak), state) in bars.iter().zip(peaks.iter_mut()).zip(states.iter_mut())
+            {
+                if value >= *peak {
+                    *peak = value;
+                    *state = PeakState::default();
+                    continue;
+                }
+
+                state.held_for += elapsed;
+                if state.held_for > hold_time {
+                    state.velocity += config.gravity * elapsed;
+                    *peak -= state.velocity * elapsed;
+                }
+
+                // the bar pushes the falling peak up
+                *peak = peak.max(value);
+            }
+        }
+    }
+
+    pub fn peaks(&self) -> &[Box<[f32]>] {
+        &self.peaks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const CONFIG: PeakHoldConfig = PeakHoldConfig {
+        hold_time: Duration::from_millis(100),
+        gravity: 2.,
+    };
+    // 100 updates per second
+    const ELAPSED: f32 = 0.01;
+
+    fn bars(value: f32) -> Box<[Box<[f32]>]> {
+        vec![vec![value; 3].into_boxed_slice()].into_boxed_slice()
+    }
+
+    #[test]
+    fn peak_follows_rising_bars() {
+        let mut peak_hold = PeakHold::new(1, 3);
+
+        for value in [0.1, 0.5, 0.7] {
+            peak_hold.update(&bars(value), ELAPSED, &CONFIG);
+            assert_eq!(peak_hold.peaks()[0][0], value);
+        }
+    }
+
+    #[test]
+    fn peak_is_held_and_falls_afterwards() {
+        let mut peak_hold = PeakHold::new(1, 3);
+        peak_hold.update(&bars(1.), ELAPSED, &CONFIG);
+
+        let mut peaks = Vec::new();
+        for _ in 0..150 {
+            peak_hold.update(&bars(0.), ELAPSED, &CONFIG);
+            peaks.push(peak_hold.peaks()[0][0]);
+        }
+
+        // held for 100ms
+        assert!(peaks[..10].iter().all(|&peak| peak == 1.), "{:?}", peaks);
+        // falls with an increasing speed...
+        let drop = |idx: usize| peaks[idx] - peaks[idx + 1];
+        assert!(0. < drop(15) && drop(15) < drop(30), "{:?}", peaks);
+        // ... until it reached the bar (after ~1.1s)
+        assert_eq!(*peaks.last().unwrap(), 0.);
+    }
+
+    #[test]
+    fn rising_bar_pushes_a_falling_peak() {
+        let mut peak_hold = PeakHold::new(1, 3);
+        peak_hold.update(&bars(1.), ELAPSED, &CONFIG);
+        for _ in 0..100 {
+            peak_hold.update(&bars(0.5), ELAPSED, &CONFIG);
+        }
+
+        assert_eq!(peak_hold.peaks()[0][0], 0.5);
+    }
+}
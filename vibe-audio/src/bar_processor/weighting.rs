@@ -0,0 +1,139 @@
+use crate::{Weighting, MIN_HUMAN_FREQUENCY};
+
+/// The frequency where the curves are normalized to 0 dB.
+const REFERENCE_FREQUENCY: f64 = 1_000.;
+
+impl Weighting {
+    /// Returns the gain of the curve for the given frequency (in Hz) in dB.
+    ///
+    /// # Example
+    /// ```
+    /// use vibe_audio::Weighting;
+    ///
+    /// assert!(Weighting::A.gain_db(1_000.).abs() < 0.01);
+    /// assert!((Weighting::A.gain_db(100.) + 19.1).abs() < 0.1);
+    /// assert_eq!(Weighting::Tilt { db_per_octave: 3. }.gain_db(4_000.), 6.);
+    /// ```
+    pub fn gain_db(&self, freq: f32) -> f32 {
+        let freq = freq as f64;
+
+        let gain = match self {
+            Self::None => 0.,
+            Self::A => 20. * a_weighting(freq).log10() + 2.,
+            Self::C => 20. * c_weighting(freq).log10() + 0.062,
+            Self::Itu468 => 20. * itu_468_weighting(freq).log10() + 18.2,
+            Self::Tilt { db_per_octave } => {
+                // the tilt would explode at the DC bin otherwise
+                let freq = freq.max(MIN_HUMAN_FREQUENCY as f64);
+                *db_per_octave as f64 * (freq / REFERENCE_FREQUENCY).log2()
+            }
+        };
+
+        gain as f32
+    }
+
+    /// Returns the gain of the curve for the given frequency (in Hz) as a linear factor for the magnitude.
+    pub fn gain(&self, freq: f32) -> f32 {
+        10f32.powf(self.gain_db(freq) / 20.)
+    }
+}
+
+// IEC 61672-1
+fn a_weighting(freq: f64) -> f64 {
+    let f2 = freq * freq;
+
+    12194f64.powi(2) * f2 * f2
+        / ((f2 + 20.6f64.powi(2))
+            * ((f2 + 107.7f64.powi(2)) * (f2 + 737.9f64.powi(2))).sqrt()
+            * (f2 + 12194f64.powi(2)))
+}
+
+// IEC 61672-1
+fn c_weighting(freq: f64) -> f64 {
+    let f2 = freq * freq;
+
+    12194f64.powi(2) * f2 / ((f2 + 20.6f64.powi(2)) * (f2 + 12194f64.powi(2)))
+}
+
+// ITU-R BS.468-4
+fn itu_468_weighting(freq: f64) -> f64 {
+    let h1 = -4.737338981378384e-24 * freq.powi(6) + 2.043828333606125e-15 * freq.powi(4)
+        - 1.363894795463638e-7 * freq.powi(2)
+        + 1.;
+    let h2 = 1.306612257412824e-19 * freq.powi(5) - 2.118150887518656e-11 * freq.powi(3)
+        + 5.559488023498642e-4 * freq;
+
+    1.246332637532143e-4 * freq / h1.hypot(h2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_gains(weighting: Weighting, expected: &[(f32, f32)]) {
+        for &(freq, gain) in expected {
+            let actual = weighting.gain_db(freq);
+            assert!(
+                (actual - gain).abs() < 0.2,
+                "{:?} at {} Hz: {} dB, expected {} dB",
+                weighting,
+                freq,
+                actual,
+                gain
+            );
+        }
+    }
+
+    #[test]
+    fn a_weighting() {
+        assert_gains(
+            Weighting::A,
+            &[
+                (31.5, -39.4),
+                (100., -19.1),
+                (1_000., 0.),
+                (4_000., 1.),
+                (10_000., -2.5),
+            ],
+        );
+    }
+
+    #[test]
+    fn c_weighting() {
+        assert_gains(
+            Weighting::C,
+            &[(31.5, -3.), (100., -0.3), (1_000., 0.), (10_000., -4.4)],
+        );
+    }
+
+    #[test]
+    fn itu_468_weighting() {
+        assert_gains(
+            Weighting::Itu468,
+            &[
+                (31.5, -29.9),
+                (100., -19.8),
+                (1_000., 0.),
+                (6_300., 12.2),
+                (10_000., 8.1),
+                (12_500., 0.),
+            ],
+        );
+    }
+
+    #[test]
+    fn tilt() {
+        let tilt = Weighting::Tilt {
+            db_per_octave: -4.5,
+        };
+
+        assert_gains(tilt, &[(250., 9.), (1_000., 0.), (2_000., -4.5)]);
+        assert!(tilt.gain(0.).is_finite());
+    }
+
+    #[test]
+    fn no_weighting() {
+        assert_eq!(Weighting::None.gain(0.), 1.);
+        assert_eq!(Weighting::None.gain(15_000.), 1.);
+    }
+}
@@ -0,0 +1,55 @@
+use std::{num::NonZero, ops::Range};
+
+/// The config options for [crate::BeatDetector].
+///
+/// Durations are measured in frames, where a frame is one call of [crate::BeatDetector::process_beats].
+#[derive(Debug, Clone)]
+pub struct BeatDetectorConfig {
+    /// The frequency bands (in Hz) which are watched for onsets independently of each other.
+    pub bands: Vec<Range<NonZero<u16>>>,
+
+    /// The amount of previous frames which are used to compute the adaptive threshold.
+    pub history: NonZero<usize>,
+
+    /// How far (in standard deviations) the spectral flux has to rise above its recent mean
+    /// to be detected as an onset.
+    /// The higher the value, the fewer onsets are detected.
+    pub sensitivity: f32,
+
+    /// The minimal spectral flux of an onset.
+    /// Prevents detecting onsets within (nearly) silent parts.
+    pub min_flux: f32,
+
+    /// The minimal amount of frames between two onsets of the same band.
+    pub cooldown: usize,
+
+    /// How much of the beat strength is kept for the next frame.
+    /// Must be within `[0, 1)`. The higher the value, the slower the beat strength fades out.
+    pub decay: f32,
+}
+
+impl Default for BeatDetectorConfig {
+    fn default() -> Self {
+        Self {
+            bands: default_bands(),
+            history: NonZero::new(43).unwrap(),
+            sensitivity: 1.5,
+            min_flux: 1e-3,
+            cooldown: 6,
+            decay: 0.85,
+        }
+    }
+}
+
+/// Returns the default value for [BeatDetectorConfig::bands]:
+///
+/// 1. Kick drum: `40..120` Hz
+/// 2. Snare drum: `150..2_500` Hz
+/// 3. Hi-hats and cymbals: `5_000..12_000` Hz
+pub fn default_bands() -> Vec<Range<NonZero<u16>>> {
+    vec![
+        NonZero::new(40).unwrap()..NonZero::new(120).unwrap(),
+        NonZero::new(150).unwrap()..NonZero::new(2_500).unwrap(),
+        NonZero::new(5_000).unwrap()..NonZero::new(12_000).unwrap(),
+    ]
+}
@@ -0,0 +1,308 @@
+mod config;
+
+use crate::{fetcher::Fetcher, SampleProcessor};
+use std::{collections::VecDeque, ops::Range};
+
+pub use config::*;
+
+/// The result of a frequency band of the [BeatDetector] for the latest frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BandBeat {
+    /// `true` if an onset has been detected within the latest frame.
+    pub is_onset: bool,
+
+    /// A continuous value within `[0, 1]` which jumps to `1` on an onset and fades out afterwards.
+    /// Useful to let something pulse to the beat.
+    pub strength: f32,
+
+    /// The raw spectral flux of the latest frame.
+    pub flux: f32,
+}
+
+/// Detects onsets (for example kick or snare drums) within the samples of the [SampleProcessor].
+///
+/// Each configured frequency band computes the spectral flux (the summed up increase of the magnitudes
+/// compared to the previous frame) and reports an onset if it exceeds an adaptive threshold which is
+/// based on the flux of the previous frames.
+/// All channels are mixed together.
+///
+/// # Example
+/// ```
+/// use vibe_audio::{SampleProcessor, BeatDetector, BeatDetectorConfig, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut beat_detector = BeatDetector::new(&sample_processor, BeatDetectorConfig::default());
+///
+/// sample_processor.process_next_samples();
+/// let beats = beat_detector.process_beats(&sample_processor);
+///
+/// // one result for each band
+/// assert_eq!(beats.len(), BeatDetectorConfig::default().bands.len());
+/// // the dummy fetcher is silent
+/// assert!(beats.iter().all(|beat| !beat.is_onset));
+/// ```
+pub struct BeatDetector {
+    bands: Box<[BandCtx]>,
+    beats: Box<[BandBeat]>,
+
+    // The magnitudes of the fft output, averaged over all channels
+    magnitudes: Box<[f32]>,
+    prev_magnitudes: Box<[f32]>,
+
+    config: BeatDetectorConfig,
+}
+
+impl BeatDetector {
+    /// Creates a new instance.
+    ///
+    /// # Panics
+    /// Panics if [BeatDetectorConfig::decay] isn't within `[0, 1)`.
+    pub fn new<F: Fetcher>(processor: &SampleProcessor<F>, config: BeatDetectorConfig) -> Self {
+        assert!(
+            (0. ..1.).contains(&config.decay),
+            "The decay of the beat detector ({}) must be within [0, 1).",
+            config.decay
+        );
+
+        let fft_size = processor.fft_size();
+        let fft_out_size = fft_size / 2 + 1;
+        let freq_resolution = processor.sample_rate() as f32 / fft_size as f32;
+
+        let bands = config
+            .bands
+            .iter()
+            .map(|band| {
+                let start = ((band.start.get() as f32 / freq_resolution) as usize)
+                    .clamp(1, fft_out_size - 1);
+                let end = ((band.end.get() as f32 / freq_resolution).ceil() as usize)
+                    .clamp(start + 1, fft_out_size);
+
+                BandCtx {
+                    bins: start..end,
+                    history: VecDeque::with_capacity(config.history.get()),
+                    frames_since_onset: usize::MAX,
+                }
+            })
+            .collect::<Box<[BandCtx]>>();
+
+        let beats = vec![BandBeat::default(); bands.len()].into_boxed_slice();
+        let magnitudes = vec![0f32; fft_out_size].into_boxed_slice();
+
+        Self {
+            bands,
+            beats,
+
+            prev_magnitudes: magnitudes.clone(),
+            magnitudes,
+
+            config,
+        }
+    }
+
+    /// Computes the beats of the latest samples of the processor.
+    ///
+    /// Returns the result of each band in the same order as in [BeatDetectorConfig::bands].
+    pub fn process_beats<F: Fetcher>(&mut self, processor: &SampleProcessor<F>) -> &[BandBeat] {
+        std::mem::swap(&mut self.magnitudes, &mut self.prev_magnitudes);
+        self.update_magnitudes(processor);
+
+        for (band, beat) in self.bands.iter_mut().zip(self.beats.iter_mut()) {
+            let bins = band.bins.clone();
+            let flux = self.magnitudes[bins.clone()]
+                .iter()
+                .zip(self.prev_magnitudes[bins].iter())
+                .map(|(mag, prev_mag)| (mag - prev_mag).max(0.))
+                .sum::<f32>()
+                / band.bins.len() as f32;
+
+            let (mean, std_dev) = band.statistics();
+            let threshold = (mean + self.config.sensitivity * std_dev).max(self.config.min_flux);
+
+            band.frames_since_onset = band.frames_since_onset.saturating_add(1);
+            let is_onset = flux > threshold && band.frames_since_onset > self.config.cooldown;
+            if is_onset {
+                band.frames_since_onset = 0;
+            }
+
+            let raw_strength = if flux > self.config.min_flux {
+                ((flux - mean) / (threshold - mean).max(self.config.min_flux)).clamp(0., 1.)
+            } else {
+                0.
+            };
+
+            *beat = BandBeat {
+                is_onset,
+                strength: raw_strength.max(beat.strength * self.config.decay),
+                flux,
+            };
+
+            band.push(flux, self.config.history.get());
+        }
+
+        &self.beats
+    }
+
+    /// Returns the results of the latest call of [BeatDetector::process_beats].
+    pub fn beats(&self) -> &[BandBeat] {
+        &self.beats
+    }
+
+    /// Returns the indices of the bands which detected an onset within the latest frame.
+    pub fn onsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.beats
+            .iter()
+            .enumerate()
+            .filter(|(_, beat)| beat.is_onset)
+            .map(|(band_idx, _)| band_idx)
+    }
+
+    /// Returns the current config of the beat detector.
+    pub fn config(&self) -> &BeatDetectorConfig {
+        &self.config
+    }
+
+    fn update_magnitudes<F: Fetcher>(&mut self, processor: &SampleProcessor<F>) {
+        let amount_channels = processor.fft_out().len();
+        let amount_bins = self.magnitudes.len();
+
+        self.magnitudes.fill(0.);
+        for channel_idx in 0..amount_channels {
+            for (magnitude, value) in self
+                .magnitudes
+                .iter_mut()
+                .zip(processor.magnitudes(channel_idx, 0..amount_bins))
+            {
+                *magnitude += value / amount_channels as f32;
+            }
+        }
+    }
+}
+
+/// The state of a frequency band of the [BeatDetector].
+struct BandCtx {
+    // the index range within the fft output
+    bins: Range<usize>,
+    // the flux of the previous frames
+    history: VecDeque<f32>,
+    frames_since_onset: usize,
+}
+
+impl BandCtx {
+    /// Returns the mean and the standard deviation of the flux history.
+    fn statistics(&self) -> (f32, f32) {
+        if self.history.is_empty() {
+            return (0., 0.);
+        }
+
+        let len = self.history.len() as f32;
+        let mean = self.history.iter().sum::<f32>() / len;
+        let variance = self
+            .history
+            .iter()
+            .map(|flux| (flux - mean).powi(2))
+            .sum::<f32>()
+            / len;
+
+        (mean, variance.sqrt())
+    }
+
+    fn push(&mut self, flux: f32, capacity: usize) {
+        if self.history.len() >= capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(flux);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fetcher::{Signal, SignalFetcher},
+        test_util::processor,
+    };
+
+    /// Returns the frames (per band) where an onset got detected.
+    fn onset_frames(
+        processor: &mut SampleProcessor<SignalFetcher>,
+        detector: &mut BeatDetector,
+        amount_frames: usize,
+    ) -> Vec<Vec<usize>> {
+        let mut onsets = vec![Vec::new(); detector.config().bands.len()];
+
+        for frame in 0..amount_frames {
+            processor.process_next_samples();
+            detector.process_beats(processor);
+
+            for band_idx in detector.onsets() {
+                onsets[band_idx].push(frame);
+            }
+        }
+
+        onsets
+    }
+
+    #[test]
+    fn silence_has_no_onsets() {
+        let mut processor = processor(vec![Signal::Silence]);
+        let mut detector = BeatDetector::new(&processor, BeatDetectorConfig::default());
+
+        let onsets = onset_frames(&mut processor, &mut detector, 120);
+
+        assert!(onsets.iter().all(|frames| frames.is_empty()));
+        assert!(detector.beats().iter().all(|beat| beat.strength == 0.));
+    }
+
+    #[test]
+    fn steady_tone_has_no_onsets_after_it_started() {
+        let mut processor = processor(vec![Signal::Sine {
+            frequency: 80.,
+            amplitude: 0.5,
+        }]);
+        let mut detector = BeatDetector::new(&processor, BeatDetectorConfig::default());
+
+        let onsets = onset_frames(&mut processor, &mut detector, 180);
+
+        // the start of the tone is allowed to be an onset
+        assert!(onsets[0].iter().all(|&frame| frame < 5), "{:?}", onsets);
+    }
+
+    #[test]
+    fn impulses_are_detected_on_each_beat() {
+        // 120 BPM at 60 frames per second => one beat every 30 frames
+        let mut processor = processor(vec![Signal::Impulses {
+            bpm: 120.,
+            amplitude: 1.,
+        }]);
+        let mut detector = BeatDetector::new(&processor, BeatDetectorConfig::default());
+
+        let onsets = onset_frames(&mut processor, &mut detector, 300);
+
+        for frames in onsets.iter() {
+            assert_eq!(frames.len(), 10, "{:?}", onsets);
+
+            for pair in frames.windows(2) {
+                assert!((29..=31).contains(&(pair[1] - pair[0])), "{:?}", onsets);
+            }
+        }
+    }
+
+    #[test]
+    fn strength_fades_out_after_an_onset() {
+        let mut processor = processor(vec![Signal::Impulses {
+            bpm: 60.,
+            amplitude: 1.,
+        }]);
+        let mut detector = BeatDetector::new(&processor, BeatDetectorConfig::default());
+
+        let mut strengths = Vec::new();
+        for _ in 0..20 {
+            processor.process_next_samples();
+            strengths.push(detector.process_beats(&processor)[2].strength);
+        }
+
+        assert_eq!(strengths[0], 1.);
+        assert!(strengths[10] < 0.5);
+        assert!(strengths.windows(2).skip(2).all(|pair| pair[1] <= pair[0]));
+    }
+}
@@ -0,0 +1,61 @@
+use std::{num::NonZero, ops::Range, time::Duration};
+
+/// The config options for [crate::ChromaExtractor].
+#[derive(Debug, Clone)]
+pub struct ChromaExtractorConfig {
+    /// The frequency range (in Hz) which is folded into the pitch classes.
+    ///
+    /// Frequencies whose fft bins are wider than a semitone are skipped regardless of the range,
+    /// so a bigger [crate::SampleProcessorConfig::window_len] is needed to include the lower notes.
+    pub freq_range: Range<NonZero<u16>>,
+
+    /// The frequency (in Hz) of the concert pitch A4 which the pitch classes are aligned to.
+    pub reference_pitch: f32,
+
+    /// Estimate how far the audio is tuned away from [ChromaExtractorConfig::reference_pitch]
+    /// and shift the pitch classes accordingly.
+    ///
+    /// Helps with recordings which aren't tuned to 440 Hz, but needs a few seconds of tonal audio to settle.
+    pub estimate_tuning: bool,
+
+    /// The time constant of the smoothing of the pitch classes.
+    /// The longer the duration, the slower the chroma follows chord changes.
+    /// [Duration::ZERO] disables the smoothing.
+    pub smoothing: Duration,
+
+    /// Decides how the values of the pitch classes are scaled.
+    pub normalization: ChromaNormalization,
+}
+
+impl Default for ChromaExtractorConfig {
+    fn default() -> Self {
+        Self {
+            freq_range: default_chroma_freq_range(),
+            reference_pitch: 440.,
+            estimate_tuning: false,
+            smoothing: Duration::from_millis(150),
+            normalization: ChromaNormalization::default(),
+        }
+    }
+}
+
+/// Decides how [crate::ChromaExtractor] scales the values of the pitch classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ChromaNormalization {
+    /// The strongest pitch class gets the value `1`.
+    #[default]
+    Max,
+
+    /// The values of all pitch classes add up to `1`.
+    Sum,
+
+    /// Keep the energy of the pitch classes.
+    /// The values depend on the loudness of the audio.
+    None,
+}
+
+/// Returns the default value for [ChromaExtractorConfig::freq_range]:
+/// From A1 (`55` Hz) up to the end of the 7th octave (`4_000` Hz).
+pub fn default_chroma_freq_range() -> Range<NonZero<u16>> {
+    NonZero::new(55).unwrap()..NonZero::new(4_000).unwrap()
+}
@@ -0,0 +1,419 @@
+mod config;
+
+use crate::{fetcher::Fetcher, SampleProcessor};
+use realfft::num_complex::Complex32;
+use std::{f32::consts::TAU, ops::Range, time::Duration};
+
+pub use config::*;
+
+/// The amount of pitch classes within an octave.
+pub const AMOUNT_PITCH_CLASSES: usize = 12;
+
+/// The names of the pitch classes in the same order as within a [Chroma].
+pub const PITCH_CLASS_NAMES: [&str; AMOUNT_PITCH_CLASSES] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// The values of the pitch classes of a channel, starting with C (see [PITCH_CLASS_NAMES]).
+pub type Chroma = [f32; AMOUNT_PITCH_CLASSES];
+
+/// The distance between the bins `k` and `k + 1` is a ratio of `(k + 1) / k`,
+/// which is smaller than a semitone (`2^(1/12) ≈ 1.0595`) from this bin on.
+const MIN_BIN: usize = 17;
+
+/// The index of A within a [Chroma].
+const A_PITCH_CLASS: i32 = 9;
+
+/// The (normalized) energy below which a channel counts as silent and doesn't get normalized.
+const MIN_ENERGY: f32 = 1e-8;
+
+/// Peaks which are weaker than this ratio of the strongest peak are ignored by the tuning estimation.
+const MIN_PEAK_RATIO: f32 = 0.1;
+
+/// The time constant of the tuning estimation.
+const TUNING_TIME_CONSTANT: Duration = Duration::from_secs(5);
+
+/// Folds the spectrum of the [SampleProcessor] into the 12 pitch classes (also known as chromagram).
+///
+/// The energy of all octaves of a note is summed up, so the result describes which notes are
+/// currently played, regardless of the octave. Useful to react to the key or the current chord.
+///
+/// # Example
+/// ```
+/// use vibe_audio::{SampleProcessor, ChromaExtractor, ChromaExtractorConfig, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut chroma_extractor = ChromaExtractor::new(&sample_processor, ChromaExtractorConfig::default());
+///
+/// sample_processor.process_next_samples();
+/// let chroma = chroma_extractor.process_chroma(&sample_processor);
+///
+/// // one result for each channel
+/// assert_eq!(chroma.len(), 2);
+/// // the dummy fetcher is silent
+/// assert!(chroma[0].iter().all(|&value| value == 0.));
+/// ```
+pub struct ChromaExtractor {
+    // the fft bins which are folded into the pitch classes
+    bins: Range<usize>,
+    // the distance (in semitones) of each bin of `bins` to the reference pitch
+    semitones: Box<[f32]>,
+    // reused buffer for the magnitudes of a channel
+    magnitudes: Box<[f32]>,
+
+    chroma: Box<[Chroma]>,
+
+    // the sum of the deviations of the spectral peaks from the closest semitone, as weighted unit vectors
+    deviations: Complex32,
+    // the estimated tuning in semitones
+    tuning: f32,
+
+    sample_rate: f32,
+    fft_size: usize,
+
+    config: ChromaExtractorConfig,
+}
+
+impl ChromaExtractor {
+    /// Creates a new instance.
+    ///
+    /// # Panics
+    /// Panics if [ChromaExtractorConfig::reference_pitch] isn't positive.
+    pub fn new<F: Fetcher>(processor: &SampleProcessor<F>, config: ChromaExtractorConfig) -> Self {
+        assert!(
+            config.reference_pitch > 0.,
+            "The reference pitch of the chroma extractor ({}) must be positive.",
+            config.reference_pitch
+        );
+
+        let sample_rate = processor.sample_rate() as f32;
+        let fft_size = processor.fft_size();
+        let fft_out_size = fft_size / 2 + 1;
+        let freq_resolution = sample_rate / fft_size as f32;
+
+        let start = ((config.freq_range.start.get() as f32 / freq_resolution).ceil() as usize)
+            .clamp(MIN_BIN, fft_out_size);
+        let end = ((config.freq_range.end.get() as f32 / freq_resolution) as usize + 1)
+            .clamp(start, fft_out_size);
+        let bins = start..end;
+
+        let semitones = bins
+            .clone()
+            .map(|bin| 12. * (bin as f32 * freq_resolution / config.reference_pitch).log2())
+            .collect();
+
+        let amount_channels = processor.fft_out().len();
+
+        Self {
+            bins,
+            semitones,
+            magnitudes: vec![0f32; fft_out_size].into_boxed_slice(),
+
+            chroma: vec![Chroma::default(); amount_channels].into_boxed_slice(),
+
+            deviations: Complex32::ZERO,
+            tuning: 0.,
+
+            sample_rate,
+            fft_size,
+
+            config,
+        }
+    }
+
+    /// Computes the pitch classes of the latest samples of the processor.
+    ///
+    /// Returns the chroma of each channel.
+    pub fn process_chroma<F: Fetcher>(&mut self, processor: &SampleProcessor<F>) -> &[Chroma] {
+        let elapsed = processor.amount_new_frames() as f32 / self.sample_rate;
+        let smoothing = decay(elapsed, self.config.smoothing);
+
+        if self.config.estimate_tuning {
+            self.deviations *= decay(elapsed, TUNING_TIME_CONSTANT);
+        }
+
+        let amount_bins = self.magnitudes.len();
+        for (channel_idx, chroma) in self.chroma.iter_mut().enumerate() {
+            for (magnitude, value) in self
+                .magnitudes
+                .iter_mut()
+                .zip(processor.magnitudes(channel_idx, 0..amount_bins))
+            {
+                *magnitude = value;
+            }
+
+            if self.config.estimate_tuning {
+                self.deviations += peak_deviations(
+                    &self.magnitudes,
+                    self.bins.clone(),
+                    &self.semitones,
+                    self.sample_rate / self.fft_size as f32,
+                    self.config.reference_pitch,
+                );
+            }
+
+            let mut energies = Chroma::default();
+            for (magnitude, semitones) in self.magnitudes[self.bins.clone()]
+                .iter()
+                .zip(self.semitones.iter())
+            {
+                energies[pitch_class(semitones - self.tuning)] += magnitude * magnitude;
+            }
+            normalize(&mut energies, self.config.normalization);
+
+            for (value, energy) in chroma.iter_mut().zip(energies) {
+                *value = smoothing * *value + (1. - smoothing) * energy;
+            }
+        }
+
+        if self.config.estimate_tuning && self.deviations != Complex32::ZERO {
+            self.tuning = self.deviations.arg() / TAU;
+        }
+
+        &self.chroma
+    }
+
+    /// Returns the results of the latest call of [ChromaExtractor::process_chroma].
+    pub fn chroma(&self) -> &[Chroma] {
+        &self.chroma
+    }
+
+    /// Returns the estimated deviation (in cents) of the tuning of the audio from
+    /// [ChromaExtractorConfig::reference_pitch].
+    ///
+    /// Always `0` if [ChromaExtractorConfig::estimate_tuning] is disabled.
+    pub fn tuning(&self) -> f32 {
+        self.tuning * 100.
+    }
+
+    /// Returns the current config of the chroma extractor.
+    pub fn config(&self) -> &ChromaExtractorConfig {
+        &self.config
+    }
+}
+
+/// Returns the index of the pitch class of the given distance (in semitones) to A4.
+fn pitch_class(semitones: f32) -> usize {
+    (semitones.round() as i32 + A_PITCH_CLASS).rem_euclid(AMOUNT_PITCH_CLASSES as i32) as usize
+}
+
+/// Returns how much of a smoothed value is kept after `elapsed` seconds.
+fn decay(elapsed: f32, time_constant: Duration) -> f32 {
+    if time_constant.is_zero() {
+        0.
+    } else {
+        (-elapsed / time_constant.as_secs_f32()).exp()
+    }
+}
+
+fn normalize(energies: &mut Chroma, normalization: ChromaNormalization) {
+    let total = energies.iter().sum::<f32>();
+    if total < MIN_ENERGY {
+        energies.fill(0.);
+        return;
+    }
+
+    let divisor = match normalization {
+        ChromaNormalization::Max => energies.iter().copied().fold(0., f32::max),
+        ChromaNormalization::Sum => total,
+        ChromaNormalization::None => 1.,
+    };
+
+    for energy in energies.iter_mut() {
+        *energy /= divisor;
+    }
+}
+
+/// Returns the sum of the deviations of the spectral peaks from their closest semitone,
+/// each as a unit vector (a full turn equals one semitone) weighted by the magnitude of the peak.
+///
+/// Averaging the vectors instead of the deviations themselves handles peaks which are
+/// roughly half a semitone away from two notes.
+fn peak_deviations(
+    magnitudes: &[f32],
+    bins: Range<usize>,
+    semitones: &[f32],
+    freq_resolution: f32,
+    reference_pitch: f32,
+) -> Complex32 {
+    let max_magnitude = magnitudes[bins.clone()].iter().copied().fold(0., f32::max);
+    if max_magnitude * max_magnitude < MIN_ENERGY {
+        return Complex32::ZERO;
+    }
+
+    let mut deviations = Complex32::ZERO;
+    for (bin, bin_semitones) in bins.zip(semitones) {
+        let Some(&[left, peak, right]) = magnitudes.get(bin - 1..bin + 2) else {
+            continue;
+        };
+
+        if peak <= left || peak < right || peak < max_magnitude * MIN_PEAK_RATIO {
+            continue;
+        }
+
+        // the peaks of the window functions are roughly gaussian,
+        // so a parabola through the log magnitudes finds the actual frequency
+        let [left, center, right] =
+            [left, peak, right].map(|magnitude| magnitude.max(f32::MIN_POSITIVE).ln());
+        let offset = 0.5 * (left - right) / (left - 2. * center + right);
+
+        let semitones = if offset.is_finite() {
+            12. * ((bin as f32 + offset) * freq_resolution / reference_pitch).log2()
+        } else {
+            *bin_semitones
+        };
+
+        deviations += Complex32::from_polar(peak, TAU * (semitones - semitones.round()));
+    }
+
+    deviations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fetcher::{Signal, SignalFetcher},
+        test_util, SampleProcessorConfig,
+    };
+    use std::num::NonZero;
+
+    fn processor(frequencies: &[f32]) -> SampleProcessor<SignalFetcher> {
+        let signals = frequencies
+            .iter()
+            .map(|&frequency| Signal::Sine {
+                frequency,
+                amplitude: 0.3,
+            })
+            .collect();
+
+        test_util::processor_with_config(
+            signals,
+            SampleProcessorConfig {
+                window_len: NonZero::new(4096),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Processes the given amount of steps and returns the chroma of each step.
+    fn extract(
+        frequencies: &[f32],
+        config: ChromaExtractorConfig,
+        amount_steps: usize,
+    ) -> (ChromaExtractor, Vec<Chroma>) {
+        let mut processor = processor(frequencies);
+        let mut extractor = ChromaExtractor::new(&processor, config);
+
+        let mut steps = Vec::new();
+        for _ in 0..amount_steps {
+            processor.process_next_samples();
+            steps.push(extractor.process_chroma(&processor)[0]);
+        }
+
+        (extractor, steps)
+    }
+
+    fn unsmoothed() -> ChromaExtractorConfig {
+        ChromaExtractorConfig {
+            smoothing: Duration::ZERO,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the pitch classes sorted by their value, the strongest one first.
+    fn ranking(chroma: &Chroma) -> Vec<usize> {
+        let mut pitch_classes: Vec<usize> = (0..AMOUNT_PITCH_CLASSES).collect();
+        pitch_classes.sort_by(|&a, &b| chroma[b].total_cmp(&chroma[a]));
+        pitch_classes
+    }
+
+    #[test]
+    fn silence() {
+        let (extractor, steps) = extract(&[], ChromaExtractorConfig::default(), 10);
+
+        assert!(steps.iter().flatten().all(|&value| value == 0.));
+        assert_eq!(extractor.tuning(), 0.);
+    }
+
+    #[test]
+    fn sine_is_folded_into_its_pitch_class() {
+        // A4 and A5
+        for frequency in [440., 880.] {
+            let (_, steps) = extract(&[frequency], unsmoothed(), 10);
+            let chroma = steps.last().unwrap();
+
+            assert_eq!(chroma[9], 1.);
+            for (pitch_class, &value) in chroma.iter().enumerate() {
+                if pitch_class != 9 {
+                    assert!(value < 0.1, "{}: {:?}", frequency, chroma);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn chord() {
+        // C major: C5, E5, G5
+        let (_, steps) = extract(&[523.25, 659.26, 783.99], unsmoothed(), 10);
+
+        let mut strongest = ranking(steps.last().unwrap())[..3].to_vec();
+        strongest.sort();
+        assert_eq!(strongest, [0, 4, 7]);
+    }
+
+    #[test]
+    fn sum_normalization() {
+        let (_, steps) = extract(
+            &[523.25, 659.26],
+            ChromaExtractorConfig {
+                normalization: ChromaNormalization::Sum,
+                ..unsmoothed()
+            },
+            10,
+        );
+
+        let sum = steps.last().unwrap().iter().sum::<f32>();
+        assert!((sum - 1.).abs() < 1e-4, "{}", sum);
+    }
+
+    #[test]
+    fn smoothing_fades_in() {
+        let (_, steps) = extract(&[440.], ChromaExtractorConfig::default(), 60);
+
+        assert!(steps[0][9] < 0.2, "{:?}", steps[0]);
+        assert!(steps.windows(2).all(|pair| pair[1][9] >= pair[0][9]));
+        assert!(steps.last().unwrap()[9] > 0.99);
+    }
+
+    #[test]
+    fn estimates_the_tuning() {
+        // 40 cents above A4, the leakage would reach into A# otherwise
+        let frequency = 440. * 2f32.powf(0.4 / 12.);
+        let (extractor, steps) = extract(
+            &[frequency],
+            ChromaExtractorConfig {
+                estimate_tuning: true,
+                ..unsmoothed()
+            },
+            60,
+        );
+        let chroma = steps.last().unwrap();
+
+        assert!(
+            (extractor.tuning() - 40.).abs() < 3.,
+            "{}",
+            extractor.tuning()
+        );
+        assert_eq!(chroma[9], 1.);
+        assert!(chroma[10] < 0.1, "{:?}", chroma);
+    }
+
+    #[test]
+    fn tuning_is_not_estimated_by_default() {
+        let frequency = 440. * 2f32.powf(0.4 / 12.);
+        let (extractor, _) = extract(&[frequency], ChromaExtractorConfig::default(), 10);
+
+        assert_eq!(extractor.tuning(), 0.);
+    }
+}
@@ -0,0 +1,509 @@
+use super::{
+    pacing::{FrameSource, Worker},
+    Fetcher, Pacing, SampleBuffer,
+};
+use cpal::SampleRate;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use symphonia::core::{
+    audio::SampleBuffer as DecodedBuffer,
+    codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL},
+    errors::Error as SymphoniaError,
+    formats::{FormatOptions, FormatReader, SeekMode, SeekTo},
+    io::MediaSourceStream,
+    meta::MetadataOptions,
+    probe::Hint,
+};
+use tracing::{debug, error, warn};
+
+/// Errors which can occur while creating [crate::fetcher::FileFetcher].
+#[derive(thiserror::Error, Debug)]
+pub enum AudioFileError {
+    #[error("Couldn't open the audio file: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("Couldn't decode the audio file: {0}")]
+    Decode(#[from] SymphoniaError),
+
+    /// The file doesn't contain any track which can be decoded.
+    #[error("The audio file doesn't contain any decodable audio track.")]
+    NoAudioTrack,
+
+    #[error("The audio track doesn't provide its sample rate.")]
+    UnknownSampleRate,
+
+    #[error("The audio track doesn't provide its amount of channels.")]
+    UnknownChannels,
+}
+
+pub struct Descriptor {
+    /// The path to the audio file.
+    /// WAV, FLAC and OGG (vorbis) files are supported.
+    pub path: PathBuf,
+
+    pub pacing: Pacing,
+
+    /// Start from the beginning again as soon as the end of the file is reached.
+    /// Otherwise silence is pushed after the end of the file.
+    pub looping: bool,
+}
+
+/// Fetcher for local audio files.
+pub struct AudioFile {
+    sample_buffer: SampleBuffer,
+    channels: u16,
+    duration: Option<Duration>,
+
+    decoder: Arc<Mutex<FileDecoder>>,
+    pacing: Pacing,
+    // reused buffer for the samples which are pushed in offline mode
+    frames: Vec<f32>,
+
+    // only set for `Pacing::RealTime`
+    worker: Option<Worker>,
+}
+
+impl AudioFile {
+    pub fn new(desc: &Descriptor) -> Result<Self, AudioFileError> {
+        let decoder = FileDecoder::new(desc)?;
+
+        let sample_rate = decoder.sample_rate;
+        let channels = decoder.channels;
+        let duration = decoder
+            .amount_frames
+            .map(|frames| Duration::from_secs_f64(frames as f64 / sample_rate as f64));
+
+        debug!(
+            "Audio file: {} channels, {} Hz, duration: {:?}",
+            channels, sample_rate, duration
+        );
+
+        let sample_buffer = SampleBuffer::new(sample_rate);
+        let decoder = Arc::new(Mutex::new(decoder));
+
+        let worker = match desc.pacing {
+            Pacing::RealTime => Some(Worker::spawn(
+                decoder.clone(),
+                sample_buffer.clone(),
+                sample_rate,
+            )),
+            Pacing::Offline { .. } => None,
+        };
+
+        Ok(Self {
+            sample_buffer,
+            channels,
+            duration,
+
+            decoder,
+            pacing: desc.pacing,
+            frames: Vec::new(),
+
+            worker,
+        })
+    }
+
+    /// Jumps to the given position within the file.
+    pub fn seek(&mut self, position: Duration) -> Result<(), AudioFileError> {
+        self.decoder
+            .lock()
+            .unwrap()
+            .seek(position)
+            .map_err(AudioFileError::from)
+    }
+
+    /// Returns the duration of the file (if the file provides it).
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Returns `true` if the end of the file has been reached and looping is disabled.
+    pub fn is_finished(&self) -> bool {
+        self.decoder.lock().unwrap().is_finished
+    }
+}
+
+impl Fetcher for AudioFile {
+    fn sample_buffer(&self) -> SampleBuffer {
+        self.sample_buffer.clone()
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn advance(&mut self) {
+        if let Pacing::Offline { frames_per_step } = self.pacing {
+            self.decoder
+                .lock()
+                .unwrap()
+                .read(frames_per_step.get(), &mut self.frames);
+
+            self.sample_buffer.push(&self.frames);
+        }
+    }
+}
+
+impl Drop for AudioFile {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            worker.stop();
+        }
+    }
+}
+
+/// Decodes the audio file into interleaved `f32` samples.
+struct FileDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+
+    sample_rate: SampleRate,
+    channels: u16,
+    amount_frames: Option<u64>,
+
+    looping: bool,
+    is_finished: bool,
+    // Prevents looping forever over a file without any samples.
+    decoded_since_seek: bool,
+    // The amount of frames which still need to be dropped after an accurate seek.
+    skip_frames: u64,
+
+    decoded: Option<DecodedBuffer<f32>>,
+    // decoded samples which haven't been read yet
+    pending: Vec<f32>,
+}
+
+impl FrameSource for FileDecoder {
+    /// Writes the next `amount_frames` frames into `out`.
+    /// Everything after the end of the file is filled up with silence.
+    fn read(&mut self, amount_frames: usize, out: &mut Vec<f32>) {
+        let amount_samples = amount_frames * self.channels as usize;
+
+        out.clear();
+        while out.len() < amount_samples {
+            if self.pending.is_empty() && !self.decode_next_packet() {
+                out.resize(amount_samples, 0.);
+                break;
+            }
+
+            let amount = (amount_samples - out.len()).min(self.pending.len());
+            out.extend(self.pending.drain(..amount));
+        }
+    }
+}
+
+impl FileDecoder {
+    fn new(desc: &Descriptor) -> Result<Self, AudioFileError> {
+        let file = std::fs::File::open(&desc.path)?;
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(extension) = desc.path.extension().and_then(|ext| ext.to_str()) {
+            hint.with_extension(extension);
+        }
+
+        let format = symphonia::default::get_probe()
+            .format(
+                &hint,
+                stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )?
+            .format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or(AudioFileError::NoAudioTrack)?;
+
+        let sample_rate = track
+            .codec_params
+            .sample_rate
+            .ok_or(AudioFileError::UnknownSampleRate)?;
+        let channels = track
+            .codec_params
+            .channels
+            .ok_or(AudioFileError::UnknownChannels)?
+            .count() as u16;
+        let amount_frames = track.codec_params.n_frames;
+        let track_id = track.id;
+
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())?;
+
+        Ok(Self {
+            format,
+            decoder,
+            track_id,
+
+            sample_rate,
+            channels,
+            amount_frames,
+
+            looping: desc.looping,
+            is_finished: false,
+            decoded_since_seek: false,
+            skip_frames: 0,
+
+            decoded: None,
+            pending: Vec::new(),
+        })
+    }
+
+    fn seek(&mut self, position: Duration) -> Result<(), SymphoniaError> {
+        let seeked_to = self.format.seek(
+            SeekMode::Accurate,
+            SeekTo::Time {
+                time: position.into(),
+                track_id: Some(self.track_id),
+            },
+        )?;
+
+        self.decoder.reset();
+        self.pending.clear();
+        self.skip_frames = seeked_to.required_ts.saturating_sub(seeked_to.actual_ts);
+        self.is_finished = false;
+        self.decoded_since_seek = false;
+
+        Ok(())
+    }
+
+    /// Decodes the next packet into `pending`.
+    /// Returns `false` if there's nothing left to decode.
+    fn decode_next_packet(&mut self) -> bool {
+        if self.is_finished {
+            return false;
+        }
+
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(err))
+                    if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    if self.looping && self.decoded_since_seek {
+                        if let Err(err) = self.seek(Duration::ZERO) {
+                            error!("Couldn't jump back to the start of the audio file: {}", err);
+                            self.is_finished = true;
+                            return false;
+                        }
+
+                        continue;
+                    }
+
+                    self.is_finished = true;
+                    return false;
+                }
+                Err(err) => {
+                    error!("Couldn't read the next packet of the audio file: {}", err);
+                    self.is_finished = true;
+                    return false;
+                }
+            };
+
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(SymphoniaError::DecodeError(err)) => {
+                    warn!("Skipping corrupted packet of the audio file: {}", err);
+                    continue;
+                }
+                Err(err) => {
+                    error!("Couldn't decode the audio file: {}", err);
+                    self.is_finished = true;
+                    return false;
+                }
+            };
+
+            let buffer = match &mut self.decoded {
+                Some(buffer) if buffer.capacity() >= decoded.capacity() => buffer,
+                buffer => buffer.insert(DecodedBuffer::new(
+                    decoded.capacity() as u64,
+                    *decoded.spec(),
+                )),
+            };
+            buffer.copy_interleaved_ref(decoded);
+            self.decoded_since_seek = true;
+
+            let channels = self.channels as usize;
+            let samples = buffer.samples();
+            let skip_samples = (self.skip_frames as usize * channels).min(samples.len());
+            self.skip_frames -= (skip_samples / channels) as u64;
+
+            self.pending.extend_from_slice(&samples[skip_samples..]);
+            if !self.pending.is_empty() {
+                return true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::pacing::REAL_TIME_TICK;
+    use std::{num::NonZero, path::Path};
+
+    /// A file in the temp directory which gets removed when it's dropped.
+    struct TempFile(PathBuf);
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    impl std::ops::Deref for TempFile {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    /// Writes the given samples as a 16-bit PCM wav file into the temp directory.
+    fn write_wav(name: &str, sample_rate: u32, channels: u16, samples: &[i16]) -> TempFile {
+        let path =
+            std::env::temp_dir().join(format!("vibe-audio-{}-{}.wav", name, std::process::id()));
+
+        let data_len = (samples.len() * 2) as u32;
+        let block_align = channels * 2;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+        bytes.extend_from_slice(&block_align.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        std::fs::write(&path, bytes).unwrap();
+        TempFile(path)
+    }
+
+    fn offline_fetcher(path: &Path, frames_per_step: usize, looping: bool) -> AudioFile {
+        AudioFile::new(&Descriptor {
+            path: path.to_path_buf(),
+            pacing: Pacing::Offline {
+                frames_per_step: NonZero::new(frames_per_step).unwrap(),
+            },
+            looping,
+        })
+        .unwrap()
+    }
+
+    // `i16` values which can be converted to `f32` without any precision loss
+    fn sample(value: i16) -> i16 {
+        value * 256
+    }
+
+    fn expected(value: i16) -> f32 {
+        value as f32 / 128.
+    }
+
+    fn newest_samples(fetcher: &AudioFile, amount: usize) -> Vec<f32> {
+        let mut samples = vec![0f32; amount];
+        fetcher.sample_buffer().snapshot(&mut samples);
+        samples
+    }
+
+    #[test]
+    fn reports_channels_and_sample_rate() {
+        let path = write_wav("metadata", 22_050, 1, &[0; 64]);
+        let fetcher = offline_fetcher(&path, 16, false);
+
+        assert_eq!(fetcher.channels(), 1);
+        assert_eq!(fetcher.sample_rate(), 22_050);
+        assert_eq!(
+            fetcher.duration(),
+            Some(Duration::from_secs_f64(64. / 22_050.))
+        );
+    }
+
+    #[test]
+    fn offline_pushes_frames_per_step() {
+        let samples: Vec<i16> = (0..16).map(sample).collect();
+        let path = write_wav("offline", 44_100, 2, &samples);
+        let mut fetcher = offline_fetcher(&path, 4, false);
+
+        fetcher.advance();
+        let expected_samples: Vec<f32> = (0..8).map(expected).collect();
+        assert_eq!(newest_samples(&fetcher, 8), expected_samples);
+
+        fetcher.advance();
+        let expected_samples: Vec<f32> = (8..16).map(expected).collect();
+        assert_eq!(newest_samples(&fetcher, 8), expected_samples);
+    }
+
+    #[test]
+    fn silence_after_the_end() {
+        let samples: Vec<i16> = (1..=4).map(sample).collect();
+        let path = write_wav("silence", 44_100, 1, &samples);
+        let mut fetcher = offline_fetcher(&path, 8, false);
+
+        fetcher.advance();
+
+        let mut expected_samples: Vec<f32> = (1..=4).map(expected).collect();
+        expected_samples.extend([0.; 4]);
+        assert_eq!(newest_samples(&fetcher, 8), expected_samples);
+        assert!(fetcher.is_finished());
+    }
+
+    #[test]
+    fn looping_starts_from_the_beginning() {
+        let samples: Vec<i16> = (1..=4).map(sample).collect();
+        let path = write_wav("looping", 44_100, 1, &samples);
+        let mut fetcher = offline_fetcher(&path, 10, true);
+
+        fetcher.advance();
+
+        let expected_samples: Vec<f32> = (1..=4).cycle().take(10).map(expected).collect();
+        assert_eq!(newest_samples(&fetcher, 10), expected_samples);
+        assert!(!fetcher.is_finished());
+    }
+
+    #[test]
+    fn seek() {
+        let samples: Vec<i16> = (0..100).map(sample).collect();
+        let path = write_wav("seek", 1_000, 1, &samples);
+        let mut fetcher = offline_fetcher(&path, 4, false);
+
+        fetcher.seek(Duration::from_millis(50)).unwrap();
+        fetcher.advance();
+
+        let expected_samples: Vec<f32> = (50..54).map(expected).collect();
+        assert_eq!(newest_samples(&fetcher, 4), expected_samples);
+    }
+
+    #[test]
+    fn real_time_pushes_samples() {
+        let path = write_wav("real-time", 44_100, 1, &[sample(1); 44_100]);
+        let fetcher = AudioFile::new(&Descriptor {
+            path: path.to_path_buf(),
+            pacing: Pacing::RealTime,
+            looping: false,
+        })
+        .unwrap();
+
+        std::thread::sleep(REAL_TIME_TICK * 5);
+
+        assert!(newest_samples(&fetcher, 1) == [expected(1)]);
+    }
+}
@@ -1,11 +1,9 @@
-use std::sync::{Arc, Mutex};
-
 use super::{Fetcher, SampleBuffer};
 
 /// A dummy fetcher which does... nothing.
 /// Mainly used for docs and tests.
 pub struct DummyFetcher {
-    sample_buffer: Arc<Mutex<SampleBuffer>>,
+    sample_buffer: SampleBuffer,
 
     amount_channels: u16,
 }
@@ -14,14 +12,14 @@ impl DummyFetcher {
     /// Creates a new instance of this struct.
     pub fn new(amount_channels: u16) -> Self {
         Self {
-            sample_buffer: Arc::new(Mutex::new(SampleBuffer::new(44_100))),
+            sample_buffer: SampleBuffer::new(44_100),
             amount_channels,
         }
     }
 }
 
 impl Fetcher for DummyFetcher {
-    fn sample_buffer(&self) -> Arc<Mutex<SampleBuffer>> {
+    fn sample_buffer(&self) -> SampleBuffer {
         self.sample_buffer.clone()
     }
 
@@ -0,0 +1,331 @@
+use super::{pcm::PcmDecoder, Fetcher, PcmFormat, SampleBuffer};
+use cpal::SampleRate;
+use std::{
+    io::Read,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::{debug, info, warn};
+
+/// The time to wait before trying to open the fifo again if opening it failed.
+const REOPEN_DELAY: Duration = Duration::from_secs(1);
+
+/// Errors which can occur while creating [crate::fetcher::FifoFetcher].
+#[derive(thiserror::Error, Debug)]
+pub enum FifoError {
+    #[error("Couldn't access the fifo: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("The amount of channels must be at least one.")]
+    NoChannels,
+
+    #[error("The sample rate must be greater than zero.")]
+    InvalidSampleRate,
+
+    #[error("'{0}' isn't a fifo. Create one with `mkfifo`.")]
+    NotAFifo(PathBuf),
+}
+
+/// The source where the raw PCM data is read from.
+pub enum Source {
+    /// The path to a named pipe (for example the fifo output of MPD or snapclient).
+    ///
+    /// The fifo gets reopened if the writer disconnects, so the writer can come and go as it wants.
+    Path(PathBuf),
+
+    /// Read from stdin, for example: `parec --raw | <your program>`.
+    ///
+    /// Silence is pushed as soon as stdin is closed.
+    Stdin,
+
+    /// Any other reader.
+    ///
+    /// Silence is pushed as soon as the reader reaches its end.
+    Reader(Box<dyn Read + Send>),
+}
+
+pub struct Descriptor {
+    pub source: Source,
+    pub format: PcmFormat,
+    pub sample_rate: SampleRate,
+    pub amount_channels: u16,
+}
+
+/// Fetcher which reads raw, interleaved PCM data from a fifo, stdin or any other reader.
+pub struct Fifo {
+    sample_buffer: SampleBuffer,
+    channels: u16,
+
+    is_running: Arc<AtomicBool>,
+}
+
+impl Fifo {
+    pub fn new(desc: Descriptor) -> Result<Self, FifoError> {
+        if desc.amount_channels == 0 {
+            return Err(FifoError::NoChannels);
+        }
+
+        if desc.sample_rate == 0 {
+            return Err(FifoError::InvalidSampleRate);
+        }
+
+        // catch typos early instead of waiting for a fifo which will never appear
+        if let Source::Path(path) = &desc.source {
+            // a regular file would be read again and again without any delay
+            if !is_fifo(&std::fs::metadata(path)?) {
+                return Err(FifoError::NotAFifo(path.clone()));
+            }
+        }
+
+        let sample_buffer = SampleBuffer::new(desc.sample_rate);
+        let is_running = Arc::new(AtomicBool::new(true));
+
+        let reader = PcmReader {
+            decoder: PcmDecoder::new(desc.format, desc.amount_channels),
+            bytes: {
+                // push roughly half of the sample buffer at once
+                let frame_size = desc.format.bytes_per_sample() * desc.amount_channels as usize;
+                let amount_frames =
+                    (sample_buffer.capacity() / 2 / desc.amount_channels as usize).max(1);
+
+                vec![0u8; amount_frames * frame_size].into_boxed_slice()
+            },
+            sample_buffer: sample_buffer.clone(),
+            is_running: is_running.clone(),
+        };
+
+        // The thread isn't joined since it might block while waiting for a writer.
+        // It stops on its own as soon as it notices that the fetcher got dropped.
+        std::thread::spawn(move || reader.run(desc.source));
+
+        Ok(Self {
+            sample_buffer,
+            channels: desc.amount_channels,
+            is_running,
+        })
+    }
+}
+
+impl Fetcher for Fifo {
+    fn sample_buffer(&self) -> SampleBuffer {
+        self.sample_buffer.clone()
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+}
+
+impl Drop for Fifo {
+    fn drop(&mut self) {
+        self.is_running.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(unix)]
+fn is_fifo(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    metadata.file_type().is_fifo()
+}
+
+// there's no portable way to check for a named pipe
+#[cfg(not(unix))]
+fn is_fifo(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
+
+/// Reads the PCM data within its own thread and pushes it into the sample buffer.
+struct PcmReader {
+    decoder: PcmDecoder,
+    bytes: Box<[u8]>,
+
+    sample_buffer: SampleBuffer,
+    is_running: Arc<AtomicBool>,
+}
+
+impl PcmReader {
+    fn run(mut self, source: Source) {
+        match source {
+            Source::Path(path) => {
+                while self.is_running.load(Ordering::Relaxed) {
+                    // blocks until a writer opens the fifo
+                    match std::fs::File::open(&path) {
+                        Ok(file) => {
+                            info!("Reading audio from '{}'.", path.to_string_lossy());
+                            self.read_until_end(file);
+                            info!("Writer of '{}' disconnected.", path.to_string_lossy());
+                        }
+                        Err(err) => {
+                            warn!("Couldn't open '{}': {}", path.to_string_lossy(), err);
+                            std::thread::sleep(REOPEN_DELAY);
+                        }
+                    }
+
+                    self.push_silence();
+                }
+            }
+            Source::Stdin => {
+                self.read_until_end(std::io::stdin().lock());
+                info!("Stdin got closed.");
+                self.push_silence();
+            }
+            Source::Reader(reader) => {
+                self.read_until_end(reader);
+                debug!("Reached the end of the PCM reader.");
+                self.push_silence();
+            }
+        }
+    }
+
+    fn read_until_end(&mut self, mut reader: impl Read) {
+        self.decoder.reset();
+
+        while self.is_running.load(Ordering::Relaxed) {
+            match reader.read(&mut self.bytes) {
+                Ok(0) => return,
+                Ok(amount_bytes) => {
+                    let samples = self.decoder.decode(&self.bytes[..amount_bytes]);
+                    self.sample_buffer.push(samples);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    warn!("Couldn't read PCM data: {}", err);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Clears the sample buffer so the visualizer doesn't freeze with the last samples.
+    fn push_silence(&self) {
+        let silence = vec![0f32; self.sample_buffer.capacity()];
+        self.sample_buffer.push(&silence);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::wait_until;
+    use std::{io::Cursor, sync::mpsc::Receiver};
+
+    #[test]
+    fn no_channels() {
+        let result = Fifo::new(Descriptor {
+            source: Source::Reader(Box::new(Cursor::new(Vec::new()))),
+            format: PcmFormat::S16Le,
+            sample_rate: 44_100,
+            amount_channels: 0,
+        });
+
+        assert!(matches!(result, Err(FifoError::NoChannels)));
+    }
+
+    #[test]
+    fn missing_fifo() {
+        let result = Fifo::new(Descriptor {
+            source: Source::Path(PathBuf::from("/this/path/does/not/exist")),
+            format: PcmFormat::S16Le,
+            sample_rate: 44_100,
+            amount_channels: 2,
+        });
+
+        assert!(matches!(result, Err(FifoError::IO(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn regular_file() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        let result = Fifo::new(Descriptor {
+            source: Source::Path(path.clone()),
+            format: PcmFormat::S16Le,
+            sample_rate: 44_100,
+            amount_channels: 2,
+        });
+
+        assert!(matches!(result, Err(FifoError::NotAFifo(not_a_fifo)) if not_a_fifo == path));
+    }
+
+    /// A reader which returns the bytes it receives and reaches its end as soon as the sender is dropped.
+    struct ChannelReader(Receiver<Vec<u8>>);
+
+    impl Read for ChannelReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.recv() {
+                Ok(bytes) => {
+                    buf[..bytes.len()].copy_from_slice(&bytes);
+                    Ok(bytes.len())
+                }
+                Err(_) => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn silence_after_the_end_of_the_reader() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let fetcher = Fifo::new(Descriptor {
+            source: Source::Reader(Box::new(ChannelReader(rx))),
+            format: PcmFormat::F32Le,
+            sample_rate: 44_100,
+            amount_channels: 2,
+        })
+        .unwrap();
+        let sample_buffer = fetcher.sample_buffer();
+
+        let bytes = [0.5f32, -0.5]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect::<Vec<u8>>();
+        tx.send(bytes).unwrap();
+        wait_until(|| sample_buffer.to_vec().ends_with(&[0.5, -0.5]));
+
+        drop(tx);
+        wait_until(|| sample_buffer.to_vec().iter().all(|&sample| sample == 0.));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writer_reconnects() {
+        use std::io::Write;
+
+        let path =
+            std::env::temp_dir().join(format!("vibe-audio-fifo-{}.fifo", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let status = std::process::Command::new("mkfifo")
+            .arg(&path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let fetcher = Fifo::new(Descriptor {
+            source: Source::Path(path.clone()),
+            format: PcmFormat::F32Le,
+            sample_rate: 44_100,
+            amount_channels: 1,
+        })
+        .unwrap();
+        let sample_buffer = fetcher.sample_buffer();
+
+        for value in [0.25f32, 0.75] {
+            let mut writer = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+            writer.write_all(&value.to_le_bytes()).unwrap();
+            writer.flush().unwrap();
+
+            wait_until(|| sample_buffer.to_vec().last() == Some(&value));
+
+            // disconnect the writer
+            drop(writer);
+            wait_until(|| sample_buffer.to_vec().last() == Some(&0.));
+        }
+
+        drop(fetcher);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,440 @@
+use super::{reserve_sample_buffer, Fetcher, SampleBuffer};
+use cpal::SampleRate;
+use std::{collections::VecDeque, time::Duration};
+
+/// The maximal amount of audio which is buffered for a source while waiting for the other sources.
+///
+/// If a source lags behind for longer (for example because its device stopped sending audio),
+/// it's treated as silent for the missing samples.
+const MAX_LATENCY: Duration = Duration::from_millis(50);
+
+/// Errors which can occur while creating [crate::fetcher::MixFetcher].
+#[derive(thiserror::Error, Debug)]
+pub enum MixError {
+    #[error("At least one source is needed to mix.")]
+    NoSources,
+
+    #[error("The sample rate must be greater than zero.")]
+    InvalidSampleRate,
+
+    #[error("The source with the index {0} has no channels.")]
+    NoChannels(usize),
+}
+
+/// A fetcher whose samples are mixed by [crate::fetcher::MixFetcher].
+pub struct Source {
+    pub fetcher: Box<dyn Fetcher>,
+
+    /// The factor which the samples of the fetcher are multiplied with.
+    pub gain: f32,
+}
+
+pub struct Descriptor {
+    pub sources: Vec<Source>,
+
+    /// The sample rate which the sources are resampled to.
+    ///
+    /// `None`: Use the highest sample rate of the sources.
+    pub sample_rate: Option<SampleRate>,
+}
+
+/// Fetcher which mixes the samples of multiple fetchers (for example the system audio and a microphone).
+///
+/// The samples of each source are resampled to a common sample rate, multiplied by the gain of the source
+/// and summed up.
+/// The mix has as many channels as the source with the most channels. Sources with less channels
+/// are repeated (so a mono source is played on every channel).
+///
+/// The sources are read each time [crate::SampleProcessor::process_next_samples] is called.
+pub struct Mix {
+    sample_buffer: SampleBuffer,
+    channels: u16,
+
+    sources: Box<[MixedSource]>,
+    // the maximal amount of frames which are buffered for a source, see `MAX_LATENCY`
+    max_latency: usize,
+    // reused buffer for the mixed frames
+    frames: Vec<f32>,
+}
+
+impl Mix {
+    pub fn new(desc: Descriptor) -> Result<Self, MixError> {
+        if desc.sources.is_empty() {
+            return Err(MixError::NoSources);
+        }
+
+        if let Some(source_idx) = desc
+            .sources
+            .iter()
+            .position(|source| source.fetcher.channels() == 0)
+        {
+            return Err(MixError::NoChannels(source_idx));
+        }
+
+        let sample_rate = match desc.sample_rate {
+            Some(0) => return Err(MixError::InvalidSampleRate),
+            Some(sample_rate) => sample_rate,
+            None => desc
+                .sources
+                .iter()
+                .map(|source| source.fetcher.sample_rate())
+                .max()
+                .unwrap(),
+        };
+
+        let channels = desc
+            .sources
+            .iter()
+            .map(|source| source.fetcher.channels())
+            .max()
+            .unwrap();
+
+        let sources = desc
+            .sources
+            .into_iter()
+            .map(|source| MixedSource::new(source, sample_rate))
+            .collect();
+
+        Ok(Self {
+            sample_buffer: SampleBuffer::new(sample_rate),
+            channels,
+
+            sources,
+            max_latency: (MAX_LATENCY.as_secs_f64() * sample_rate as f64) as usize,
+            frames: Vec::new(),
+        })
+    }
+
+    /// Returns the amount of sources.
+    pub fn amount_sources(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Returns the gain of the source with the given index.
+    pub fn gain(&self, source_idx: usize) -> f32 {
+        self.sources[source_idx].gain
+    }
+
+    /// Changes the gain of the source with the given index.
+    ///
+    /// # Panics
+    /// Panics if there's no source with the given index.
+    pub fn set_gain(&mut self, source_idx: usize, gain: f32) {
+        self.sources[source_idx].gain = gain;
+    }
+}
+
+impl Fetcher for Mix {
+    fn sample_buffer(&self) -> SampleBuffer {
+        self.sample_buffer.clone()
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn advance(&mut self) {
+        for source in self.sources.iter_mut() {
+            source.fetcher.advance();
+            source.fetch_new_frames();
+        }
+
+        let queued_frames = self.sources.iter().map(MixedSource::queued_frames);
+        let shortest = queued_frames.clone().min().unwrap();
+        let longest = queued_frames.max().unwrap();
+        let amount_frames = shortest.max(longest.saturating_sub(self.max_latency));
+        if amount_frames == 0 {
+            return;
+        }
+
+        let channels = self.channels as usize;
+        self.frames.clear();
+        self.frames.resize(amount_frames * channels, 0.);
+
+        for source in self.sources.iter_mut() {
+            let amount_source_frames = amount_frames.min(source.queued_frames());
+
+            for (frame_idx, frame) in self.frames.chunks_exact_mut(channels).enumerate() {
+                if frame_idx >= amount_source_frames {
+                    break;
+                }
+
+                for (channel_idx, sample) in frame.iter_mut().enumerate() {
+                    let source_sample =
+                        source.queue[frame_idx * source.channels + channel_idx % source.channels];
+                    *sample += source.gain * source_sample;
+                }
+            }
+
+            source.queue.drain(..amount_source_frames * source.channels);
+        }
+
+        self.sample_buffer.push(&self.frames);
+    }
+}
+
+/// A source with the state which is needed to mix it.
+struct MixedSource {
+    fetcher: Box<dyn Fetcher>,
+    gain: f32,
+    channels: usize,
+
+    // the value of `SampleBuffer::amount_pushed_samples` of the fetcher when it got read the last time
+    read_samples: u64,
+    // reused buffer for the new samples of the fetcher
+    samples: Vec<f32>,
+    resampler: Resampler,
+    // the resampled (interleaved) frames which haven't been mixed yet
+    queue: VecDeque<f32>,
+}
+
+impl MixedSource {
+    fn new(source: Source, sample_rate: SampleRate) -> Self {
+        let channels = source.fetcher.channels() as usize;
+        let source_sample_rate = source.fetcher.sample_rate();
+
+        let read_samples = reserve_sample_buffer(source.fetcher.as_ref());
+
+        Self {
+            fetcher: source.fetcher,
+            gain: source.gain,
+            channels,
+
+            read_samples,
+            samples: Vec::new(),
+            resampler: Resampler::new(source_sample_rate, sample_rate, channels),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Resamples the frames which the fetcher pushed since the last call and queues them.
+    fn fetch_new_frames(&mut self) {
+        self.read_samples = self
+            .fetcher
+            .sample_buffer()
+            .read_since(self.read_samples, &mut self.samples);
+
+        // skip the incomplete frame if older samples got lost
+        let incomplete_samples = self.samples.len() % self.channels;
+        self.resampler
+            .process(&self.samples[incomplete_samples..], &mut self.queue);
+    }
+
+    fn queued_frames(&self) -> usize {
+        self.queue.len() / self.channels
+    }
+}
+
+/// Resamples interleaved frames by interpolating linearly between two frames.
+struct Resampler {
+    // the amount of input frames per output frame
+    step: f64,
+    // the position of the next output frame, relative to `prev`
+    pos: f64,
+    // the last input frame of the previous call
+    prev: Box<[f32]>,
+}
+
+impl Resampler {
+    fn new(from: SampleRate, to: SampleRate, channels: usize) -> Self {
+        Self {
+            step: from as f64 / to as f64,
+            pos: 0.,
+            prev: vec![0f32; channels].into_boxed_slice(),
+        }
+    }
+
+    fn process(&mut self, input: &[f32], out: &mut VecDeque<f32>) {
+        let channels = self.prev.len();
+        let amount_frames = input.len() / channels;
+        if amount_frames == 0 {
+            return;
+        }
+
+        // frame `0` is `prev`, frame `i` is the `(i-1)`-th frame of the input
+        let frame = |idx: usize| {
+            if idx == 0 {
+                &self.prev[..]
+            } else {
+                &input[(idx - 1) * channels..idx * channels]
+            }
+        };
+
+        while (self.pos as usize) < amount_frames {
+            let idx = self.pos as usize;
+            let t = (self.pos - idx as f64) as f32;
+
+            let (left, right) = (frame(idx), frame(idx + 1));
+            out.extend(left.iter().zip(right).map(|(l, r)| (1. - t) * l + t * r));
+
+            self.pos += self.step;
+        }
+
+        self.pos -= amount_frames as f64;
+        self.prev
+            .copy_from_slice(&input[(amount_frames - 1) * channels..amount_frames * channels]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::{DummyFetcher, Pacing, Signal, SignalFetcher, SignalFetcherDescriptor};
+    use std::num::NonZero;
+
+    fn sine(sample_rate: SampleRate, channels: u16, frames_per_step: usize) -> Box<dyn Fetcher> {
+        Box::new(
+            SignalFetcher::new(&SignalFetcherDescriptor {
+                signals: vec![Signal::Sine {
+                    frequency: 441.,
+                    amplitude: 1.,
+                }],
+                sample_rate,
+                amount_channels: channels,
+                pacing: Pacing::Offline {
+                    frames_per_step: NonZero::new(frames_per_step).unwrap(),
+                },
+            })
+            .unwrap(),
+        )
+    }
+
+    fn newest_samples(fetcher: &impl Fetcher, amount: usize) -> Vec<f32> {
+        let mut samples = vec![0f32; amount];
+        fetcher.sample_buffer().snapshot(&mut samples);
+        samples
+    }
+
+    #[test]
+    fn no_sources() {
+        let result = Mix::new(Descriptor {
+            sources: vec![],
+            sample_rate: None,
+        });
+
+        assert!(matches!(result, Err(MixError::NoSources)));
+    }
+
+    #[test]
+    fn sums_the_sources_with_their_gains() {
+        let mut mix = Mix::new(Descriptor {
+            sources: vec![
+                Source {
+                    fetcher: sine(44_100, 1, 441),
+                    gain: 0.5,
+                },
+                Source {
+                    fetcher: sine(44_100, 1, 441),
+                    gain: 0.25,
+                },
+            ],
+            sample_rate: None,
+        })
+        .unwrap();
+        let mut reference = sine(44_100, 1, 441);
+
+        mix.advance();
+        reference.advance();
+
+        // the resampler delays the samples by one frame
+        let mixed = newest_samples(&mix, 441);
+        let reference = newest_samples(&reference, 441);
+        assert_eq!(mixed[0], 0.);
+        for (mixed, reference) in mixed[1..].iter().zip(&reference[..440]) {
+            assert!((mixed - 0.75 * reference).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn resamples_to_the_highest_sample_rate() {
+        let mut mix = Mix::new(Descriptor {
+            sources: vec![
+                Source {
+                    fetcher: sine(22_050, 1, 441),
+                    gain: 1.,
+                },
+                Source {
+                    fetcher: sine(44_100, 1, 882),
+                    gain: 0.,
+                },
+            ],
+            sample_rate: None,
+        })
+        .unwrap();
+        assert_eq!(mix.sample_rate(), 44_100);
+
+        mix.advance();
+
+        // 441 Hz => one period every 100 frames at 44.1 kHz
+        let samples = newest_samples(&mix, 882);
+        let sine = |idx: usize| (idx as f32 / 100. * std::f32::consts::TAU).sin();
+        for (idx, sample) in samples.iter().enumerate().skip(2) {
+            assert!((sample - sine(idx - 2)).abs() < 0.01, "{}", idx);
+        }
+    }
+
+    #[test]
+    fn mono_sources_are_played_on_every_channel() {
+        let mut mix = Mix::new(Descriptor {
+            sources: vec![
+                Source {
+                    fetcher: sine(44_100, 1, 441),
+                    gain: 1.,
+                },
+                Source {
+                    fetcher: sine(44_100, 2, 441),
+                    gain: 0.,
+                },
+            ],
+            sample_rate: None,
+        })
+        .unwrap();
+        assert_eq!(mix.channels(), 2);
+
+        mix.advance();
+
+        let samples = newest_samples(&mix, 882);
+        let (frames, _) = samples.as_chunks::<2>();
+        assert!(frames.iter().all(|[left, right]| left == right));
+        assert!(frames.iter().any(|[left, _]| *left != 0.));
+    }
+
+    #[test]
+    fn silent_sources_dont_block_the_mix() {
+        let mut mix = Mix::new(Descriptor {
+            sources: vec![
+                Source {
+                    fetcher: sine(44_100, 1, 441),
+                    gain: 1.,
+                },
+                Source {
+                    // never pushes any samples
+                    fetcher: Box::new(DummyFetcher::new(1)),
+                    gain: 1.,
+                },
+            ],
+            sample_rate: None,
+        })
+        .unwrap();
+
+        for _ in 0..10 {
+            mix.advance();
+        }
+
+        let sample_buffer = mix.sample_buffer();
+        let amount_pushed_samples = sample_buffer.amount_pushed_samples();
+        // everything except the buffered latency got pushed
+        assert_eq!(amount_pushed_samples, 10 * 441 - mix.max_latency as u64);
+    }
+
+    #[test]
+    fn resampler_keeps_the_samples_with_the_same_sample_rate() {
+        let mut resampler = Resampler::new(48_000, 48_000, 2);
+        let mut out = VecDeque::new();
+
+        resampler.process(&[1., 2., 3., 4.], &mut out);
+        resampler.process(&[5., 6.], &mut out);
+
+        assert_eq!(out, [0., 0., 1., 2., 3., 4.]);
+    }
+}
@@ -1,124 +1,109 @@
 //! Each struct here can be used to fetch the audio data from various sources.
 //! Pick the one you need to fetch from.
+mod audio_file;
 mod dummy;
+mod fifo;
+mod mix;
+mod network;
+mod pacing;
+mod pcm;
+mod recorder;
+mod recording;
+mod replay;
+mod sample_buffer;
+mod signal;
 mod system_audio;
 
 use cpal::SampleRate;
-use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+pub use audio_file::{
+    AudioFile as FileFetcher, AudioFileError, Descriptor as FileFetcherDescriptor,
+};
 pub use dummy::DummyFetcher;
+pub use fifo::{
+    Descriptor as FifoFetcherDescriptor, Fifo as FifoFetcher, FifoError, Source as FifoSource,
+};
+pub use mix::{
+    Descriptor as MixFetcherDescriptor, Mix as MixFetcher, MixError, Source as MixSource,
+};
+pub use network::{
+    Descriptor as NetworkFetcherDescriptor, Network as NetworkFetcher, NetworkError, PacketError,
+    PacketHeader, Protocol as NetworkProtocol,
+};
+pub use pacing::Pacing;
+// the name of the pacing when only the file fetcher used it
+pub use pacing::Pacing as FilePacing;
+pub use pcm::{PcmFormat, PcmFormatError};
+pub use recorder::Recorder as RecordingFetcher;
+pub use recording::RecordingError;
+pub use replay::{Descriptor as ReplayFetcherDescriptor, Replay as ReplayFetcher, ReplayMode};
+pub use sample_buffer::SampleBuffer;
+pub use signal::{Descriptor as SignalFetcherDescriptor, Signal, SignalError, SignalFetcher};
 pub use system_audio::{
-    Descriptor as SystemAudioFetcherDescriptor, SystemAudio as SystemAudioFetcher, SystemAudioError,
+    Descriptor as SystemAudioFetcherDescriptor, SystemAudio as SystemAudioFetcher,
+    SystemAudioError, SystemAudioEvent,
 };
 
 /// Interface for all structs (fetchers) which are listed in the [fetcher module](crate::fetcher).
 pub trait Fetcher {
-    /// Returns the [SampleBuffer] (aka the input for the fft calculations).
-    fn sample_buffer(&self) -> Arc<Mutex<SampleBuffer>>;
+    /// Returns a handle to the [SampleBuffer] (aka the input for the fft calculations).
+    ///
+    /// The fetcher is the only one which should push into it.
+    fn sample_buffer(&self) -> SampleBuffer;
 
     /// Returns the amount of channels which are used from the fetcher.
     fn channels(&self) -> u16;
-}
 
-/// Holds the audio samples which gets filled by the fetcher
-#[derive(Debug, Clone)]
-pub struct SampleBuffer {
-    buffer: Box<[f32]>,
-    sample_rate: SampleRate,
-}
-
-impl SampleBuffer {
-    /// Create a new instance for the given sample rate.
-    pub fn new(sample_rate: SampleRate) -> Self {
-        // props to cava for this heuristic.
-        let factor = if sample_rate < 8_125 {
-            1
-        } else if sample_rate <= 16_250 {
-            2
-        } else if sample_rate <= 32_500 {
-            4
-        } else if sample_rate <= 75_000 {
-            8
-        } else if sample_rate <= 150_000 {
-            16
-        } else if sample_rate <= 300_000 {
-            32
-        } else {
-            64
-        };
-
-        let buffer = vec![0f32; factor * 128].into_boxed_slice();
-
-        Self {
-            buffer,
-            sample_rate,
-        }
+    /// Returns the sample rate of the samples which the fetcher pushes into its [SampleBuffer].
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_buffer().sample_rate()
     }
 
-    /// Pushes the given data to the front of `buffer` and moves the current data to the right.
-    /// Basically a `VecDeque::push_before` just on a `Box<[f32]>`.
-    pub fn push_before(&mut self, data: &[f32]) {
-        let data_len = data.len();
-        let buffer_len = self.buffer.len();
-
-        // split point
-        let split_point = buffer_len.min(data_len);
-
-        // move current values to the end/right of the buffer
-        self.buffer
-            .copy_within(..split_point, buffer_len - split_point);
+    /// Gets called by [crate::SampleProcessor::process_next_samples] right before the samples are read.
+    ///
+    /// Fetchers which fill their [SampleBuffer] by themselves (for example within an audio stream callback)
+    /// don't need to do anything here, which is the default.
+    /// Fetchers which are driven by the processor (for example to process a file frame by frame)
+    /// can push their next samples here.
+    fn advance(&mut self) {}
+}
 
-        // write the new data [at the beginning]/[on the left] of the buffer
-        self.buffer[..split_point].copy_from_slice(&data[..split_point]);
+/// The minimal amount of audio which the [SampleBuffer] of a fetcher can hold if a reader needs every sample
+/// (for example a source of [MixFetcher] or the [crate::LoudnessMeter]), so no samples get lost between two reads.
+const MIN_READ_BUFFER_DURATION: Duration = Duration::from_millis(250);
+
+/// Makes sure that the [SampleBuffer] of the given fetcher can hold at least
+/// [MIN_READ_BUFFER_DURATION] of audio.
+///
+/// Returns the amount of samples which the fetcher pushed so far.
+pub(crate) fn reserve_sample_buffer<F: Fetcher + ?Sized>(fetcher: &F) -> u64 {
+    let sample_buffer = fetcher.sample_buffer();
+
+    let min_capacity = (MIN_READ_BUFFER_DURATION.as_secs_f64() * sample_buffer.sample_rate() as f64)
+        as usize
+        * fetcher.channels() as usize;
+    if sample_buffer.capacity() < min_capacity {
+        sample_buffer.set_capacity(min_capacity);
     }
 
-    pub fn sample_rate(&self) -> SampleRate {
-        self.sample_rate
-    }
+    sample_buffer.amount_pushed_samples()
+}
 
-    pub fn capacity(&self) -> usize {
-        self.buffer.len()
+impl<F: Fetcher + ?Sized> Fetcher for Box<F> {
+    fn sample_buffer(&self) -> SampleBuffer {
+        self.as_ref().sample_buffer()
     }
 
-    pub fn buffer(&self) -> &[f32] {
-        &self.buffer
+    fn channels(&self) -> u16 {
+        self.as_ref().channels()
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    mod sample_buffer {
-        use super::*;
 
-        #[test]
-        fn push_more_than_capacity() {
-            // buffer should have length of `1` * `128`
-            let mut sample_buffer = SampleBuffer::new(1);
-            sample_buffer.push_before(&[1f32; 129]);
-
-            assert_eq!(sample_buffer.buffer.len(), 128);
-            assert!(sample_buffer.buffer.iter().all(|&value| value == 1f32));
-        }
-
-        #[test]
-        fn new_values_are_moved_to_the_beginning() {
-            let mut sample_buffer = SampleBuffer::new(1);
-            sample_buffer.push_before(&[69f32]);
-
-            assert_eq!(sample_buffer.buffer.len(), 128);
-            assert_eq!(sample_buffer.buffer[0], 69f32);
-            assert!(sample_buffer.buffer[1..].iter().all(|&value| value == 0f32));
-        }
-
-        #[test]
-        fn no_values_pushed() {
-            let mut sample_buffer = SampleBuffer::new(1);
-            sample_buffer.push_before(&[]);
+    fn sample_rate(&self) -> SampleRate {
+        self.as_ref().sample_rate()
+    }
 
-            assert_eq!(sample_buffer.buffer.len(), 128);
-            assert!(sample_buffer.buffer.iter().all(|&value| value == 0f32));
-        }
+    fn advance(&mut self) {
+        self.as_mut().advance()
     }
 }
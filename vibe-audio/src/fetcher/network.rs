@@ -0,0 +1,764 @@
+//! Receives raw PCM data over the network.
+//!
+//! Each packet (UDP) or frame (TCP) starts with a [PacketHeader] followed by
+//! `payload_len` bytes of interleaved PCM data:
+//!
+//! | Bytes   | Field         | Type                                       |
+//! |---------|---------------|--------------------------------------------|
+//! | 0..4    | magic         | `b"VIBE"`                                  |
+//! | 4..8    | sequence      | `u32`, little endian                       |
+//! | 8..12   | sample rate   | `u32`, little endian                       |
+//! | 12..14  | channels      | `u16`, little endian                       |
+//! | 14      | format        | `0` = s16le, `1` = s32le, `2` = f32le      |
+//! | 15      | reserved      | `0`                                        |
+//! | 16..20  | payload_len   | `u32`, little endian                       |
+//!
+//! A frame of a TCP stream may contain at most 500 ms of audio, otherwise the sender gets disconnected.
+use super::{Fetcher, PcmFormat, SampleBuffer};
+use cpal::SampleRate;
+use std::{
+    io::Read,
+    net::{SocketAddr, TcpListener, TcpStream, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, warn};
+
+/// How often the receiver checks whether the stream fell behind while no data arrives.
+const GAP_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// How far the stream may fall behind the wall-clock time before silence is pushed for the missing time.
+/// Absorbs the jitter of the network, so late packets don't cause dropouts.
+const JITTER_ALLOWANCE: Duration = Duration::from_millis(150);
+
+/// The maximum size of an UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 65_536;
+
+/// The maximum amount of audio which a single frame of a TCP stream may contain.
+/// Protects against headers which would make the receiver buffer gigabytes.
+const MAX_FRAME_DURATION: Duration = Duration::from_millis(500);
+
+/// Packets which arrive up to this many packets too late are dropped and up to this many lost packets are
+/// filled with silence.
+/// A bigger step in either direction means that the sender restarted, so the stream gets synchronized again.
+const MAX_REORDER_DISTANCE: u32 = 64;
+
+/// Errors which can occur while creating [crate::fetcher::NetworkFetcher].
+#[derive(thiserror::Error, Debug)]
+pub enum NetworkError {
+    #[error("Couldn't listen on the given address: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("The amount of channels must be at least one.")]
+    NoChannels,
+
+    #[error("The sample rate must be greater than zero.")]
+    InvalidSampleRate,
+}
+
+/// Errors which can occur while parsing a [PacketHeader].
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum PacketError {
+    #[error("The packet is too short to contain a header.")]
+    TooShort,
+
+    #[error("The packet doesn't start with the magic bytes.")]
+    InvalidMagic,
+
+    #[error("Unknown PCM format id: {0}")]
+    UnknownFormat(u8),
+
+    #[error("The payload of {payload_len} bytes exceeds the maximum of {max} bytes.")]
+    PayloadTooLarge { payload_len: u32, max: usize },
+}
+
+/// The header of each packet of the network stream.
+///
+/// See the [module documentation](self) for the layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PacketHeader {
+    /// Incremented by one for each packet. Used to detect lost packets.
+    pub sequence: u32,
+    pub sample_rate: SampleRate,
+    pub channels: u16,
+    pub format: PcmFormat,
+    /// The amount of bytes of PCM data which follow the header.
+    pub payload_len: u32,
+}
+
+impl PacketHeader {
+    pub const MAGIC: [u8; 4] = *b"VIBE";
+    pub const SIZE: usize = 20;
+
+    /// Converts the header into its byte representation.
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+
+        bytes[0..4].copy_from_slice(&Self::MAGIC);
+        bytes[4..8].copy_from_slice(&self.sequence.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.sample_rate.to_le_bytes());
+        bytes[12..14].copy_from_slice(&self.channels.to_le_bytes());
+        bytes[14] = match self.format {
+            PcmFormat::S16Le => 0,
+            PcmFormat::S32Le => 1,
+            PcmFormat::F32Le => 2,
+        };
+        bytes[16..20].copy_from_slice(&self.payload_len.to_le_bytes());
+
+        bytes
+    }
+
+    /// Parses the header at the beginning of `bytes`.
+    pub fn decode(bytes: &[u8]) -> Result<Self, PacketError> {
+        let Some(bytes) = bytes.first_chunk::<{ Self::SIZE }>() else {
+            return Err(PacketError::TooShort);
+        };
+
+        if bytes[0..4] != Self::MAGIC {
+            return Err(PacketError::InvalidMagic);
+        }
+
+        let format = match bytes[14] {
+            0 => PcmFormat::S16Le,
+            1 => PcmFormat::S32Le,
+            2 => PcmFormat::F32Le,
+            id => return Err(PacketError::UnknownFormat(id)),
+        };
+
+        Ok(Self {
+            sequence: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            sample_rate: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+            channels: u16::from_le_bytes([bytes[12], bytes[13]]),
+            format,
+            payload_len: u32::from_le_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Protocol {
+    /// Recommended for streams within the LAN. Lost packets are replaced by silence.
+    #[default]
+    Udp,
+
+    /// Accepts one sender at a time. The next sender can connect as soon as the current one disconnects.
+    Tcp,
+}
+
+pub struct Descriptor {
+    pub protocol: Protocol,
+
+    /// The local address to listen on, for example `0.0.0.0:7000`.
+    pub address: SocketAddr,
+
+    /// The sample rate which the sender has to use.
+    /// Packets with a different sample rate are dropped.
+    pub sample_rate: SampleRate,
+
+    /// The amount of channels which the sender has to use.
+    /// Packets with a different amount of channels are dropped.
+    pub amount_channels: u16,
+}
+
+/// Fetcher which receives raw PCM data over UDP or TCP.
+pub struct Network {
+    sample_buffer: SampleBuffer,
+    channels: u16,
+    local_addr: SocketAddr,
+
+    is_running: Arc<AtomicBool>,
+}
+
+impl Network {
+    pub fn new(desc: &Descriptor) -> Result<Self, NetworkError> {
+        if desc.amount_channels == 0 {
+            return Err(NetworkError::NoChannels);
+        }
+
+        if desc.sample_rate == 0 {
+            return Err(NetworkError::InvalidSampleRate);
+        }
+
+        let sample_buffer = SampleBuffer::new(desc.sample_rate);
+        let is_running = Arc::new(AtomicBool::new(true));
+
+        let receiver = Receiver {
+            sample_rate: desc.sample_rate,
+            channels: desc.amount_channels,
+            expected_sequence: None,
+            late_sequence: None,
+            format: None,
+            has_mismatch: false,
+
+            clock_start: Instant::now(),
+            pushed_frames: 0,
+            has_gap: true,
+
+            samples: Vec::new(),
+            sample_buffer: sample_buffer.clone(),
+            is_running: is_running.clone(),
+        };
+
+        let local_addr = match desc.protocol {
+            Protocol::Udp => {
+                let socket = UdpSocket::bind(desc.address)?;
+                socket.set_read_timeout(Some(GAP_TIMEOUT))?;
+                let local_addr = socket.local_addr()?;
+
+                std::thread::spawn(move || receiver.run_udp(socket));
+                local_addr
+            }
+            Protocol::Tcp => {
+                let listener = TcpListener::bind(desc.address)?;
+                // otherwise we couldn't push silence while waiting for a sender
+                listener.set_nonblocking(true)?;
+                let local_addr = listener.local_addr()?;
+
+                std::thread::spawn(move || receiver.run_tcp(listener));
+                local_addr
+            }
+        };
+
+        info!("Listening for audio on {}", local_addr);
+
+        Ok(Self {
+            sample_buffer,
+            channels: desc.amount_channels,
+            local_addr,
+            is_running,
+        })
+    }
+
+    /// Returns the address which the fetcher listens on.
+    /// Useful if port `0` was given to let the system choose a free port.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Fetcher for Network {
+    fn sample_buffer(&self) -> SampleBuffer {
+        self.sample_buffer.clone()
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+}
+
+impl Drop for Network {
+    fn drop(&mut self) {
+        // the thread notices it within `GAP_TIMEOUT`
+        self.is_running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Receives the packets within its own thread and pushes them into the sample buffer.
+struct Receiver {
+    sample_rate: SampleRate,
+    channels: u16,
+    expected_sequence: Option<u32>,
+    // the sequence which follows the last dropped late packet. If the next packet has it, the "late" packets
+    // are the new stream of a restarted sender.
+    late_sequence: Option<u32>,
+    // the format of the previous packet, the sender restarted if it changes
+    format: Option<PcmFormat>,
+    // prevents spamming the log with the same warning for each packet
+    has_mismatch: bool,
+
+    // the point in time since which `pushed_frames` got counted
+    clock_start: Instant,
+    // the amount of frames (audio and silence) which have been pushed since `clock_start`
+    pushed_frames: u64,
+    // silence got pushed since the last packet, so the next packet starts counting again
+    has_gap: bool,
+
+    samples: Vec<f32>,
+    sample_buffer: SampleBuffer,
+    is_running: Arc<AtomicBool>,
+}
+
+impl Receiver {
+    fn run_udp(mut self, socket: UdpSocket) {
+        let mut datagram = vec![0u8; MAX_DATAGRAM_SIZE];
+
+        while self.is_running.load(Ordering::Relaxed) {
+            match socket.recv(&mut datagram) {
+                Ok(len) => self.handle_packet(&datagram[..len]),
+                Err(err) if is_timeout(&err) => self.fill_gap(),
+                Err(err) => {
+                    warn!("Couldn't receive audio packet: {}", err);
+                    self.fill_gap();
+                }
+            }
+        }
+    }
+
+    fn run_tcp(mut self, listener: TcpListener) {
+        while self.is_running.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, addr)) => {
+                    info!("Audio sender {} connected.", addr);
+                    self.read_tcp_stream(stream);
+                    info!("Audio sender {} disconnected.", addr);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(GAP_TIMEOUT);
+                    self.fill_gap();
+                }
+                Err(err) => {
+                    warn!("Couldn't accept audio sender: {}", err);
+                    std::thread::sleep(GAP_TIMEOUT);
+                }
+            }
+        }
+    }
+
+    fn read_tcp_stream(&mut self, mut stream: TcpStream) {
+        if let Err(err) = stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.set_read_timeout(Some(GAP_TIMEOUT)))
+        {
+            warn!(
+                "Couldn't configure the connection to the audio sender: {}",
+                err
+            );
+            return;
+        }
+
+        self.expected_sequence = None;
+
+        // bytes which have been received but don't form a complete frame yet
+        let mut pending = Vec::new();
+        let mut bytes = vec![0u8; MAX_DATAGRAM_SIZE];
+
+        while self.is_running.load(Ordering::Relaxed) {
+            match stream.read(&mut bytes) {
+                Ok(0) => return,
+                Ok(len) => {
+                    pending.extend_from_slice(&bytes[..len]);
+
+                    loop {
+                        let header = match PacketHeader::decode(&pending)
+                            .and_then(|header| self.check_payload_len(header))
+                        {
+                            Ok(header) => header,
+                            Err(PacketError::TooShort) => break,
+                            Err(err) => {
+                                warn!("Invalid audio stream: {}. Disconnecting sender.", err);
+                                return;
+                            }
+                        };
+
+                        let frame_len = PacketHeader::SIZE + header.payload_len as usize;
+                        if pending.len() < frame_len {
+                            break;
+                        }
+
+                        self.handle_packet(&pending[..frame_len]);
+                        pending.drain(..frame_len);
+                    }
+                }
+                Err(err) if is_timeout(&err) => self.fill_gap(),
+                Err(err) => {
+                    warn!("Couldn't receive audio data: {}", err);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Rejects frames whose payload would contain more than [MAX_FRAME_DURATION] of audio.
+    fn check_payload_len(&self, header: PacketHeader) -> Result<PacketHeader, PacketError> {
+        let max = (MAX_FRAME_DURATION.as_secs_f64() * self.sample_rate as f64) as usize
+            * self.channels as usize
+            * header.format.bytes_per_sample();
+
+        if header.payload_len as usize > max {
+            return Err(PacketError::PayloadTooLarge {
+                payload_len: header.payload_len,
+                max,
+            });
+        }
+
+        Ok(header)
+    }
+
+    fn handle_packet(&mut self, packet: &[u8]) {
+        let header = match PacketHeader::decode(packet) {
+            Ok(header) => header,
+            Err(err) => {
+                debug!("Dropping audio packet: {}", err);
+                return;
+            }
+        };
+
+        if header.sample_rate != self.sample_rate || header.channels != self.channels {
+            if !self.has_mismatch {
+                warn!(
+                    "Dropping audio packets with {} Hz and {} channels. Expected {} Hz and {} channels.",
+                    header.sample_rate, header.channels, self.sample_rate, self.channels
+                );
+                self.has_mismatch = true;
+            }
+            return;
+        }
+        self.has_mismatch = false;
+
+        let payload = &packet[PacketHeader::SIZE..];
+        let payload = &payload[..payload.len().min(header.payload_len as usize)];
+
+        let frame_size = header.format.bytes_per_sample() * self.channels as usize;
+        let payload = &payload[..payload.len() - payload.len() % frame_size];
+        let amount_frames = payload.len() / frame_size;
+
+        if self.format != Some(header.format) {
+            debug!("The audio sender switched to {:?}.", header.format);
+            self.format = Some(header.format);
+            self.expected_sequence = None;
+        }
+
+        if let Some(expected) = self.expected_sequence {
+            let distance = header.sequence.wrapping_sub(expected);
+            let late_distance = expected.wrapping_sub(header.sequence);
+
+            // Reordered packets arrive in between the others. So if nothing arrived for a while or the
+            // "late" packets continue each other, the sender started again with a smaller sequence.
+            let continues_late_packets = self.late_sequence == Some(header.sequence);
+            let is_new = self.has_gap || continues_late_packets;
+
+            if distance > u32::MAX / 2 && late_distance <= MAX_REORDER_DISTANCE && !is_new {
+                // arrived too late, its time slot has already been filled
+                self.late_sequence = Some(header.sequence.wrapping_add(1));
+                return;
+            }
+
+            if distance > MAX_REORDER_DISTANCE || continues_late_packets {
+                info!("The audio sender restarted its sequence.");
+            } else if distance > 0 {
+                debug!("Lost {} audio packets.", distance);
+                // `distance` is at most `MAX_REORDER_DISTANCE` here
+                self.push_zero_frames(distance as usize * amount_frames);
+            }
+        }
+        self.expected_sequence = Some(header.sequence.wrapping_add(1));
+        self.late_sequence = None;
+
+        // the stream got interrupted or the clock of the sender runs differently, so start counting again
+        if self.has_gap || self.frames_behind().unsigned_abs() > self.jitter_allowance_frames() {
+            self.clock_start = Instant::now();
+            self.pushed_frames = 0;
+            self.has_gap = false;
+        }
+
+        self.samples.clear();
+        header.format.decode(payload, &mut self.samples);
+        self.sample_buffer.push(&self.samples);
+        self.pushed_frames += amount_frames as u64;
+    }
+
+    /// Pushes silence for the missing time if the stream fell behind by more than [JITTER_ALLOWANCE].
+    fn fill_gap(&mut self) {
+        let frames_behind = self.frames_behind();
+        if frames_behind > self.jitter_allowance_frames() as i64 {
+            self.push_zero_frames(frames_behind as usize);
+            self.has_gap = true;
+        }
+    }
+
+    /// Returns how many frames are missing since `clock_start`. Negative if more frames arrived than time passed.
+    fn frames_behind(&self) -> i64 {
+        let expected_frames =
+            (self.clock_start.elapsed().as_secs_f64() * self.sample_rate as f64) as u64;
+        expected_frames as i64 - self.pushed_frames as i64
+    }
+
+    fn jitter_allowance_frames(&self) -> u64 {
+        (JITTER_ALLOWANCE.as_secs_f64() * self.sample_rate as f64) as u64
+    }
+
+    fn push_zero_frames(&mut self, amount_frames: usize) {
+        self.pushed_frames += amount_frames as u64;
+
+        // there's no need to push more than the buffer can hold
+        let amount_samples =
+            (amount_frames * self.channels as usize).min(self.sample_buffer.capacity());
+
+        self.samples.clear();
+        self.samples.resize(amount_samples, 0.);
+        self.sample_buffer.push(&self.samples);
+    }
+}
+
+fn is_timeout(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::wait_until;
+    use std::io::Write;
+
+    const SAMPLE_RATE: SampleRate = 44_100;
+
+    fn fetcher(protocol: Protocol) -> Network {
+        Network::new(&Descriptor {
+            protocol,
+            address: "127.0.0.1:0".parse().unwrap(),
+            sample_rate: SAMPLE_RATE,
+            amount_channels: 1,
+        })
+        .unwrap()
+    }
+
+    fn packet(sequence: u32, samples: &[f32]) -> Vec<u8> {
+        let payload = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        let header = PacketHeader {
+            sequence,
+            sample_rate: SAMPLE_RATE,
+            channels: 1,
+            format: PcmFormat::F32Le,
+            payload_len: payload.len() as u32,
+        };
+
+        let mut packet = header.encode().to_vec();
+        packet.extend(payload);
+        packet
+    }
+
+    #[test]
+    fn header_roundtrip() {
+        let header = PacketHeader {
+            sequence: 42,
+            sample_rate: 48_000,
+            channels: 2,
+            format: PcmFormat::S32Le,
+            payload_len: 1024,
+        };
+
+        assert_eq!(PacketHeader::decode(&header.encode()), Ok(header));
+    }
+
+    #[test]
+    fn invalid_header() {
+        assert_eq!(PacketHeader::decode(b"VIBE"), Err(PacketError::TooShort));
+        assert_eq!(
+            PacketHeader::decode(&[0u8; PacketHeader::SIZE]),
+            Err(PacketError::InvalidMagic)
+        );
+    }
+
+    #[test]
+    fn udp() {
+        let fetcher = fetcher(Protocol::Udp);
+        let sample_buffer = fetcher.sample_buffer();
+
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .send_to(&packet(0, &[0.25, 0.5]), fetcher.local_addr())
+            .unwrap();
+
+        wait_until(|| sample_buffer.to_vec().ends_with(&[0.25, 0.5]));
+    }
+
+    #[test]
+    fn lost_udp_packets_are_zero_filled() {
+        let fetcher = fetcher(Protocol::Udp);
+        let sample_buffer = fetcher.sample_buffer();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        socket
+            .send_to(&packet(0, &[0.25, 0.25]), fetcher.local_addr())
+            .unwrap();
+        wait_until(|| sample_buffer.to_vec().last() == Some(&0.25));
+
+        // packet `1` got lost
+        socket
+            .send_to(&packet(2, &[0.5, 0.5]), fetcher.local_addr())
+            .unwrap();
+        wait_until(|| sample_buffer.to_vec().last() == Some(&0.5));
+
+        assert!(sample_buffer.to_vec().ends_with(&[0., 0., 0.5, 0.5]));
+    }
+
+    #[test]
+    fn sender_restarts() {
+        let fetcher = fetcher(Protocol::Udp);
+        let sample_buffer = fetcher.sample_buffer();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        for (sequence, sample) in [(1000, 0.25), (1001, 0.5)] {
+            socket
+                .send_to(&packet(sequence, &[sample]), fetcher.local_addr())
+                .unwrap();
+            wait_until(|| sample_buffer.to_vec().last() == Some(&sample));
+        }
+
+        // a late packet is still dropped
+        socket
+            .send_to(&packet(1000, &[1.]), fetcher.local_addr())
+            .unwrap();
+        // the sender starts again with sequence `0`
+        for (sequence, sample) in [(0, 0.75), (1, 0.125)] {
+            socket
+                .send_to(&packet(sequence, &[sample]), fetcher.local_addr())
+                .unwrap();
+            wait_until(|| sample_buffer.to_vec().last() == Some(&sample));
+        }
+
+        assert!(!sample_buffer.to_vec().contains(&1.));
+    }
+
+    #[test]
+    fn sender_restarts_with_a_slightly_smaller_sequence() {
+        let fetcher = fetcher(Protocol::Udp);
+        let sample_buffer = fetcher.sample_buffer();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        for (sequence, sample) in [(10, 0.25), (11, 0.5), (12, 0.75)] {
+            socket
+                .send_to(&packet(sequence, &[sample]), fetcher.local_addr())
+                .unwrap();
+            wait_until(|| sample_buffer.to_vec().last() == Some(&sample));
+        }
+
+        // the first packet of the new stream looks like a late one, the following ones continue it
+        for sequence in [0, 1, 2] {
+            socket
+                .send_to(&packet(sequence, &[0.125]), fetcher.local_addr())
+                .unwrap();
+        }
+        wait_until(|| sample_buffer.to_vec().ends_with(&[0.75, 0.125, 0.125]));
+
+        // a packet of the new stream arriving after a pause is accepted as well
+        std::thread::sleep(JITTER_ALLOWANCE + 2 * GAP_TIMEOUT);
+        socket
+            .send_to(&packet(0, &[1.]), fetcher.local_addr())
+            .unwrap();
+        wait_until(|| sample_buffer.to_vec().last() == Some(&1.));
+    }
+
+    #[test]
+    fn big_sequence_jump_isnt_zero_filled() {
+        let fetcher = fetcher(Protocol::Udp);
+        let sample_buffer = fetcher.sample_buffer();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        for (sequence, sample) in [(0, 0.25), (u32::MAX / 2, 0.5)] {
+            socket
+                .send_to(&packet(sequence, &[sample, sample]), fetcher.local_addr())
+                .unwrap();
+            wait_until(|| sample_buffer.to_vec().last() == Some(&sample));
+        }
+
+        assert!(sample_buffer.to_vec().ends_with(&[0.25, 0.25, 0.5, 0.5]));
+    }
+
+    #[test]
+    fn silence_if_nothing_arrives() {
+        let fetcher = fetcher(Protocol::Udp);
+        let sample_buffer = fetcher.sample_buffer();
+
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .send_to(&packet(0, &[1.; 8]), fetcher.local_addr())
+            .unwrap();
+        wait_until(|| sample_buffer.to_vec().last() == Some(&1.));
+
+        wait_until(|| sample_buffer.to_vec().iter().all(|&sample| sample == 0.));
+    }
+
+    #[test]
+    fn jitter_doesnt_cause_dropouts() {
+        let fetcher = fetcher(Protocol::Udp);
+        let sample_buffer = fetcher.sample_buffer();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        socket
+            .send_to(&packet(0, &[0.25, 0.25]), fetcher.local_addr())
+            .unwrap();
+        wait_until(|| sample_buffer.to_vec().last() == Some(&0.25));
+
+        // a read timeout passes before the next packet arrives
+        std::thread::sleep(GAP_TIMEOUT + Duration::from_millis(20));
+        socket
+            .send_to(&packet(1, &[0.5, 0.5]), fetcher.local_addr())
+            .unwrap();
+        wait_until(|| sample_buffer.to_vec().last() == Some(&0.5));
+
+        assert!(sample_buffer.to_vec().ends_with(&[0.25, 0.25, 0.5, 0.5]));
+    }
+
+    #[test]
+    fn tcp_with_split_frames() {
+        let fetcher = fetcher(Protocol::Tcp);
+        let sample_buffer = fetcher.sample_buffer();
+
+        let mut stream = TcpStream::connect(fetcher.local_addr()).unwrap();
+        let packet = packet(0, &[0.25, 0.75]);
+        let (first, second) = packet.split_at(PacketHeader::SIZE + 2);
+
+        stream.write_all(first).unwrap();
+        stream.flush().unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        stream.write_all(second).unwrap();
+
+        wait_until(|| sample_buffer.to_vec().ends_with(&[0.25, 0.75]));
+    }
+
+    #[test]
+    fn tcp_sender_with_huge_frame_gets_disconnected() {
+        let fetcher = fetcher(Protocol::Tcp);
+
+        let mut stream = TcpStream::connect(fetcher.local_addr()).unwrap();
+        let mut header = packet(0, &[]);
+        header[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        stream.write_all(&header).unwrap();
+
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        match stream.read(&mut [0u8; 1]) {
+            Ok(0) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::ConnectionReset => {}
+            result => panic!("The sender wasn't disconnected: {:?}", result),
+        }
+
+        // the next sender can connect
+        let mut stream = TcpStream::connect(fetcher.local_addr()).unwrap();
+        stream.write_all(&packet(0, &[0.25])).unwrap();
+
+        let sample_buffer = fetcher.sample_buffer();
+        wait_until(|| sample_buffer.to_vec().last() == Some(&0.25));
+    }
+
+    #[test]
+    fn mismatching_packets_are_dropped() {
+        let fetcher = fetcher(Protocol::Udp);
+        let sample_buffer = fetcher.sample_buffer();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let mut stereo_packet = packet(0, &[1., 1.]);
+        stereo_packet[12..14].copy_from_slice(&2u16.to_le_bytes());
+        socket
+            .send_to(&stereo_packet, fetcher.local_addr())
+            .unwrap();
+        socket
+            .send_to(&packet(1, &[0.5]), fetcher.local_addr())
+            .unwrap();
+
+        wait_until(|| sample_buffer.to_vec().last() == Some(&0.5));
+        assert!(!sample_buffer.to_vec().contains(&1.));
+    }
+}
@@ -0,0 +1,93 @@
+use super::SampleBuffer;
+use cpal::SampleRate;
+use std::{
+    num::NonZero,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+use tracing::error;
+
+/// The interval in which the worker of [Pacing::RealTime] pushes the next samples.
+pub(crate) const REAL_TIME_TICK: Duration = Duration::from_millis(10);
+
+/// The maximal duration of audio which the worker of [Pacing::RealTime] pushes at once
+/// (a few rendered frames) if it got stalled. The rest of the stall is skipped.
+const MAX_CATCH_UP: Duration = Duration::from_millis(50);
+
+/// Decides how fast the samples of a fetcher are pushed into the [SampleBuffer].
+///
+/// Used by fetchers which produce their samples by themselves (for example by decoding a file).
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Pacing {
+    /// Push the samples with the speed of the sample rate,
+    /// like a music player would play them.
+    #[default]
+    RealTime,
+
+    /// Push the next `frames_per_step` frames each time [crate::SampleProcessor::process_next_samples]
+    /// is called, regardless of how much time has passed in between.
+    ///
+    /// Useful for offline processing and tests since the result doesn't depend on the timing of the caller.
+    Offline { frames_per_step: NonZero<usize> },
+}
+
+/// Something which produces interleaved frames on demand.
+pub(crate) trait FrameSource: Send + 'static {
+    /// Writes the next `amount_frames` frames into `out`.
+    fn read(&mut self, amount_frames: usize, out: &mut Vec<f32>);
+}
+
+/// The thread which pushes the samples of a [FrameSource] for [Pacing::RealTime].
+pub(crate) struct Worker {
+    is_running: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl Worker {
+    pub fn spawn<S: FrameSource>(
+        source: Arc<Mutex<S>>,
+        sample_buffer: SampleBuffer,
+        sample_rate: SampleRate,
+    ) -> Self {
+        let is_running = Arc::new(AtomicBool::new(true));
+
+        let handle = std::thread::spawn({
+            let is_running = is_running.clone();
+
+            move || {
+                let start = Instant::now();
+                let mut pushed_frames = 0u64;
+                let mut frames = Vec::new();
+                let max_frames = (MAX_CATCH_UP.as_secs_f64() * sample_rate as f64) as usize;
+
+                while is_running.load(Ordering::Relaxed) {
+                    let due_frames = (start.elapsed().as_secs_f64() * sample_rate as f64) as u64;
+
+                    // a burst after a stall would show up as a jump in the visualization
+                    let amount_frames = ((due_frames - pushed_frames) as usize).min(max_frames);
+                    if amount_frames > 0 {
+                        source.lock().unwrap().read(amount_frames, &mut frames);
+                        sample_buffer.push(&frames);
+                        pushed_frames = due_frames;
+                    }
+
+                    std::thread::sleep(REAL_TIME_TICK);
+                }
+            }
+        });
+
+        Self { is_running, handle }
+    }
+
+    pub fn stop(self) {
+        self.is_running.store(false, Ordering::Relaxed);
+
+        if self.handle.join().is_err() {
+            error!("The thread of a real time fetcher panicked.");
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::str::FromStr;
+
+/// The sample formats of raw, interleaved PCM data which can be read by the fetchers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PcmFormat {
+    /// Signed 16 bit integers, little endian.
+    #[default]
+    S16Le,
+
+    /// Signed 32 bit integers, little endian.
+    S32Le,
+
+    /// 32 bit floats, little endian.
+    F32Le,
+}
+
+impl PcmFormat {
+    /// Returns the amount of bytes which a single sample needs.
+    pub fn bytes_per_sample(&self) -> usize {
+        match self {
+            Self::S16Le => 2,
+            Self::S32Le | Self::F32Le => 4,
+        }
+    }
+
+    /// Converts the given bytes to samples and appends them to `out`.
+    /// Trailing bytes which don't form a complete sample are ignored.
+    pub fn decode(&self, bytes: &[u8], out: &mut Vec<f32>) {
+        match self {
+            Self::S16Le => out.extend(
+                bytes
+                    .as_chunks::<2>()
+                    .0
+                    .iter()
+                    .map(|&sample| i16::from_le_bytes(sample) as f32 / -(i16::MIN as f32)),
+            ),
+            Self::S32Le => out.extend(
+                bytes
+                    .as_chunks::<4>()
+                    .0
+                    .iter()
+                    .map(|&sample| i32::from_le_bytes(sample) as f32 / -(i32::MIN as f32)),
+            ),
+            Self::F32Le => out.extend(
+                bytes
+                    .as_chunks::<4>()
+                    .0
+                    .iter()
+                    .map(|&sample| f32::from_le_bytes(sample)),
+            ),
+        }
+    }
+}
+
+impl FromStr for PcmFormat {
+    type Err = PcmFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "s16le" => Ok(Self::S16Le),
+            "s32le" => Ok(Self::S32Le),
+            "f32le" => Ok(Self::F32Le),
+            _ => Err(PcmFormatError(s.to_string())),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Unknown PCM format '{0}'. Available formats: s16le, s32le, f32le")]
+pub struct PcmFormatError(String);
+
+/// Converts a stream of bytes into interleaved samples.
+///
+/// The bytes may arrive in arbitrary chunks. Incomplete frames are kept until the
+/// rest of the frame arrives, so the channels never get mixed up.
+#[derive(Debug, Clone)]
+pub(crate) struct PcmDecoder {
+    format: PcmFormat,
+    frame_size: usize,
+
+    remainder: Vec<u8>,
+    samples: Vec<f32>,
+}
+
+impl PcmDecoder {
+    pub fn new(format: PcmFormat, amount_channels: u16) -> Self {
+        Self {
+            format,
+            frame_size: format.bytes_per_sample() * amount_channels as usize,
+
+            remainder: Vec::new(),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Returns the samples of all complete frames which are available with the given bytes.
+    pub fn decode(&mut self, bytes: &[u8]) -> &[f32] {
+        self.remainder.extend_from_slice(bytes);
+
+        let complete_len = self.remainder.len() - self.remainder.len() % self.frame_size;
+
+        self.samples.clear();
+        self.format
+            .decode(&self.remainder[..complete_len], &mut self.samples);
+        self.remainder.drain(..complete_len);
+
+        &self.samples
+    }
+
+    /// Drops the bytes of the incomplete frame (for example if the writer disconnected).
+    pub fn reset(&mut self) {
+        self.remainder.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_format() {
+        assert_eq!(PcmFormat::from_str("s16le").unwrap(), PcmFormat::S16Le);
+        assert_eq!(PcmFormat::from_str("S32LE").unwrap(), PcmFormat::S32Le);
+        assert_eq!(PcmFormat::from_str("f32le").unwrap(), PcmFormat::F32Le);
+        assert!(PcmFormat::from_str("u8").is_err());
+    }
+
+    #[test]
+    fn decode_s16le() {
+        let mut samples = Vec::new();
+        let bytes = [i16::MIN, 0, 16_384]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        PcmFormat::S16Le.decode(&bytes, &mut samples);
+
+        assert_eq!(samples, [-1., 0., 0.5]);
+    }
+
+    #[test]
+    fn decode_f32le() {
+        let mut samples = Vec::new();
+        let bytes = [0.25f32, -0.75]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        PcmFormat::F32Le.decode(&bytes, &mut samples);
+
+        assert_eq!(samples, [0.25, -0.75]);
+    }
+
+    #[test]
+    fn decoder_keeps_incomplete_frames() {
+        let bytes = [0.25f32, -0.25, 0.5, -0.5]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        let mut decoder = PcmDecoder::new(PcmFormat::F32Le, 2);
+
+        // one complete frame and half of the second frame
+        assert_eq!(decoder.decode(&bytes[..12]), [0.25, -0.25]);
+        assert_eq!(decoder.decode(&bytes[12..]), [0.5, -0.5]);
+    }
+}
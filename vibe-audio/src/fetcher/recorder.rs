@@ -0,0 +1,126 @@
+use super::{
+    recording::{self, Header, RecordingError},
+    reserve_sample_buffer, Fetcher, SampleBuffer,
+};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+use tracing::error;
+
+/// Wraps a fetcher and writes everything it pushes into a file,
+/// which can be played back with [crate::fetcher::ReplayFetcher].
+///
+/// The samples are recorded in steps of [Fetcher::advance], so a replay can reproduce
+/// exactly which samples the [crate::SampleProcessor] saw in each processing.
+///
+/// # Example
+/// ```no_run
+/// use vibe_audio::{SampleProcessor, fetcher::{SystemAudioFetcher, SystemAudioFetcherDescriptor, RecordingFetcher}};
+///
+/// let fetcher = SystemAudioFetcher::new(&SystemAudioFetcherDescriptor::default()).unwrap();
+/// let mut sample_processor = SampleProcessor::new(RecordingFetcher::new(fetcher, "session.vrec").unwrap());
+///
+/// // everything which gets processed now is recorded as well
+/// sample_processor.process_next_samples();
+/// ```
+pub struct Recorder<F: Fetcher> {
+    // the samples of `fetcher` are pushed into this buffer after they got recorded
+    sample_buffer: SampleBuffer,
+    fetcher: F,
+
+    // `None` if writing the recording failed
+    writer: Option<BufWriter<File>>,
+    // the value of `SampleBuffer::amount_pushed_samples` of the fetcher when it got read the last time
+    read_samples: u64,
+    // reused buffer for the new samples of the fetcher
+    samples: Vec<f32>,
+}
+
+impl<F: Fetcher> Recorder<F> {
+    /// Starts to record the samples of the given fetcher into the file of the given path.
+    /// An existing file is overwritten.
+    pub fn new(fetcher: F, path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        Header {
+            channels: fetcher.channels(),
+            sample_rate: fetcher.sample_rate(),
+        }
+        .write(&mut writer)?;
+
+        Ok(Self {
+            sample_buffer: SampleBuffer::new(fetcher.sample_rate()),
+            read_samples: reserve_sample_buffer(&fetcher),
+            fetcher,
+
+            writer: Some(writer),
+            samples: Vec::new(),
+        })
+    }
+
+    /// Returns `false` if the recording stopped because the file couldn't be written.
+    pub fn is_recording(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Writes the buffered samples into the file.
+    pub fn flush(&mut self) -> Result<(), RecordingError> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the fetcher which is recorded.
+    pub fn fetcher(&self) -> &F {
+        &self.fetcher
+    }
+
+    /// Returns the fetcher which is recorded.
+    pub fn fetcher_mut(&mut self) -> &mut F {
+        &mut self.fetcher
+    }
+
+    /// Stops the recording and returns the recorded fetcher.
+    pub fn into_inner(mut self) -> Result<F, RecordingError> {
+        self.flush()?;
+        Ok(self.fetcher)
+    }
+}
+
+impl<F: Fetcher> Fetcher for Recorder<F> {
+    fn sample_buffer(&self) -> SampleBuffer {
+        self.sample_buffer.clone()
+    }
+
+    fn channels(&self) -> u16 {
+        self.fetcher.channels()
+    }
+
+    fn advance(&mut self) {
+        self.fetcher.advance();
+
+        self.read_samples = self
+            .fetcher
+            .sample_buffer()
+            .read_since(self.read_samples, &mut self.samples);
+
+        // skip the incomplete frame if older samples got lost
+        let incomplete_samples = self.samples.len() % self.fetcher.channels() as usize;
+        let samples = &self.samples[incomplete_samples..];
+
+        // empty blocks are recorded as well, so the replay keeps the same steps
+        if let Some(writer) = self.writer.as_mut() {
+            // flush each block since the recording usually ends with a signal which doesn't drop the writer
+            let result = recording::write_block(writer, samples).and_then(|_| writer.flush());
+            if let Err(err) = result {
+                error!("Stopped recording the audio: {}", err);
+                self.writer = None;
+            }
+        }
+
+        self.sample_buffer.push(samples);
+    }
+}
@@ -0,0 +1,215 @@
+//! The file format of the recordings of [crate::fetcher::RecordingFetcher].
+//!
+//! All values are little endian:
+//!
+//! | Field           | Type       |
+//! |-----------------|------------|
+//! | magic           | `b"VIBEREC\0"` |
+//! | version         | `u16`      |
+//! | amount channels | `u16`      |
+//! | sample rate     | `u32`      |
+//!
+//! followed by one block for each call of [crate::fetcher::Fetcher::advance]:
+//!
+//! | Field             | Type                 |
+//! |-------------------|----------------------|
+//! | amount of samples | `u32`                |
+//! | samples           | `f32` (interleaved)  |
+//!
+//! The last block might be incomplete if the recording got interrupted. It's ignored by the replay.
+use cpal::SampleRate;
+use std::io::{self, Write};
+use tracing::warn;
+
+const MAGIC: [u8; 8] = *b"VIBEREC\0";
+const VERSION: u16 = 1;
+
+/// Errors which can occur while writing or reading a recording.
+#[derive(thiserror::Error, Debug)]
+pub enum RecordingError {
+    #[error("Couldn't access the recording: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("The file isn't a recording of vibe.")]
+    InvalidMagic,
+
+    #[error("The recording has the unsupported version {0}.")]
+    UnsupportedVersion(u16),
+
+    #[error("The recording has no channels or a sample rate of zero.")]
+    InvalidHeader,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Header {
+    pub channels: u16,
+    pub sample_rate: SampleRate,
+}
+
+impl Header {
+    const SIZE: usize = MAGIC.len() + 2 + 2 + 4;
+
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.channels.to_le_bytes())?;
+        writer.write_all(&self.sample_rate.to_le_bytes())
+    }
+}
+
+/// Writes the samples which got pushed within one step.
+pub(crate) fn write_block(writer: &mut impl Write, samples: &[f32]) -> io::Result<()> {
+    writer.write_all(&(samples.len() as u32).to_le_bytes())?;
+    for sample in samples {
+        writer.write_all(&sample.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// The content of a recording.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Recording {
+    pub header: Header,
+    // the samples of all blocks
+    pub samples: Vec<f32>,
+    // the amount of samples of each block
+    pub blocks: Vec<usize>,
+}
+
+impl Recording {
+    pub fn parse(bytes: &[u8]) -> Result<Self, RecordingError> {
+        let Some((header, mut bytes)) = bytes.split_first_chunk::<{ Header::SIZE }>() else {
+            return Err(RecordingError::InvalidMagic);
+        };
+
+        if header[..MAGIC.len()] != MAGIC {
+            return Err(RecordingError::InvalidMagic);
+        }
+
+        let version = u16::from_le_bytes([header[8], header[9]]);
+        if version != VERSION {
+            return Err(RecordingError::UnsupportedVersion(version));
+        }
+
+        let header = Header {
+            channels: u16::from_le_bytes([header[10], header[11]]),
+            sample_rate: u32::from_le_bytes([header[12], header[13], header[14], header[15]]),
+        };
+        if header.channels == 0 || header.sample_rate == 0 {
+            return Err(RecordingError::InvalidHeader);
+        }
+
+        let mut samples = Vec::new();
+        let mut blocks = Vec::new();
+        while let Some((len, rest)) = bytes.split_first_chunk::<4>() {
+            let amount_samples = u32::from_le_bytes(*len) as usize;
+            if rest.len() < amount_samples * 4 {
+                break;
+            }
+
+            let (block, rest) = rest.split_at(amount_samples * 4);
+            samples.extend(
+                block
+                    .as_chunks::<4>()
+                    .0
+                    .iter()
+                    .map(|bytes| f32::from_le_bytes(*bytes)),
+            );
+            blocks.push(amount_samples);
+
+            bytes = rest;
+        }
+
+        if !bytes.is_empty() {
+            warn!(
+                "The recording ends in the middle of a block (probably because vibe got killed). Ignoring the last {} bytes.",
+                bytes.len()
+            );
+        }
+
+        Ok(Self {
+            header,
+            samples,
+            blocks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: Header = Header {
+        channels: 2,
+        sample_rate: 48_000,
+    };
+
+    fn encode(blocks: &[&[f32]]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        HEADER.write(&mut bytes).unwrap();
+        for block in blocks {
+            write_block(&mut bytes, block).unwrap();
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn roundtrip() {
+        let recording = Recording::parse(&encode(&[&[0.5, -0.5], &[], &[1., 0.25, -1., 0.]]));
+
+        assert_eq!(
+            recording.unwrap(),
+            Recording {
+                header: HEADER,
+                samples: vec![0.5, -0.5, 1., 0.25, -1., 0.],
+                blocks: vec![2, 0, 4],
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_magic() {
+        let mut bytes = encode(&[]);
+        bytes[0] = b'X';
+
+        assert!(matches!(
+            Recording::parse(&bytes),
+            Err(RecordingError::InvalidMagic)
+        ));
+        assert!(matches!(
+            Recording::parse(b"VIBE"),
+            Err(RecordingError::InvalidMagic)
+        ));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut bytes = encode(&[]);
+        bytes[8] = 2;
+
+        assert!(matches!(
+            Recording::parse(&bytes),
+            Err(RecordingError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn truncated_block_is_ignored() {
+        let expected = Recording {
+            header: HEADER,
+            samples: vec![0.5, -0.5],
+            blocks: vec![2],
+        };
+
+        let mut bytes = encode(&[&[0.5, -0.5], &[1., 0.25]]);
+        bytes.pop();
+        assert_eq!(Recording::parse(&bytes).unwrap(), expected);
+
+        // only a part of the length of the last block got written
+        let mut bytes = encode(&[&[0.5, -0.5]]);
+        bytes.extend_from_slice(&[2, 0]);
+        assert_eq!(Recording::parse(&bytes).unwrap(), expected);
+    }
+}
@@ -0,0 +1,269 @@
+use super::{
+    pacing::{FrameSource, Worker},
+    recording::{Recording, RecordingError},
+    Fetcher, SampleBuffer,
+};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::debug;
+
+/// Decides how [crate::fetcher::ReplayFetcher] plays back the recording.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Push the samples of exactly one recorded step each time
+    /// [crate::SampleProcessor::process_next_samples] is called.
+    ///
+    /// The processor sees the same samples in each processing as during the recording,
+    /// regardless of how much time passes in between.
+    #[default]
+    FrameAccurate,
+
+    /// Push the samples with the speed of the sample rate, like they were recorded.
+    RealTime,
+}
+
+pub struct Descriptor {
+    /// The path to the recording of [crate::fetcher::RecordingFetcher].
+    pub path: PathBuf,
+
+    pub mode: ReplayMode,
+}
+
+/// Fetcher which plays back a recording of [crate::fetcher::RecordingFetcher].
+///
+/// Silence is pushed after the end of the recording in [ReplayMode::RealTime].
+pub struct Replay {
+    sample_buffer: SampleBuffer,
+    channels: u16,
+    duration: Duration,
+
+    player: Arc<Mutex<Player>>,
+    mode: ReplayMode,
+
+    // only set for `ReplayMode::RealTime`
+    worker: Option<Worker>,
+}
+
+impl Replay {
+    pub fn new(desc: &Descriptor) -> Result<Self, RecordingError> {
+        let recording = Recording::parse(&std::fs::read(&desc.path)?)?;
+
+        let channels = recording.header.channels;
+        let sample_rate = recording.header.sample_rate;
+        let duration = Duration::from_secs_f64(
+            (recording.samples.len() / channels as usize) as f64 / sample_rate as f64,
+        );
+
+        debug!(
+            "Recording: {} channels, {} Hz, {} steps, duration: {:?}",
+            channels,
+            sample_rate,
+            recording.blocks.len(),
+            duration
+        );
+
+        let sample_buffer = SampleBuffer::new(sample_rate);
+        let player = Arc::new(Mutex::new(Player::new(recording)));
+
+        let worker = match desc.mode {
+            ReplayMode::RealTime => Some(Worker::spawn(
+                player.clone(),
+                sample_buffer.clone(),
+                sample_rate,
+            )),
+            ReplayMode::FrameAccurate => None,
+        };
+
+        Ok(Self {
+            sample_buffer,
+            channels,
+            duration,
+
+            player,
+            mode: desc.mode,
+
+            worker,
+        })
+    }
+
+    /// Returns the duration of the recording.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the amount of recorded steps (calls of [Fetcher::advance]).
+    pub fn amount_steps(&self) -> usize {
+        self.player.lock().unwrap().recording.blocks.len()
+    }
+
+    /// Returns `true` if the whole recording has been played back.
+    pub fn is_finished(&self) -> bool {
+        let player = self.player.lock().unwrap();
+
+        match self.mode {
+            ReplayMode::FrameAccurate => player.block >= player.recording.blocks.len(),
+            ReplayMode::RealTime => player.position >= player.recording.samples.len(),
+        }
+    }
+}
+
+impl Fetcher for Replay {
+    fn sample_buffer(&self) -> SampleBuffer {
+        self.sample_buffer.clone()
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn advance(&mut self) {
+        if self.mode == ReplayMode::FrameAccurate {
+            let mut player = self.player.lock().unwrap();
+            if let Some(samples) = player.next_block() {
+                self.sample_buffer.push(samples);
+            }
+        }
+    }
+}
+
+impl Drop for Replay {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            worker.stop();
+        }
+    }
+}
+
+/// Keeps track of the position within the recording.
+struct Player {
+    recording: Recording,
+
+    // the index of the next block (only used by `ReplayMode::FrameAccurate`)
+    block: usize,
+    // the index of the next sample
+    position: usize,
+}
+
+impl Player {
+    fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            block: 0,
+            position: 0,
+        }
+    }
+
+    fn next_block(&mut self) -> Option<&[f32]> {
+        let amount_samples = *self.recording.blocks.get(self.block)?;
+        let samples = &self.recording.samples[self.position..self.position + amount_samples];
+
+        self.block += 1;
+        self.position += amount_samples;
+        Some(samples)
+    }
+}
+
+impl FrameSource for Player {
+    fn read(&mut self, amount_frames: usize, out: &mut Vec<f32>) {
+        let amount_samples = amount_frames * self.recording.header.channels as usize;
+        let end = (self.position + amount_samples).min(self.recording.samples.len());
+
+        out.clear();
+        out.extend_from_slice(&self.recording.samples[self.position..end]);
+        out.resize(amount_samples, 0.);
+
+        self.position = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fetcher::{pacing::REAL_TIME_TICK, DummyFetcher, RecordingFetcher},
+        SampleProcessor,
+    };
+
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("vibe-audio-{}-{}.vrec", name, std::process::id()))
+    }
+
+    /// Records the given (stereo) steps and returns the samples of the processor after each step.
+    fn record(path: &PathBuf, frames_per_step: &[usize]) -> Vec<Vec<f32>> {
+        let mut processor =
+            SampleProcessor::new(RecordingFetcher::new(DummyFetcher::new(2), path).unwrap());
+
+        let mut steps = Vec::new();
+        for (step, &amount_frames) in frames_per_step.iter().enumerate() {
+            let samples: Vec<f32> = (0..amount_frames * 2)
+                .map(|idx| ((step * 1_000 + idx) as f32).sin())
+                .collect();
+            // the dummy fetcher doesn't push anything by itself
+            processor.fetcher().fetcher().sample_buffer().push(&samples);
+
+            processor.process_next_samples();
+            steps.push(processor.samples(0).to_vec());
+        }
+
+        processor.fetcher_mut().flush().unwrap();
+        steps
+    }
+
+    #[test]
+    fn frame_accurate_replay() {
+        let path = path("frame-accurate");
+        let recorded_steps = record(&path, &[128, 0, 300, 7]);
+
+        let replay = Replay::new(&Descriptor {
+            path: path.clone(),
+            mode: ReplayMode::FrameAccurate,
+        })
+        .unwrap();
+        assert_eq!(replay.channels(), 2);
+        assert_eq!(replay.sample_rate(), 44_100);
+        assert_eq!(replay.amount_steps(), 4);
+
+        let mut processor = SampleProcessor::new(replay);
+        for recorded_samples in recorded_steps {
+            assert!(!processor.fetcher().is_finished());
+
+            processor.process_next_samples();
+            assert_eq!(processor.samples(0), recorded_samples.as_slice());
+        }
+        assert!(processor.fetcher().is_finished());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn real_time_replay() {
+        let path = path("real-time");
+        record(&path, &[441, 441]);
+
+        let replay = Replay::new(&Descriptor {
+            path: path.clone(),
+            mode: ReplayMode::RealTime,
+        })
+        .unwrap();
+        assert_eq!(replay.duration(), Duration::from_millis(20));
+
+        std::thread::sleep(REAL_TIME_TICK * 10);
+        assert!(replay.is_finished());
+        assert!(replay.sample_buffer().amount_pushed_samples() >= 2 * 441 * 2);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn missing_file() {
+        let result = Replay::new(&Descriptor {
+            path: path("missing"),
+            mode: ReplayMode::FrameAccurate,
+        });
+
+        assert!(matches!(result, Err(RecordingError::IO(_))));
+    }
+}
@@ -0,0 +1,477 @@
+use arc_swap::{ArcSwap, ArcSwapOption};
+use cpal::SampleRate;
+use std::sync::{
+    atomic::{fence, AtomicU32, AtomicU64, Ordering},
+    Arc,
+};
+
+/// Holds the most recent audio samples which get pushed by the fetcher.
+///
+/// It's a ring buffer for one producer (the fetcher, for example within the callback of an audio stream)
+/// and any amount of readers (for example [crate::SampleProcessor]) which take snapshots of it.
+/// Neither side ever blocks the other one: pushing is wait-free and a snapshot only gets retried
+/// if the producer overwrote the samples while they got copied.
+///
+/// Cloning the buffer is cheap and returns a handle to the same samples.
+///
+/// # Example
+/// ```
+/// use vibe_audio::fetcher::SampleBuffer;
+///
+/// let sample_buffer = SampleBuffer::new(44_100);
+/// sample_buffer.push(&[1., 2.]);
+/// sample_buffer.push(&[3.]);
+///
+/// let mut snapshot = [0f32; 4];
+/// let amount_pushed_samples = sample_buffer.snapshot(&mut snapshot);
+///
+/// // the oldest sample comes first
+/// assert_eq!(snapshot, [0., 1., 2., 3.]);
+/// assert_eq!(amount_pushed_samples, 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SampleBuffer {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    ring: ArcSwap<Ring>,
+    // the ring with the new capacity which the producer swaps in with its next push
+    pending_ring: ArcSwapOption<Ring>,
+    sample_rate: SampleRate,
+
+    // the amount of samples which have been pushed since the creation of the buffer
+    written: AtomicU64,
+    // the value which `written` will have once the current push is done
+    writing: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Ring {
+    // the amount of samples which can be read
+    capacity: usize,
+    // Twice the capacity, so the producer can keep pushing while a reader copies the newest samples.
+    // The samples are stored as the bits of a `f32`.
+    slots: Box<[AtomicU32]>,
+}
+
+impl Ring {
+    fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            capacity,
+            slots: (0..2 * capacity).map(|_| AtomicU32::new(0)).collect(),
+        }
+    }
+
+    /// Returns the slots of the `amount` samples which start with the given (absolute) sample index.
+    ///
+    /// The slots are split into two parts where the ring wraps around.
+    fn slots(&self, start: u64, amount: usize) -> [&[AtomicU32]; 2] {
+        let offset = (start % self.slots.len() as u64) as usize;
+        let (wrapped, first) = self.slots.split_at(offset);
+        let amount_first = amount.min(first.len());
+
+        [&first[..amount_first], &wrapped[..amount - amount_first]]
+    }
+
+    /// Copies the newest samples (up to the `end`-th sample) of the other ring which fit into this ring.
+    fn copy_from(&self, other: &Ring, end: u64) {
+        let amount = end.min(self.capacity.min(other.capacity) as u64);
+        for idx in end - amount..end {
+            let bits = other.slot(idx).load(Ordering::Relaxed);
+            self.slot(idx).store(bits, Ordering::Relaxed);
+        }
+    }
+
+    fn slot(&self, idx: u64) -> &AtomicU32 {
+        &self.slots[(idx % self.slots.len() as u64) as usize]
+    }
+
+    fn store(&self, start: u64, samples: &[f32]) {
+        let mut samples = samples;
+        for slots in self.slots(start, samples.len()) {
+            let (current, next) = samples.split_at(slots.len());
+            for (slot, sample) in slots.iter().zip(current) {
+                slot.store(sample.to_bits(), Ordering::Relaxed);
+            }
+            samples = next;
+        }
+    }
+
+    fn load(&self, start: u64, out: &mut [f32]) {
+        let mut out = out;
+        for slots in self.slots(start, out.len()) {
+            let (current, next) = out.split_at_mut(slots.len());
+            for (sample, slot) in current.iter_mut().zip(slots) {
+                *sample = f32::from_bits(slot.load(Ordering::Relaxed));
+            }
+            out = next;
+        }
+    }
+}
+
+impl SampleBuffer {
+    /// Create a new instance for the given sample rate.
+    pub fn new(sample_rate: SampleRate) -> Self {
+        // props to cava for this heuristic.
+        let factor = if sample_rate < 8_125 {
+            1
+        } else if sample_rate <= 16_250 {
+            2
+        } else if sample_rate <= 32_500 {
+            4
+        } else if sample_rate <= 75_000 {
+            8
+        } else if sample_rate <= 150_000 {
+            16
+        } else if sample_rate <= 300_000 {
+            32
+        } else {
+            64
+        };
+
+        Self {
+            shared: Arc::new(Shared {
+                ring: ArcSwap::from_pointee(Ring::new(factor * 128)),
+                pending_ring: ArcSwapOption::empty(),
+                sample_rate,
+
+                written: AtomicU64::new(0),
+                writing: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Appends the given (interleaved) samples.
+    ///
+    /// If more samples than the capacity are given, only the most recent ones are kept.
+    ///
+    /// Only one thread may push into the buffer at a time, the samples of concurrent pushes get garbled.
+    pub fn push(&self, data: &[f32]) {
+        if data.is_empty() {
+            return;
+        }
+
+        let shared = &self.shared;
+
+        // only the producer changes `written`
+        let start = shared.written.load(Ordering::Relaxed);
+
+        // The producer resizes the ring by itself, otherwise the samples which it pushes while
+        // the newest samples get copied into the new ring would be lost.
+        let pending_ring = shared.pending_ring.load();
+        if let Some(new_ring) = pending_ring.as_ref() {
+            new_ring.copy_from(&shared.ring.load(), start);
+            shared.ring.store(new_ring.clone());
+
+            // keep the ring if another capacity has been set in the meantime
+            shared.pending_ring.compare_and_swap(&pending_ring, None);
+        }
+
+        let ring = shared.ring.load();
+        let end = start + data.len() as u64;
+        let skipped = data.len().saturating_sub(ring.capacity);
+
+        shared.writing.store(end, Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        ring.store(start + skipped as u64, &data[skipped..]);
+
+        shared.written.store(end, Ordering::Release);
+    }
+
+    /// The name of [SampleBuffer::push] before the buffer became a ring buffer.
+    ///
+    /// Kept so custom fetchers keep compiling.
+    ///
+    /// **Its behaviour changed:** It appends the samples just like [SampleBuffer::push],
+    /// they aren't written to the front of the buffer anymore.
+    #[deprecated(
+        note = "behaviour changed: the samples are appended now (the newest sample comes last) instead of being written to the front. Use `SampleBuffer::push` instead."
+    )]
+    pub fn push_before(&self, data: &[f32]) {
+        self.push(data);
+    }
+
+    /// Copies the most recent `out.len()` samples into `out` with the oldest sample first.
+    ///
+    /// If the buffer holds less samples (see [SampleBuffer::capacity]) or less samples have been pushed,
+    /// the beginning of `out` is filled with zeros.
+    ///
+    /// Returns the value of [SampleBuffer::amount_pushed_samples] which belongs to the snapshot.
+    pub fn snapshot(&self, out: &mut [f32]) -> u64 {
+        loop {
+            let ring = self.shared.ring.load();
+
+            let amount_samples = out.len().min(ring.capacity);
+            let (padding, samples) = out.split_at_mut(out.len() - amount_samples);
+            padding.fill(0.);
+
+            let end = self.shared.written.load(Ordering::Acquire);
+            if self.copy(&ring, end, samples) {
+                return end;
+            }
+        }
+    }
+
+    /// Replaces the content of `out` with the samples which have been pushed after `since` samples
+    /// (see [SampleBuffer::amount_pushed_samples]), with the oldest sample first.
+    ///
+    /// At most [SampleBuffer::capacity] samples are returned, older samples are lost.
+    ///
+    /// Returns the value of [SampleBuffer::amount_pushed_samples] which belongs to the read samples,
+    /// so it can be passed to the next call.
+    pub fn read_since(&self, since: u64, out: &mut Vec<f32>) -> u64 {
+        loop {
+            let ring = self.shared.ring.load();
+            let end = self.shared.written.load(Ordering::Acquire);
+            let amount_samples = end.saturating_sub(since).min(ring.capacity as u64) as usize;
+
+            out.clear();
+            out.resize(amount_samples, 0.);
+            if self.copy(&ring, end, out) {
+                return end;
+            }
+        }
+    }
+
+    /// Copies the samples right before the `end`-th sample into `out`.
+    ///
+    /// Returns `false` if the producer overwrote some of them in the meantime
+    /// or pushed them into a resized ring.
+    fn copy(&self, ring: &Ring, end: u64, out: &mut [f32]) -> bool {
+        let start = end.saturating_sub(out.len() as u64);
+        let (never_pushed, out) = out.split_at_mut(out.len() - (end - start) as usize);
+
+        never_pushed.fill(0.);
+        ring.load(start, out);
+
+        fence(Ordering::Acquire);
+        self.shared.writing.load(Ordering::Relaxed) <= start + ring.slots.len() as u64
+            && std::ptr::eq(ring, &**self.shared.ring.load())
+    }
+
+    pub fn sample_rate(&self) -> SampleRate {
+        self.shared.sample_rate
+    }
+
+    /// Returns the amount of samples (of all channels) which have been pushed since the creation of the buffer.
+    pub fn amount_pushed_samples(&self) -> u64 {
+        self.shared.written.load(Ordering::Acquire)
+    }
+
+    /// Changes the amount of samples (of all channels) which the buffer can hold.
+    ///
+    /// The most recent samples are kept.
+    /// The new capacity takes effect with the next push, so it can be changed while the audio is flowing.
+    pub fn set_capacity(&self, capacity: usize) {
+        self.shared
+            .pending_ring
+            .store(Some(Arc::new(Ring::new(capacity))));
+    }
+
+    /// Returns the amount of samples (of all channels) which the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        match self.shared.pending_ring.load().as_ref() {
+            Some(ring) => ring.capacity,
+            None => self.shared.ring.load().capacity,
+        }
+    }
+
+    /// Returns all samples of the buffer with the oldest sample first.
+    pub fn to_vec(&self) -> Vec<f32> {
+        let mut samples = vec![0f32; self.capacity()];
+        self.snapshot(&mut samples);
+        samples
+    }
+
+    /// The samples of the buffer can't be borrowed anymore because the fetcher might push into them at any time.
+    ///
+    /// Kept so custom fetchers keep compiling. Returns the same as [SampleBuffer::to_vec].
+    ///
+    /// **Its behaviour changed:** The oldest sample comes first now and the samples are copied.
+    #[deprecated(
+        note = "behaviour changed: the oldest sample comes first now. Use `SampleBuffer::to_vec` or `SampleBuffer::snapshot` instead."
+    )]
+    pub fn buffer(&self) -> Vec<f32> {
+        self.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_more_than_capacity() {
+        // buffer should have length of `1` * `128`
+        let sample_buffer = SampleBuffer::new(1);
+        sample_buffer.push(&[1f32; 129]);
+
+        let samples = sample_buffer.to_vec();
+        assert_eq!(samples.len(), 128);
+        assert!(samples.iter().all(|&value| value == 1f32));
+    }
+
+    #[test]
+    fn new_values_are_appended() {
+        let sample_buffer = SampleBuffer::new(1);
+        sample_buffer.push(&[1., 2.]);
+        sample_buffer.push(&[3.]);
+
+        let samples = sample_buffer.to_vec();
+        assert_eq!(samples.len(), 128);
+        assert_eq!(samples[125..], [1., 2., 3.]);
+        assert!(samples[..125].iter().all(|&value| value == 0f32));
+    }
+
+    #[test]
+    fn wraps_around() {
+        let sample_buffer = SampleBuffer::new(1);
+        for chunk in (0..1000).collect::<Vec<u16>>().chunks(7) {
+            let chunk: Vec<f32> = chunk.iter().map(|&value| value as f32).collect();
+            sample_buffer.push(&chunk);
+        }
+
+        let expected: Vec<f32> = (872..1000).map(|value| value as f32).collect();
+        assert_eq!(sample_buffer.to_vec(), expected);
+    }
+
+    #[test]
+    fn counts_pushed_samples() {
+        let sample_buffer = SampleBuffer::new(1);
+        sample_buffer.push(&[1f32; 129]);
+        sample_buffer.push(&[1f32; 3]);
+
+        assert_eq!(sample_buffer.amount_pushed_samples(), 132);
+    }
+
+    #[test]
+    fn read_since() {
+        let sample_buffer = SampleBuffer::new(1);
+        let mut samples = Vec::new();
+
+        sample_buffer.push(&[1., 2.]);
+        let read = sample_buffer.read_since(0, &mut samples);
+        assert_eq!(samples, [1., 2.]);
+
+        sample_buffer.push(&[3., 4.]);
+        assert_eq!(sample_buffer.read_since(read, &mut samples), 4);
+        assert_eq!(samples, [3., 4.]);
+
+        // older samples are lost
+        sample_buffer.push(&[5f32; 200]);
+        sample_buffer.read_since(read, &mut samples);
+        assert_eq!(samples.len(), 128);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn push_before_appends() {
+        let sample_buffer = SampleBuffer::new(1);
+        sample_buffer.set_capacity(3);
+
+        sample_buffer.push_before(&[1., 2.]);
+        sample_buffer.push_before(&[3.]);
+        assert_eq!(sample_buffer.buffer(), [1., 2., 3.]);
+    }
+
+    #[test]
+    fn set_capacity_keeps_the_newest_values() {
+        let sample_buffer = SampleBuffer::new(1);
+        sample_buffer.push(&[1., 2., 3.]);
+
+        sample_buffer.set_capacity(2);
+        assert_eq!(sample_buffer.to_vec(), [2., 3.]);
+
+        sample_buffer.push(&[4.]);
+        assert_eq!(sample_buffer.to_vec(), [3., 4.]);
+
+        sample_buffer.set_capacity(4);
+        assert_eq!(sample_buffer.to_vec(), [0., 0., 3., 4.]);
+
+        sample_buffer.push(&[5.]);
+        assert_eq!(sample_buffer.to_vec(), [0., 3., 4., 5.]);
+    }
+
+    #[test]
+    fn set_capacity_while_pushing() {
+        let sample_buffer = SampleBuffer::new(1);
+
+        let producer = std::thread::spawn({
+            let sample_buffer = sample_buffer.clone();
+
+            move || {
+                // exactly representable as `f32`
+                for chunk in (1..200_000).collect::<Vec<u32>>().chunks(50) {
+                    let chunk: Vec<f32> = chunk.iter().map(|&value| value as f32).collect();
+                    sample_buffer.push(&chunk);
+                }
+            }
+        });
+
+        let mut snapshot = [0f32; 512];
+        for capacity in [64, 512, 128, 256].into_iter().cycle() {
+            if producer.is_finished() {
+                break;
+            }
+            sample_buffer.set_capacity(capacity);
+
+            // none of the pushed samples may be missing
+            let end = sample_buffer.snapshot(&mut snapshot);
+            let amount_samples = snapshot.iter().rev().take_while(|&&s| s != 0.).count();
+            assert!(snapshot[..snapshot.len() - amount_samples]
+                .iter()
+                .all(|&sample| sample == 0.));
+            for (sample, sample_idx) in snapshot
+                .iter()
+                .rev()
+                .zip((0..end).rev())
+                .take(amount_samples)
+            {
+                assert_eq!(*sample, (sample_idx + 1) as f32);
+            }
+        }
+    }
+
+    #[test]
+    fn no_values_pushed() {
+        let sample_buffer = SampleBuffer::new(1);
+        sample_buffer.push(&[]);
+
+        let samples = sample_buffer.to_vec();
+        assert_eq!(samples.len(), 128);
+        assert!(samples.iter().all(|&value| value == 0f32));
+    }
+
+    #[test]
+    fn snapshots_are_consistent_while_pushing() {
+        let sample_buffer = SampleBuffer::new(1);
+
+        let producer = std::thread::spawn({
+            let sample_buffer = sample_buffer.clone();
+
+            move || {
+                // exactly representable as `f32`
+                for chunk in (1..200_000).collect::<Vec<u32>>().chunks(50) {
+                    let chunk: Vec<f32> = chunk.iter().map(|&value| value as f32).collect();
+                    sample_buffer.push(&chunk);
+                }
+            }
+        });
+
+        let mut snapshot = [0f32; 128];
+        while !producer.is_finished() {
+            let end = sample_buffer.snapshot(&mut snapshot);
+
+            for (sample, sample_idx) in snapshot.iter().rev().zip((0..end).rev()) {
+                assert_eq!(*sample, (sample_idx + 1) as f32);
+            }
+        }
+
+        producer.join().unwrap();
+    }
+}
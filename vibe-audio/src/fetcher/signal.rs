@@ -0,0 +1,432 @@
+use super::{
+    pacing::{FrameSource, Worker},
+    Fetcher, Pacing, SampleBuffer,
+};
+use cpal::SampleRate;
+use std::{
+    f64::consts::TAU,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// The time in which an impulse of [Signal::Impulses] decays.
+const IMPULSE_DECAY: Duration = Duration::from_millis(10);
+
+/// Errors which can occur while creating [crate::fetcher::SignalFetcher].
+#[derive(thiserror::Error, Debug)]
+pub enum SignalError {
+    #[error("The amount of channels must be at least one.")]
+    NoChannels,
+
+    #[error("The sample rate must be greater than zero.")]
+    InvalidSampleRate,
+
+    #[error("Invalid signal {0:?}: frequencies, BPM and durations must be greater than zero.")]
+    InvalidSignal(Signal),
+}
+
+/// A signal which can be generated by [crate::fetcher::SignalFetcher].
+///
+/// `amplitude` should be within `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    /// A sine tone with the given frequency (in Hz).
+    Sine { frequency: f32, amplitude: f32 },
+
+    /// A sine tone whose frequency rises exponentially from `from` to `to` (in Hz) within `duration`.
+    /// Starts again with `from` afterwards.
+    Sweep {
+        from: f32,
+        to: f32,
+        duration: Duration,
+        amplitude: f32,
+    },
+
+    /// Noise with the same power in every frequency.
+    WhiteNoise { amplitude: f32 },
+
+    /// Noise with the same power in every octave (the power decreases by 3 dB per octave).
+    PinkNoise { amplitude: f32 },
+
+    /// A short click (a quickly decaying noise burst) on each beat.
+    Impulses { bpm: f32, amplitude: f32 },
+
+    /// Nothing.
+    Silence,
+}
+
+impl Signal {
+    fn is_valid(&self) -> bool {
+        match *self {
+            Self::Sine { frequency, .. } => frequency > 0.,
+            Self::Sweep {
+                from, to, duration, ..
+            } => from > 0. && to > 0. && !duration.is_zero(),
+            Self::Impulses { bpm, .. } => bpm > 0.,
+            Self::WhiteNoise { .. } | Self::PinkNoise { .. } | Self::Silence => true,
+        }
+    }
+}
+
+pub struct Descriptor {
+    /// The signals which are summed up. Each channel gets the same samples.
+    pub signals: Vec<Signal>,
+    pub sample_rate: SampleRate,
+    pub amount_channels: u16,
+    pub pacing: Pacing,
+}
+
+/// Fetcher which generates synthetic signals (for example for demos and tests).
+pub struct SignalFetcher {
+    sample_buffer: SampleBuffer,
+    channels: u16,
+
+    generator: Arc<Mutex<Generator>>,
+    pacing: Pacing,
+    // reused buffer for the samples which are pushed in offline mode
+    frames: Vec<f32>,
+
+    // only set for `Pacing::RealTime`
+    worker: Option<Worker>,
+}
+
+impl SignalFetcher {
+    pub fn new(desc: &Descriptor) -> Result<Self, SignalError> {
+        if desc.amount_channels == 0 {
+            return Err(SignalError::NoChannels);
+        }
+
+        if desc.sample_rate == 0 {
+            return Err(SignalError::InvalidSampleRate);
+        }
+
+        if let Some(signal) = desc.signals.iter().find(|signal| !signal.is_valid()) {
+            return Err(SignalError::InvalidSignal(*signal));
+        }
+
+        let sample_buffer = SampleBuffer::new(desc.sample_rate);
+        let generator = Arc::new(Mutex::new(Generator::new(desc)));
+
+        let worker = match desc.pacing {
+            Pacing::RealTime => Some(Worker::spawn(
+                generator.clone(),
+                sample_buffer.clone(),
+                desc.sample_rate,
+            )),
+            Pacing::Offline { .. } => None,
+        };
+
+        Ok(Self {
+            sample_buffer,
+            channels: desc.amount_channels,
+
+            generator,
+            pacing: desc.pacing,
+            frames: Vec::new(),
+
+            worker,
+        })
+    }
+}
+
+impl Fetcher for SignalFetcher {
+    fn sample_buffer(&self) -> SampleBuffer {
+        self.sample_buffer.clone()
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn advance(&mut self) {
+        if let Pacing::Offline { frames_per_step } = self.pacing {
+            self.generator
+                .lock()
+                .unwrap()
+                .read(frames_per_step.get(), &mut self.frames);
+
+            self.sample_buffer.push(&self.frames);
+        }
+    }
+}
+
+impl Drop for SignalFetcher {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            worker.stop();
+        }
+    }
+}
+
+/// Computes the samples of the signals.
+struct Generator {
+    oscillators: Box<[Oscillator]>,
+    sample_rate: f64,
+    channels: usize,
+
+    // the index of the next frame
+    frame: u64,
+    rng: XorShift,
+}
+
+impl Generator {
+    fn new(desc: &Descriptor) -> Self {
+        Self {
+            oscillators: desc
+                .signals
+                .iter()
+                .map(|&signal| Oscillator {
+                    signal,
+                    phase: 0.,
+                    pink: [0.; 7],
+                })
+                .collect(),
+            sample_rate: desc.sample_rate as f64,
+            channels: desc.amount_channels as usize,
+
+            frame: 0,
+            rng: XorShift::default(),
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let time = self.frame as f64 / self.sample_rate;
+        self.frame += 1;
+
+        let mut sample = 0.;
+        for oscillator in self.oscillators.iter_mut() {
+            sample += oscillator.next_sample(time, self.sample_rate, &mut self.rng);
+        }
+
+        sample.clamp(-1., 1.)
+    }
+}
+
+impl FrameSource for Generator {
+    fn read(&mut self, amount_frames: usize, out: &mut Vec<f32>) {
+        out.clear();
+        out.reserve(amount_frames * self.channels);
+
+        for _ in 0..amount_frames {
+            let sample = self.next_sample();
+            out.extend(std::iter::repeat_n(sample, self.channels));
+        }
+    }
+}
+
+/// The state of a single signal.
+struct Oscillator {
+    signal: Signal,
+    // within [0, 1)
+    phase: f64,
+    // the filter state of the pink noise
+    pink: [f32; 7],
+}
+
+impl Oscillator {
+    fn next_sample(&mut self, time: f64, sample_rate: f64, rng: &mut XorShift) -> f32 {
+        match self.signal {
+            Signal::Sine {
+                frequency,
+                amplitude,
+            } => amplitude * self.next_phase(frequency as f64 / sample_rate),
+            Signal::Sweep {
+                from,
+                to,
+                duration,
+                amplitude,
+            } => {
+                let progress = (time / duration.as_secs_f64()).fract();
+                let frequency = from as f64 * (to as f64 / from as f64).powf(progress);
+
+                amplitude * self.next_phase(frequency / sample_rate)
+            }
+            Signal::WhiteNoise { amplitude } => amplitude * rng.next_f32(),
+            Signal::PinkNoise { amplitude } => {
+                // Paul Kellet's refined method: https://www.firstpr.com.au/dsp/pink-noise/
+                let white = rng.next_f32();
+                let b = &mut self.pink;
+
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.153852;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+                b[6] = white * 0.115926;
+
+                // the filter has a gain of roughly 5
+                amplitude * pink * 0.2
+            }
+            Signal::Impulses { bpm, amplitude } => {
+                let beat_duration = 60. / bpm as f64;
+                let since_beat = time % beat_duration;
+                let envelope = (-since_beat / IMPULSE_DECAY.as_secs_f64()).exp() as f32;
+
+                amplitude * envelope * rng.next_f32()
+            }
+            Signal::Silence => 0.,
+        }
+    }
+
+    /// Returns the sine of the current phase and moves the phase forward.
+    fn next_phase(&mut self, step: f64) -> f32 {
+        let sample = (self.phase * TAU).sin() as f32;
+        self.phase = (self.phase + step).fract();
+        sample
+    }
+}
+
+/// A tiny, deterministic pseudo random number generator for the noise signals.
+struct XorShift(u64);
+
+impl Default for XorShift {
+    fn default() -> Self {
+        Self(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+impl XorShift {
+    /// Returns a value within `[-1, 1)`.
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        // use the upper 24 bits since they fit into the mantissa of a f32
+        (self.0 >> 40) as f32 / (1u64 << 23) as f32 - 1.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZero;
+
+    fn offline(signals: Vec<Signal>, amount_channels: u16) -> SignalFetcher {
+        SignalFetcher::new(&Descriptor {
+            signals,
+            sample_rate: 44_100,
+            amount_channels,
+            pacing: Pacing::Offline {
+                frames_per_step: NonZero::new(256).unwrap(),
+            },
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn no_channels() {
+        let result = SignalFetcher::new(&Descriptor {
+            signals: vec![Signal::Silence],
+            sample_rate: 44_100,
+            amount_channels: 0,
+            pacing: Pacing::RealTime,
+        });
+
+        assert!(matches!(result, Err(SignalError::NoChannels)));
+    }
+
+    #[test]
+    fn invalid_signal() {
+        let result = SignalFetcher::new(&Descriptor {
+            signals: vec![Signal::Impulses {
+                bpm: 0.,
+                amplitude: 1.,
+            }],
+            sample_rate: 44_100,
+            amount_channels: 1,
+            pacing: Pacing::RealTime,
+        });
+
+        assert!(matches!(result, Err(SignalError::InvalidSignal(_))));
+    }
+
+    #[test]
+    fn sine_has_the_given_frequency() {
+        // 441 Hz => one period every 100 samples
+        let mut fetcher = offline(
+            vec![Signal::Sine {
+                frequency: 441.,
+                amplitude: 0.5,
+            }],
+            1,
+        );
+        fetcher.advance();
+
+        let mut samples = Vec::new();
+        fetcher.sample_buffer().read_since(0, &mut samples);
+        assert_eq!(samples.len(), 256);
+
+        assert_eq!(samples[0], 0.);
+        assert!((samples[25] - 0.5).abs() < 1e-4);
+        assert!((samples[75] + 0.5).abs() < 1e-4);
+        assert!((samples[0] - samples[100]).abs() < 1e-4);
+    }
+
+    #[test]
+    fn every_channel_gets_the_same_samples() {
+        let mut fetcher = offline(vec![Signal::WhiteNoise { amplitude: 1. }], 2);
+        fetcher.advance();
+
+        let mut samples = Vec::new();
+        fetcher.sample_buffer().read_since(0, &mut samples);
+        let frames = samples.as_chunks::<2>().0;
+
+        assert!(frames.iter().all(|[left, right]| left == right));
+        assert!(frames.iter().any(|[left, _]| *left != 0.));
+    }
+
+    #[test]
+    fn impulses_on_each_beat() {
+        // 120 BPM => one beat every 0.5 seconds
+        let mut generator = Generator::new(&Descriptor {
+            signals: vec![Signal::Impulses {
+                bpm: 120.,
+                amplitude: 1.,
+            }],
+            sample_rate: 1_000,
+            amount_channels: 1,
+            pacing: Pacing::RealTime,
+        });
+
+        let mut samples = Vec::new();
+        generator.read(1_000, &mut samples);
+
+        let loudness = |range: std::ops::Range<usize>| {
+            samples[range]
+                .iter()
+                .map(|sample| sample.abs())
+                .sum::<f32>()
+        };
+
+        assert!(loudness(0..10) > 10. * loudness(400..500));
+        assert!(loudness(500..510) > 10. * loudness(400..500));
+    }
+
+    #[test]
+    fn samples_are_clamped() {
+        let mut generator = Generator::new(&Descriptor {
+            signals: vec![
+                Signal::Sine {
+                    frequency: 100.,
+                    amplitude: 1.,
+                },
+                Signal::Sine {
+                    frequency: 100.,
+                    amplitude: 1.,
+                },
+            ],
+            sample_rate: 44_100,
+            amount_channels: 1,
+            pacing: Pacing::RealTime,
+        });
+
+        let mut samples = Vec::new();
+        generator.read(1_000, &mut samples);
+
+        assert!(samples.iter().all(|sample| (-1. ..=1.).contains(sample)));
+        assert!(samples.contains(&1.));
+    }
+}
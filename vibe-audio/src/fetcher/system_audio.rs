@@ -1,8 +1,27 @@
 use super::{Fetcher, SampleBuffer};
-use crate::DEFAULT_SAMPLE_RATE;
+use crate::{util::DeviceType, DEFAULT_SAMPLE_RATE};
 use cpal::traits::{DeviceTrait, StreamTrait};
-use std::sync::{Arc, Mutex};
-use tracing::debug;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, warn};
+
+/// The time between the stream got lost and the first attempt to reopen it.
+const MIN_RECONNECT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The maximal time between two attempts to reopen the stream.
+const MAX_RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the watcher thread checks the state of the stream while no errors arrive.
+const WATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often the default device is checked if [Descriptor::follow_default_device] is set.
+const DEFAULT_DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Errors which can occur while creating [crate::fetcher::SystemAudioFetcher].
 #[derive(thiserror::Error, Debug)]
@@ -15,18 +34,66 @@ pub enum SystemAudioError {
     #[error("Couldn't retrieve any config of the output stream of the default device.")]
     NoAvailableOutputConfigs,
 
+    /// The device doesn't support the amount of channels and the sample rate of the stream which got lost.
+    #[error(
+        "The device doesn't support {channels} channels with a sample rate of {sample_rate} Hz."
+    )]
+    UnsupportedStreamConfig {
+        channels: u16,
+        sample_rate: cpal::SampleRate,
+    },
+
     #[error("Couldn't get supported output config of device: {0}")]
     SupportedStreamConfigError(#[from] cpal::SupportedStreamConfigsError),
 
     #[error("Couldn't build an audio stream:\n{0}")]
     BuildOutputStreamError(#[from] cpal::BuildStreamError),
+
+    #[error("Couldn't start the audio stream:\n{0}")]
+    PlayStreamError(#[from] cpal::PlayStreamError),
+
+    #[error("The thread which watches the audio stream stopped unexpectedly.")]
+    WatcherStopped,
+}
+
+/// The events of a [crate::fetcher::SystemAudioFetcher] (see [SystemAudio::subscribe]).
+#[derive(Debug, Clone)]
+pub enum SystemAudioEvent {
+    /// The audio stream reported an error.
+    StreamError(cpal::StreamError),
+
+    /// The audio stream stopped (for example because the device got unplugged).
+    /// Silence is pushed until the stream is reopened.
+    Disconnected,
+
+    /// Reopening the audio stream failed. The next attempt is made after `retry_in`.
+    ReconnectFailed { reason: String, retry_in: Duration },
+
+    /// The default device of the system changed, so the stream is reopened with it.
+    DefaultDeviceChanged,
+
+    /// The audio stream got reopened.
+    Reconnected,
 }
 
 pub struct Descriptor {
     pub device: cpal::Device,
+
+    /// Whether `device` is an output device (whose audio is monitored) or an input device (for example a microphone).
+    pub device_type: DeviceType,
+
     pub sample_rate: cpal::SampleRate,
     pub sample_format: Option<cpal::SampleFormat>,
+
+    /// The preferred amount of channels.
+    ///
+    /// If the device doesn't support it, another amount of channels is used.
+    /// Use [crate::ChannelMix] to downmix them.
     pub amount_channels: Option<u16>,
+
+    /// Switch to the new default device if the default device of the system changes
+    /// (and reopen the stream with the default device if `device` is gone).
+    pub follow_default_device: bool,
 }
 
 impl Default for Descriptor {
@@ -36,42 +103,50 @@ impl Default for Descriptor {
 
         Self {
             device,
+            device_type: DeviceType::Output,
             sample_rate: DEFAULT_SAMPLE_RATE,
             sample_format: None,
             amount_channels: None,
+            follow_default_device: false,
         }
     }
 }
 
 /// Fetcher for the system audio.
+///
+/// Errors of the audio stream don't stop the fetcher: If the stream stops (for example because the device
+/// got unplugged), silence is pushed and the stream is reopened with an increasing delay between the attempts.
+/// The reopened stream keeps the amount of channels and the sample rate of the first stream.
+/// The stream is watched by its own thread, because enumerating the devices and reopening the stream can block
+/// for a while. [Fetcher::advance] only passes the events of that thread on to the subscribers.
 pub struct SystemAudio {
-    sample_buffer: Arc<Mutex<SampleBuffer>>,
+    sample_buffer: SampleBuffer,
     channels: u16,
-    _stream: cpal::Stream,
+
+    // the events of the thread which watches the stream
+    events: Receiver<SystemAudioEvent>,
+    subscribers: Vec<Sender<SystemAudioEvent>>,
+    is_connected: Arc<AtomicBool>,
+    is_running: Arc<AtomicBool>,
 }
 
 impl SystemAudio {
     pub fn new(desc: &Descriptor) -> Result<Self, SystemAudioError> {
-        let device = &desc.device;
         let stream_config = {
-            let mut matching_configs: Vec<_> = desc
-                .device
-                .supported_output_configs()?
-                .filter(|conf| {
-                    let matching_sample_format = desc
-                        .sample_format
-                        .map(|sample_format| sample_format == conf.sample_format())
-                        .unwrap_or(true);
-                    let matching_amount_channels = desc
-                        .amount_channels
-                        .map(|amount| amount == conf.channels())
-                        .unwrap_or(true);
-
-                    matching_sample_format && matching_amount_channels
-                })
-                .collect();
-
-            matching_configs.sort_by(|a, b| a.cmp_default_heuristics(b));
+            let mut matching_configs =
+                supported_configs(&desc.device, desc.device_type, desc.sample_format)?;
+
+            // prefer the configs with the requested amount of channels
+            let has_other_amount_channels = |conf: &cpal::SupportedStreamConfigRange| {
+                desc.amount_channels
+                    .map(|amount| amount != conf.channels())
+                    .unwrap_or(false)
+            };
+            matching_configs.sort_by(|a, b| {
+                has_other_amount_channels(a)
+                    .cmp(&has_other_amount_channels(b))
+                    .then_with(|| a.cmp_default_heuristics(b))
+            });
             let supported_stream_config = matching_configs
                 .into_iter()
                 .next()
@@ -88,68 +163,430 @@ impl SystemAudio {
 
         debug!("Stream config: {:#?}", stream_config);
 
-        let sample_buffer = Arc::new(Mutex::new(SampleBuffer::new(sample_rate)));
-
-        let stream = {
-            let stream = device.build_input_stream(
-                &stream_config,
-                {
-                    let buffer = sample_buffer.clone();
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                        let mut buf = buffer.lock().unwrap();
-                        buf.push_before(data);
-                    }
-                },
-                |err| panic!("`shady-audio`: {}", err),
-                None,
-            )?;
-            stream.play().expect("Start listening to audio");
-            stream
+        let sample_buffer = SampleBuffer::new(sample_rate);
+        let (event_sender, events) = mpsc::channel();
+        let is_connected = Arc::new(AtomicBool::new(true));
+        let is_running = Arc::new(AtomicBool::new(true));
+
+        let watcher = Watcher {
+            device: desc.device.clone(),
+            device_type: desc.device_type,
+            stream_config,
+            sample_format: desc.sample_format,
+            follow_default_device: desc.follow_default_device,
+            sample_buffer: sample_buffer.clone(),
+
+            events: event_sender,
+            is_connected: is_connected.clone(),
+            is_running: is_running.clone(),
         };
 
+        // the stream can't be moved to another thread on every platform, so it's built within the watcher thread
+        let (result_sender, result) = mpsc::channel();
+        std::thread::spawn(move || watcher.run(result_sender));
+        result
+            .recv()
+            .unwrap_or(Err(SystemAudioError::WatcherStopped))?;
+
         Ok(Self {
-            _stream: stream,
-            channels,
             sample_buffer,
+            channels,
+
+            events,
+            subscribers: Vec::new(),
+            is_connected,
+            is_running,
         })
     }
+
+    /// Returns a receiver for the events of the fetcher (for example if the device got lost).
+    ///
+    /// The events are sent within [Fetcher::advance].
+    pub fn subscribe(&mut self) -> Receiver<SystemAudioEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Returns `true` if the audio stream is running.
+    /// `false` if the fetcher pushes silence while it waits for the device.
+    pub fn is_connected(&self) -> bool {
+        self.is_connected.load(Ordering::Relaxed)
+    }
 }
 
 impl Drop for SystemAudio {
-    /// Closes the audio stream before it gets dropped.
-    ///
-    /// **Panics** if it couldn't close the stream correctly.
+    /// Tells the watcher thread to close the audio stream.
     fn drop(&mut self) {
-        self._stream.pause().expect("Stop stream");
+        // the thread notices it within `WATCH_INTERVAL`
+        self.is_running.store(false, Ordering::Relaxed);
     }
 }
 
 impl Fetcher for SystemAudio {
-    fn sample_buffer(&self) -> Arc<Mutex<SampleBuffer>> {
+    fn sample_buffer(&self) -> SampleBuffer {
         self.sample_buffer.clone()
     }
 
     fn channels(&self) -> u16 {
         self.channels
     }
+
+    fn advance(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            self.subscribers
+                .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+}
+
+/// Owns the audio stream within its own thread: Reopens it if it got lost and follows the default device.
+struct Watcher {
+    device: cpal::Device,
+    device_type: DeviceType,
+    stream_config: cpal::StreamConfig,
+    sample_format: Option<cpal::SampleFormat>,
+    follow_default_device: bool,
+    sample_buffer: SampleBuffer,
+
+    events: Sender<SystemAudioEvent>,
+    is_connected: Arc<AtomicBool>,
+    is_running: Arc<AtomicBool>,
+}
+
+/// The state of the watcher thread which only lives within it.
+struct WatcherState {
+    stream: StreamState,
+    // the errors which the audio stream reports
+    errors: Receiver<cpal::StreamError>,
+    error_sender: Sender<cpal::StreamError>,
+    last_default_device_check: Instant,
+    // the default device which doesn't support the stream config (so it's not switched to it again)
+    rejected_default_device: Option<cpal::DeviceId>,
+}
+
+enum StreamState {
+    Running(cpal::Stream),
+    Lost {
+        // the time when the silence got pushed the last time
+        silence_pushed_at: Instant,
+        backoff: Backoff,
+    },
+}
+
+impl Watcher {
+    /// Opens the stream, sends the result to `result` and watches the stream until the fetcher gets dropped.
+    fn run(mut self, result: Sender<Result<(), SystemAudioError>>) {
+        let (error_sender, errors) = mpsc::channel();
+
+        let stream = match build_stream(
+            &self.device,
+            &self.stream_config,
+            self.sample_buffer.clone(),
+            error_sender.clone(),
+        ) {
+            Ok(stream) => stream,
+            Err(err) => {
+                let _ = result.send(Err(err));
+                return;
+            }
+        };
+        let _ = result.send(Ok(()));
+
+        let mut state = WatcherState {
+            stream: StreamState::Running(stream),
+            errors,
+            error_sender,
+            last_default_device_check: Instant::now(),
+            rejected_default_device: None,
+        };
+
+        while self.is_running.load(Ordering::Relaxed) {
+            match state.errors.recv_timeout(WATCH_INTERVAL) {
+                Ok(err) => self.handle_stream_error(&mut state, err),
+                Err(RecvTimeoutError::Timeout) => {}
+                // the watcher holds a sender itself
+                Err(RecvTimeoutError::Disconnected) => unreachable!(),
+            }
+
+            self.follow_default_device(&mut state);
+
+            if let StreamState::Lost { backoff, .. } = &state.stream {
+                let retry = backoff.is_due();
+
+                self.push_silence(&mut state);
+                if retry {
+                    self.reconnect(&mut state);
+                }
+            }
+        }
+
+        if let StreamState::Running(stream) = &state.stream {
+            if let Err(err) = stream.pause() {
+                debug!("Couldn't stop the audio stream: {}", err);
+            }
+        }
+    }
+
+    fn notify(&self, event: SystemAudioEvent) {
+        // the receiver only disappears if the fetcher got dropped
+        let _ = self.events.send(event);
+    }
+
+    fn handle_stream_error(&mut self, state: &mut WatcherState, err: cpal::StreamError) {
+        warn!("Error of the audio stream: {}", err);
+        self.notify(SystemAudioEvent::StreamError(err.clone()));
+
+        // glitches don't stop the stream
+        let is_fatal = err != cpal::StreamError::BufferUnderrun;
+        if is_fatal && matches!(state.stream, StreamState::Running(_)) {
+            self.disconnect(state);
+        }
+    }
+
+    fn follow_default_device(&mut self, state: &mut WatcherState) {
+        if !self.follow_default_device
+            || state.last_default_device_check.elapsed() < DEFAULT_DEVICE_CHECK_INTERVAL
+        {
+            return;
+        }
+        state.last_default_device_check = Instant::now();
+
+        let Some(default_device) = crate::util::get_default_device(self.device_type) else {
+            return;
+        };
+
+        let default_device_id = default_device.id().ok();
+        if default_device_id == self.device.id().ok()
+            || default_device_id == state.rejected_default_device
+        {
+            return;
+        }
+
+        // the samples of the processor would be misinterpreted with another amount of channels or sample rate
+        if let Err(err) = self.check_stream_config(&default_device) {
+            warn!(
+                "The default audio device changed but it can't be used: {} Staying with the current device.",
+                err
+            );
+            state.rejected_default_device = default_device_id;
+            return;
+        }
+        state.rejected_default_device = None;
+
+        info!("The default audio device changed. Switching to it.");
+        self.notify(SystemAudioEvent::DefaultDeviceChanged);
+
+        let prev_device = std::mem::replace(&mut self.device, default_device);
+        if matches!(state.stream, StreamState::Running(_)) {
+            self.disconnect(state);
+        }
+        self.reconnect(state);
+
+        if matches!(state.stream, StreamState::Lost { .. }) {
+            warn!("Couldn't open the new default audio device. Going back to the previous one.");
+            state.rejected_default_device = default_device_id;
+            self.device = prev_device;
+            self.reconnect(state);
+        }
+    }
+
+    fn disconnect(&mut self, state: &mut WatcherState) {
+        if let StreamState::Running(stream) = &state.stream {
+            if let Err(err) = stream.pause() {
+                debug!("Couldn't pause the lost audio stream: {}", err);
+            }
+        }
+
+        state.stream = StreamState::Lost {
+            silence_pushed_at: Instant::now(),
+            backoff: Backoff::new(),
+        };
+        self.is_connected.store(false, Ordering::Relaxed);
+        self.notify(SystemAudioEvent::Disconnected);
+    }
+
+    fn reconnect(&mut self, state: &mut WatcherState) {
+        match self.reopen_stream(state) {
+            Ok(stream) => {
+                info!("Reopened the audio stream.");
+                state.stream = StreamState::Running(stream);
+                self.is_connected.store(true, Ordering::Relaxed);
+                self.notify(SystemAudioEvent::Reconnected);
+            }
+            Err(err) => {
+                let StreamState::Lost { backoff, .. } = &mut state.stream else {
+                    unreachable!("Only lost streams are reopened.");
+                };
+                backoff.failed();
+                let retry_in = backoff.interval;
+
+                debug!("Couldn't reopen the audio stream: {}", err);
+                self.notify(SystemAudioEvent::ReconnectFailed {
+                    reason: err.to_string(),
+                    retry_in,
+                });
+            }
+        }
+    }
+
+    fn reopen_stream(&mut self, state: &WatcherState) -> Result<cpal::Stream, SystemAudioError> {
+        // the old handle might belong to a device which is gone
+        let device = self
+            .device
+            .id()
+            .ok()
+            .and_then(|id| crate::util::get_device(id, self.device_type).ok().flatten());
+
+        let device = match device {
+            Some(device) => device,
+            None if self.follow_default_device => crate::util::get_default_device(self.device_type)
+                .ok_or(SystemAudioError::NoDefaultDevice)?,
+            None => self.device.clone(),
+        };
+
+        self.check_stream_config(&device)?;
+
+        let stream = build_stream(
+            &device,
+            &self.stream_config,
+            self.sample_buffer.clone(),
+            state.error_sender.clone(),
+        )?;
+        self.device = device;
+
+        Ok(stream)
+    }
+
+    /// Checks if the device supports the amount of channels and the sample rate of the first stream.
+    fn check_stream_config(&self, device: &cpal::Device) -> Result<(), SystemAudioError> {
+        let channels = self.stream_config.channels;
+        let sample_rate = self.stream_config.sample_rate;
+
+        let is_supported = supported_configs(device, self.device_type, self.sample_format)?
+            .iter()
+            .any(|conf| {
+                conf.channels() == channels
+                    && conf.min_sample_rate() <= sample_rate
+                    && sample_rate <= conf.max_sample_rate()
+            });
+
+        if is_supported {
+            Ok(())
+        } else {
+            Err(SystemAudioError::UnsupportedStreamConfig {
+                channels,
+                sample_rate,
+            })
+        }
+    }
+
+    fn push_silence(&mut self, state: &mut WatcherState) {
+        let StreamState::Lost {
+            silence_pushed_at, ..
+        } = &mut state.stream
+        else {
+            return;
+        };
+
+        let sample_rate = self.stream_config.sample_rate as f64;
+        let amount_frames = (silence_pushed_at.elapsed().as_secs_f64() * sample_rate) as usize;
+        if amount_frames == 0 {
+            return;
+        }
+
+        // keep the fraction of the frame for the next time
+        *silence_pushed_at += Duration::from_secs_f64(amount_frames as f64 / sample_rate);
+
+        let silence = vec![0f32; amount_frames * self.stream_config.channels as usize];
+        self.sample_buffer.push(&silence);
+    }
+}
+
+fn supported_configs(
+    device: &cpal::Device,
+    device_type: DeviceType,
+    sample_format: Option<cpal::SampleFormat>,
+) -> Result<Vec<cpal::SupportedStreamConfigRange>, SystemAudioError> {
+    let supported_configs: Vec<_> = match device_type {
+        DeviceType::Output => device.supported_output_configs()?.collect(),
+        DeviceType::Input => device.supported_input_configs()?.collect(),
+    };
+
+    Ok(supported_configs
+        .into_iter()
+        .filter(|conf| {
+            sample_format
+                .map(|sample_format| sample_format == conf.sample_format())
+                .unwrap_or(true)
+        })
+        .collect())
+}
+
+fn build_stream(
+    device: &cpal::Device,
+    stream_config: &cpal::StreamConfig,
+    sample_buffer: SampleBuffer,
+    error_sender: Sender<cpal::StreamError>,
+) -> Result<cpal::Stream, SystemAudioError> {
+    let stream = device.build_input_stream(
+        stream_config,
+        move |data: &[f32], _: &cpal::InputCallbackInfo| {
+            sample_buffer.push(data);
+        },
+        move |err| {
+            // the receiver only disappears if the fetcher got dropped
+            let _ = error_sender.send(err);
+        },
+        None,
+    )?;
+    stream.play()?;
+
+    Ok(stream)
+}
+
+/// Decides when the next attempt to reopen the stream is made.
+#[derive(Debug, Clone)]
+struct Backoff {
+    interval: Duration,
+    next_attempt: Instant,
+}
+
+impl Backoff {
+    fn new() -> Self {
+        Self {
+            interval: MIN_RECONNECT_INTERVAL,
+            next_attempt: Instant::now() + MIN_RECONNECT_INTERVAL,
+        }
+    }
+
+    fn is_due(&self) -> bool {
+        Instant::now() >= self.next_attempt
+    }
+
+    /// Doubles the interval up to [MAX_RECONNECT_INTERVAL].
+    fn failed(&mut self) {
+        self.interval = (self.interval * 2).min(MAX_RECONNECT_INTERVAL);
+        self.next_attempt = Instant::now() + self.interval;
+    }
 }
 
-// #[instrument(skip_all)]
-// fn default_output_config(
-//     device: &cpal::Device,
-// ) -> Result<SupportedStreamConfigRange, SystemAudioError> {
-//     let mut matching_configs: Vec<_> = device
-//         .supported_output_configs()
-//         .expect(concat![
-//             "Eh... somehow `shady-audio` couldn't get any supported output configs of your audio device.\n",
-//             "Could it be that you are running \"pure\" pulseaudio?\n",
-//             "Only ALSA and JACK are supported for audio processing :("
-//         ])
-//         .collect();
-
-//     matching_configs.sort_by(|a, b| a.cmp_default_heuristics(b));
-//     matching_configs
-//         .into_iter()
-//         .next()
-//         .ok_or(SystemAudioError::NoAvailableOutputConfigs)
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let mut backoff = Backoff::new();
+        assert!(!backoff.is_due());
+
+        let mut intervals = Vec::new();
+        for _ in 0..6 {
+            backoff.failed();
+            intervals.push(backoff.interval.as_millis());
+        }
+
+        assert_eq!(intervals, [500, 1_000, 2_000, 4_000, 5_000, 5_000]);
+        assert!(!backoff.is_due());
+    }
+}
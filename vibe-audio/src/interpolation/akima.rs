@@ -0,0 +1,160 @@
+use super::{context::InterpolationCtx, hermite, Interpolater, InterpolatorDescriptor};
+
+/// Applies the Akima interpolation between two supporting points.
+///
+/// The slope of each supporting point only depends on the two secants on each side of it,
+/// so a peak doesn't cause wiggles across the whole spectrum (unlike [super::CubicSplineInterpolation]).
+#[derive(Debug, Clone)]
+pub struct AkimaInterpolation {
+    ctx: InterpolationCtx,
+
+    // The secants between the supporting points with two extrapolated secants on each side.
+    secants: Box<[f32]>,
+    slopes: Box<[f32]>,
+}
+
+/// The amount of extrapolated secants on each side.
+const SECANT_PADDING: usize = 2;
+
+impl Interpolater for AkimaInterpolation {
+    fn new(desc: InterpolatorDescriptor) -> Self {
+        let ctx = InterpolationCtx::new(desc);
+
+        let amount_points = ctx.supporting_points.len();
+        let secants =
+            vec![0f32; amount_points.saturating_sub(1) + 2 * SECANT_PADDING].into_boxed_slice();
+        let slopes = vec![0f32; amount_points].into_boxed_slice();
+
+        Self {
+            ctx,
+            secants,
+            slopes,
+        }
+    }
+
+    fn interpolate(&mut self, buffer: &mut [f32]) {
+        if self.ctx.supporting_points.len() < 2 {
+            for point in self.ctx.supporting_points.iter() {
+                buffer[point.x] = point.y;
+            }
+            return;
+        }
+
+        self.update_slopes();
+        hermite::interpolate(&self.ctx, &self.slopes, buffer);
+    }
+
+    fn get_ctx(&self) -> &InterpolationCtx {
+        &self.ctx
+    }
+
+    fn get_ctx_mut(&mut self) -> &mut InterpolationCtx {
+        &mut self.ctx
+    }
+}
+
+impl AkimaInterpolation {
+    fn update_slopes(&mut self) {
+        let len = self.secants.len();
+        hermite::update_secants(
+            &self.ctx,
+            &mut self.secants[SECANT_PADDING..len - SECANT_PADDING],
+        );
+
+        // extrapolate the secants linearly on both sides
+        let m = &mut self.secants;
+        if len == 1 + 2 * SECANT_PADDING {
+            let secant = m[SECANT_PADDING];
+            m.fill(secant);
+        } else {
+            m[1] = 2. * m[2] - m[3];
+            m[0] = 2. * m[1] - m[2];
+            m[len - 2] = 2. * m[len - 3] - m[len - 4];
+            m[len - 1] = 2. * m[len - 2] - m[len - 3];
+        }
+
+        // the slope of the i-th point lies between the secants `m[i+1]` and `m[i+2]`
+        for (slope, m) in self.slopes.iter_mut().zip(self.secants.windows(4)) {
+            let left_weight = (m[3] - m[2]).abs();
+            let right_weight = (m[1] - m[0]).abs();
+
+            *slope = if left_weight + right_weight > f32::EPSILON {
+                (left_weight * m[1] + right_weight * m[2]) / (left_weight + right_weight)
+            } else {
+                (m[1] + m[2]) / 2.
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpolation::SupportingPoint;
+
+    fn interpolate(supporting_points: &[SupportingPoint]) -> Vec<f32> {
+        let mut interpolator = AkimaInterpolation::new(InterpolatorDescriptor {
+            supporting_points: supporting_points.into(),
+            ..Default::default()
+        });
+        let mut buffer = vec![0f32; supporting_points.last().map(|p| p.x + 1).unwrap_or(0)];
+
+        interpolator.interpolate(&mut buffer);
+        buffer
+    }
+
+    #[test]
+    fn no_supporting_points() {
+        assert!(interpolate(&[]).is_empty());
+    }
+
+    #[test]
+    fn one_supporting_point() {
+        assert_eq!(interpolate(&[SupportingPoint { x: 0, y: 0.5 }]), [0.5]);
+    }
+
+    #[test]
+    fn two_supporting_points_are_linear() {
+        let buffer = interpolate(&[
+            SupportingPoint { x: 0, y: 0.0 },
+            SupportingPoint { x: 4, y: 1.0 },
+        ]);
+
+        assert_eq!(&buffer, &[0.0, 0.25, 0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn reproduces_lines() {
+        let buffer = interpolate(&[
+            SupportingPoint { x: 0, y: 0.0 },
+            SupportingPoint { x: 2, y: 0.2 },
+            SupportingPoint { x: 7, y: 0.7 },
+            SupportingPoint { x: 10, y: 1.0 },
+        ]);
+
+        for (idx, value) in buffer.iter().enumerate() {
+            assert!((value - idx as f32 / 10.).abs() < 1e-5, "{:?}", buffer);
+        }
+    }
+
+    #[test]
+    fn flat_sections_stay_flat() {
+        // the spline would swing below zero next to the peak
+        let buffer = interpolate(&[
+            SupportingPoint { x: 0, y: 0.0 },
+            SupportingPoint { x: 4, y: 0.0 },
+            SupportingPoint { x: 8, y: 0.0 },
+            SupportingPoint { x: 10, y: 1.0 },
+            SupportingPoint { x: 12, y: 0.0 },
+            SupportingPoint { x: 16, y: 0.0 },
+            SupportingPoint { x: 20, y: 0.0 },
+        ]);
+
+        for value in buffer[..=8].iter().chain(&buffer[12..]) {
+            assert_eq!(*value, 0., "{:?}", buffer);
+        }
+        for value in &buffer[8..=12] {
+            assert!((0. ..=1.).contains(value), "{:?}", buffer);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use super::{
+    context::InterpolationCtx, AkimaInterpolation, CubicSplineInterpolation, Interpolater,
+    InterpolatorDescriptor, LinearInterpolation, NothingInterpolation, PchipInterpolation,
+};
+use crate::InterpolationVariant;
+
+/// Uses the interpolation which is set in [InterpolatorDescriptor::variant]
+/// (or [crate::BarProcessorConfig::interpolation] respectively).
+///
+/// Use this one if the interpolation should be chosen at runtime, for example from a config file.
+///
+/// # Example
+/// ```
+/// use vibe_audio::{SampleProcessor, BarProcessor, BarProcessorConfig, fetcher::DummyFetcher, DynamicInterpolation, InterpolationVariant};
+///
+/// let sample_processor = SampleProcessor::new(DummyFetcher::new(1));
+/// let bar_processor: BarProcessor<DynamicInterpolation> = BarProcessor::new(
+///     &sample_processor,
+///     BarProcessorConfig {
+///         interpolation: InterpolationVariant::Pchip,
+///         ..Default::default()
+///     }
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub enum DynamicInterpolation {
+    Nothing(NothingInterpolation),
+    Linear(LinearInterpolation),
+    CubicSpline(CubicSplineInterpolation),
+    Pchip(PchipInterpolation),
+    Akima(AkimaInterpolation),
+}
+
+impl Interpolater for DynamicInterpolation {
+    fn new(desc: InterpolatorDescriptor) -> Self {
+        match desc.variant {
+            InterpolationVariant::None => Self::Nothing(NothingInterpolation::new(desc)),
+            InterpolationVariant::Linear => Self::Linear(LinearInterpolation::new(desc)),
+            InterpolationVariant::CubicSpline => {
+                Self::CubicSpline(CubicSplineInterpolation::new(desc))
+            }
+            InterpolationVariant::Pchip => Self::Pchip(PchipInterpolation::new(desc)),
+            InterpolationVariant::Akima => Self::Akima(AkimaInterpolation::new(desc)),
+        }
+    }
+
+    fn interpolate(&mut self, buffer: &mut [f32]) {
+        match self {
+            Self::Nothing(interpolator) => interpolator.interpolate(buffer),
+            Self::Linear(interpolator) => interpolator.interpolate(buffer),
+            Self::CubicSpline(interpolator) => interpolator.interpolate(buffer),
+            Self::Pchip(interpolator) => interpolator.interpolate(buffer),
+            Self::Akima(interpolator) => interpolator.interpolate(buffer),
+        }
+    }
+
+    fn get_ctx(&self) -> &InterpolationCtx {
+        match self {
+            Self::Nothing(interpolator) => interpolator.get_ctx(),
+            Self::Linear(interpolator) => interpolator.get_ctx(),
+            Self::CubicSpline(interpolator) => interpolator.get_ctx(),
+            Self::Pchip(interpolator) => interpolator.get_ctx(),
+            Self::Akima(interpolator) => interpolator.get_ctx(),
+        }
+    }
+
+    fn get_ctx_mut(&mut self) -> &mut InterpolationCtx {
+        match self {
+            Self::Nothing(interpolator) => interpolator.get_ctx_mut(),
+            Self::Linear(interpolator) => interpolator.get_ctx_mut(),
+            Self::CubicSpline(interpolator) => interpolator.get_ctx_mut(),
+            Self::Pchip(interpolator) => interpolator.get_ctx_mut(),
+            Self::Akima(interpolator) => interpolator.get_ctx_mut(),
+        }
+    }
+}
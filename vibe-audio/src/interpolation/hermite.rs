@@ -0,0 +1,42 @@
+use super::context::InterpolationCtx;
+
+/// Stores the secants between each two neighbouring supporting points into `secants`.
+///
+/// `secants[i]` is the gradient between the `i`-th and the `(i+1)`-th supporting point.
+pub fn update_secants(ctx: &InterpolationCtx, secants: &mut [f32]) {
+    for (secant, pair) in secants.iter_mut().zip(ctx.supporting_points.windows(2)) {
+        let (left, right) = (&pair[0], &pair[1]);
+
+        *secant = (right.y - left.y) / (right.x - left.x) as f32;
+    }
+}
+
+/// Fills the sections of the buffer with the cubic hermite polynomial which goes through the supporting points
+/// with the given slope (`slopes[i]` belongs to the `i`-th supporting point).
+pub fn interpolate(ctx: &InterpolationCtx, slopes: &[f32], buffer: &mut [f32]) {
+    for point in ctx.supporting_points.iter() {
+        buffer[point.x] = point.y;
+    }
+
+    for section in ctx.sections.iter() {
+        let idx = section.left_supporting_point_idx;
+
+        let left = &ctx.supporting_points[idx];
+        let right = &ctx.supporting_points[idx + 1];
+        let width = (right.x - left.x) as f32;
+
+        let left_slope = slopes[idx] * width;
+        let right_slope = slopes[idx + 1] * width;
+
+        for interpolated_idx in 0..section.amount {
+            let t = (interpolated_idx + 1) as f32 / width;
+            let t2 = t * t;
+            let t3 = t2 * t;
+
+            buffer[left.x + interpolated_idx + 1] = (2. * t3 - 3. * t2 + 1.) * left.y
+                + (t3 - 2. * t2 + t) * left_slope
+                + (-2. * t3 + 3. * t2) * right.y
+                + (t3 - t2) * right_slope;
+        }
+    }
+}
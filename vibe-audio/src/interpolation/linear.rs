@@ -2,7 +2,7 @@ use super::{context::InterpolationCtx, Interpolater, InterpolatorDescriptor};
 use tracing::debug;
 
 /// Interpolates linearly between two supporting points.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LinearInterpolation {
     ctx: InterpolationCtx,
 }
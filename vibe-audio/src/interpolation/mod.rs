@@ -1,20 +1,32 @@
 //! Everything related to the interpolation calculation.
+mod akima;
 mod context;
 mod cubic_spline;
+mod dynamic;
+mod hermite;
 mod linear;
 mod nothing;
+mod pchip;
 
+use crate::InterpolationVariant;
 use context::InterpolationCtx;
 use std::ops::Range;
 
+pub use akima::AkimaInterpolation;
 pub use cubic_spline::CubicSplineInterpolation;
+pub use dynamic::DynamicInterpolation;
 pub use linear::LinearInterpolation;
 pub use nothing::NothingInterpolation;
+pub use pchip::PchipInterpolation;
 
 /// Descriptor to create new interpolations.
 #[derive(Default)]
 pub struct InterpolatorDescriptor {
     pub supporting_points: Box<[SupportingPoint]>,
+
+    /// The interpolation which [DynamicInterpolation] should use.
+    /// Ignored by the other interpolations.
+    pub variant: InterpolationVariant,
 }
 
 /// Methods for an interpolator.
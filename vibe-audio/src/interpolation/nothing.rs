@@ -2,7 +2,7 @@ use super::{context::InterpolationCtx, Interpolater, InterpolatorDescriptor};
 
 /// Interpolates nothing... as the name says...
 /// which basically means that it won't fill any other values.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NothingInterpolation {
     ctx: InterpolationCtx,
 }
@@ -0,0 +1,180 @@
+use super::{context::InterpolationCtx, hermite, Interpolater, InterpolatorDescriptor};
+
+/// Applies the monotone cubic interpolation (PCHIP) between two supporting points.
+///
+/// The slopes are chosen as described by Fritsch and Carlson, so the interpolation never overshoots:
+/// It's monotone between two supporting points and has a flat peak at each local extremum.
+#[derive(Debug, Clone)]
+pub struct PchipInterpolation {
+    ctx: InterpolationCtx,
+
+    secants: Box<[f32]>,
+    slopes: Box<[f32]>,
+}
+
+impl Interpolater for PchipInterpolation {
+    fn new(desc: InterpolatorDescriptor) -> Self {
+        let ctx = InterpolationCtx::new(desc);
+
+        let amount_points = ctx.supporting_points.len();
+        let secants = vec![0f32; amount_points.saturating_sub(1)].into_boxed_slice();
+        let slopes = vec![0f32; amount_points].into_boxed_slice();
+
+        Self {
+            ctx,
+            secants,
+            slopes,
+        }
+    }
+
+    fn interpolate(&mut self, buffer: &mut [f32]) {
+        if self.ctx.supporting_points.len() < 2 {
+            for point in self.ctx.supporting_points.iter() {
+                buffer[point.x] = point.y;
+            }
+            return;
+        }
+
+        hermite::update_secants(&self.ctx, &mut self.secants);
+        self.update_slopes();
+        hermite::interpolate(&self.ctx, &self.slopes, buffer);
+    }
+
+    fn get_ctx(&self) -> &InterpolationCtx {
+        &self.ctx
+    }
+
+    fn get_ctx_mut(&mut self) -> &mut InterpolationCtx {
+        &mut self.ctx
+    }
+}
+
+impl PchipInterpolation {
+    fn update_slopes(&mut self) {
+        let points = &self.ctx.supporting_points;
+        let secants = &self.secants;
+        let width = |idx: usize| (points[idx + 1].x - points[idx].x) as f32;
+
+        let last = secants.len();
+        if last == 1 {
+            self.slopes.fill(secants[0]);
+            return;
+        }
+
+        // inner points: weighted harmonic mean of the neighbouring secants
+        for idx in 1..last {
+            let (prev, next) = (secants[idx - 1], secants[idx]);
+
+            self.slopes[idx] = if prev * next <= 0. {
+                // local extremum (or flat section)
+                0.
+            } else {
+                let w1 = 2. * width(idx) + width(idx - 1);
+                let w2 = width(idx) + 2. * width(idx - 1);
+
+                (w1 + w2) / (w1 / prev + w2 / next)
+            };
+        }
+
+        self.slopes[0] = end_slope(width(0), width(1), secants[0], secants[1]);
+        self.slopes[last] = end_slope(
+            width(last - 1),
+            width(last - 2),
+            secants[last - 1],
+            secants[last - 2],
+        );
+    }
+}
+
+/// The shape-preserving three-point slope of an end point.
+///
+/// `width` and `secant` belong to the section next to the end point and `next_width` and `next_secant`
+/// to the section after it.
+fn end_slope(width: f32, next_width: f32, secant: f32, next_secant: f32) -> f32 {
+    let slope = ((2. * width + next_width) * secant - width * next_secant) / (width + next_width);
+
+    if slope * secant <= 0. {
+        0.
+    } else if secant * next_secant <= 0. && slope.abs() > 3. * secant.abs() {
+        3. * secant
+    } else {
+        slope
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpolation::SupportingPoint;
+
+    fn interpolate(supporting_points: &[SupportingPoint]) -> Vec<f32> {
+        let mut interpolator = PchipInterpolation::new(InterpolatorDescriptor {
+            supporting_points: supporting_points.into(),
+            ..Default::default()
+        });
+        let mut buffer = vec![0f32; supporting_points.last().map(|p| p.x + 1).unwrap_or(0)];
+
+        interpolator.interpolate(&mut buffer);
+        buffer
+    }
+
+    #[test]
+    fn no_supporting_points() {
+        assert!(interpolate(&[]).is_empty());
+    }
+
+    #[test]
+    fn one_supporting_point() {
+        assert_eq!(interpolate(&[SupportingPoint { x: 0, y: 0.5 }]), [0.5]);
+    }
+
+    #[test]
+    fn two_supporting_points_are_linear() {
+        let buffer = interpolate(&[
+            SupportingPoint { x: 0, y: 0.0 },
+            SupportingPoint { x: 4, y: 1.0 },
+        ]);
+
+        assert_eq!(&buffer, &[0.0, 0.25, 0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn doesnt_overshoot() {
+        let supporting_points = [
+            SupportingPoint { x: 0, y: 0.0 },
+            SupportingPoint { x: 5, y: 0.0 },
+            SupportingPoint { x: 7, y: 1.0 },
+            SupportingPoint { x: 15, y: 0.9 },
+            SupportingPoint { x: 17, y: 0.0 },
+            SupportingPoint { x: 30, y: 0.1 },
+        ];
+
+        let buffer = interpolate(&supporting_points);
+
+        for pair in supporting_points.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+            let (min, max) = (left.y.min(right.y), left.y.max(right.y));
+
+            for value in &buffer[left.x..=right.x] {
+                assert!(min <= *value && *value <= max, "{:?}", buffer);
+            }
+        }
+    }
+
+    #[test]
+    fn is_smooth() {
+        let buffer = interpolate(&[
+            SupportingPoint { x: 0, y: 0.0 },
+            SupportingPoint { x: 4, y: 0.2 },
+            SupportingPoint { x: 8, y: 1.0 },
+        ]);
+
+        // the values increase strictly without a kink at the middle point
+        for pair in buffer.windows(2) {
+            assert!(pair[0] < pair[1], "{:?}", buffer);
+        }
+        let left_step = buffer[4] - buffer[3];
+        let right_step = buffer[5] - buffer[4];
+        assert!(right_step / left_step < 2., "{:?}", buffer);
+    }
+}
@@ -91,8 +91,17 @@
 //! }
 //! ```
 mod bar_processor;
+mod beat_detector;
+mod chroma_extractor;
 mod interpolation;
+mod loudness_meter;
+mod pitch_tracker;
 mod sample_processor;
+mod silence_detector;
+mod spectral_features;
+mod tempo_tracker;
+#[cfg(test)]
+mod test_util;
 
 pub mod fetcher;
 pub mod util;
@@ -100,8 +109,18 @@ pub mod util;
 use cpal::SampleRate;
 
 pub use bar_processor::*;
+pub use beat_detector::*;
+pub use chroma_extractor::*;
 pub use cpal;
-pub use sample_processor::SampleProcessor;
+pub use loudness_meter::*;
+pub use pitch_tracker::*;
+pub use sample_processor::{
+    ChannelMix, ConstantQConfig, Hop, MultiResolutionConfig, SampleProcessor,
+    SampleProcessorConfig, SpectralBackend, WindowFunction,
+};
+pub use silence_detector::*;
+pub use spectral_features::*;
+pub use tempo_tracker::*;
 
 type Hz = u16;
 
@@ -0,0 +1,17 @@
+use std::time::Duration;
+
+/// The config options for [crate::LoudnessMeter].
+#[derive(Debug, Clone)]
+pub struct LoudnessMeterConfig {
+    /// The duration of the audio which is used to compute [crate::ChannelLoudness::rms].
+    pub rms_window: Duration,
+}
+
+impl Default for LoudnessMeterConfig {
+    fn default() -> Self {
+        Self {
+            // the integration time of a classic VU meter
+            rms_window: Duration::from_millis(300),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! The K-weighting filter of ITU-R BS.1770.
+//!
+//! The coefficients are computed for any sample rate, see:
+//! <https://github.com/jiixyj/libebur128/blob/master/ebur128/ebur128.c>
+use std::f64::consts::PI;
+
+/// A biquad filter (transposed direct form II).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Biquad {
+    pub b: [f64; 3],
+    // `a[0]` is normalized to `1`
+    pub a: [f64; 2],
+
+    state: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            state: [0.; 2],
+        }
+    }
+
+    pub fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.state[0];
+        self.state[0] = self.b[1] * x - self.a[0] * y + self.state[1];
+        self.state[1] = self.b[2] * x - self.a[1] * y;
+
+        y
+    }
+}
+
+/// The pre-filter (a high shelf which models the acoustic effect of the head)
+/// followed by the RLB high pass filter.
+#[derive(Debug, Clone)]
+pub struct KWeighting {
+    pub shelf: Biquad,
+    pub high_pass: Biquad,
+}
+
+impl KWeighting {
+    pub fn new(sample_rate: f64) -> Self {
+        let shelf = {
+            let f0 = 1681.974450955533;
+            let gain = 3.999843853973347;
+            let q = 0.7071752369554196;
+
+            let k = (PI * f0 / sample_rate).tan();
+            let vh = 10f64.powf(gain / 20.);
+            let vb = vh.powf(0.4996667741545416);
+            let a0 = 1. + k / q + k * k;
+
+            Biquad::new(
+                [
+                    (vh + vb * k / q + k * k) / a0,
+                    2. * (k * k - vh) / a0,
+                    (vh - vb * k / q + k * k) / a0,
+                ],
+                [2. * (k * k - 1.) / a0, (1. - k / q + k * k) / a0],
+            )
+        };
+
+        let high_pass = {
+            let f0 = 38.13547087602444;
+            let q = 0.5003270373238773;
+
+            let k = (PI * f0 / sample_rate).tan();
+            let a0 = 1. + k / q + k * k;
+
+            Biquad::new(
+                [1., -2., 1.],
+                [2. * (k * k - 1.) / a0, (1. - k / q + k * k) / a0],
+            )
+        };
+
+        Self { shelf, high_pass }
+    }
+
+    pub fn process(&mut self, sample: f32) -> f64 {
+        self.high_pass.process(self.shelf.process(sample as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-8, "{:?}", actual);
+        }
+    }
+
+    /// The coefficients which are listed in ITU-R BS.1770-4 for 48 kHz.
+    #[test]
+    fn coefficients_at_48khz() {
+        let filter = KWeighting::new(48_000.);
+
+        assert_close(
+            &filter.shelf.b,
+            &[1.53512485958697, -2.69169618940638, 1.19839281085285],
+        );
+        assert_close(&filter.shelf.a, &[-1.69065929318241, 0.73248077421585]);
+
+        assert_close(&filter.high_pass.b, &[1.0, -2.0, 1.0]);
+        assert_close(&filter.high_pass.a, &[-1.99004745483398, 0.99007225036621]);
+    }
+}
@@ -0,0 +1,374 @@
+mod config;
+mod k_weighting;
+mod true_peak;
+
+use crate::{
+    fetcher::{reserve_sample_buffer, Fetcher},
+    SampleProcessor,
+};
+use k_weighting::KWeighting;
+use std::{collections::VecDeque, time::Duration};
+use true_peak::TruePeak;
+
+pub use config::*;
+
+/// The lowest loudness (in LUFS) which is reported.
+/// Equals the absolute gate of EBU R128, everything below counts as silence.
+pub const MIN_LOUDNESS: f32 = -70.;
+
+/// The window of the momentary loudness (see EBU R128).
+const MOMENTARY_WINDOW: Duration = Duration::from_millis(400);
+
+/// The window of the short-term loudness (see EBU R128).
+const SHORT_TERM_WINDOW: Duration = Duration::from_secs(3);
+
+/// Converts a linear amplitude (for example [ChannelLoudness::rms]) to decibels relative to full scale (dBFS).
+pub fn amplitude_to_db(amplitude: f32) -> f32 {
+    20. * amplitude.log10()
+}
+
+/// The levels of a channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelLoudness {
+    /// The root mean square of the samples within [LoudnessMeterConfig::rms_window] (linear, `1` = full scale).
+    pub rms: f32,
+
+    /// The highest (interpolated) absolute sample value of the latest processed samples (linear, `1` = full scale).
+    ///
+    /// The value can be above `1` if the signal would clip after the digital to analog conversion.
+    pub true_peak: f32,
+
+    /// The K-weighted loudness of the latest 400ms in LUFS.
+    pub momentary: f32,
+
+    /// The K-weighted loudness of the latest 3s in LUFS.
+    pub short_term: f32,
+}
+
+impl Default for ChannelLoudness {
+    fn default() -> Self {
+        Self {
+            rms: 0.,
+            true_peak: 0.,
+            momentary: MIN_LOUDNESS,
+            short_term: MIN_LOUDNESS,
+        }
+    }
+}
+
+/// Measures the absolute levels of the samples of the [SampleProcessor].
+///
+/// In contrast to the [crate::BarProcessor], the values aren't normalized, so they can be used
+/// for VU meters or to react to the overall loudness.
+///
+/// Every sample is measured exactly once, even if the fetcher pushed more samples than the window of the
+/// processor holds. The sample buffer of the fetcher is enlarged to hold at least 250ms,
+/// so [LoudnessMeter::process_loudness] should be called at least that often.
+///
+/// # Example
+/// ```
+/// use vibe_audio::{SampleProcessor, LoudnessMeter, LoudnessMeterConfig, MIN_LOUDNESS, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut loudness_meter = LoudnessMeter::new(&sample_processor, LoudnessMeterConfig::default());
+///
+/// sample_processor.process_next_samples();
+/// let loudness = loudness_meter.process_loudness(&sample_processor);
+///
+/// // one result for each channel
+/// assert_eq!(loudness.len(), 2);
+/// // the dummy fetcher is silent
+/// assert_eq!(loudness[0].rms, 0.);
+/// assert_eq!(loudness_meter.momentary(), MIN_LOUDNESS);
+/// ```
+pub struct LoudnessMeter {
+    channels: Box<[ChannelMeter]>,
+    loudness: Box<[ChannelLoudness]>,
+
+    // the loudness of all channels together
+    momentary: f32,
+    short_term: f32,
+
+    // the amount of samples of the sample buffer which have been measured so far
+    read_samples: u64,
+    // reused buffer for the samples which haven't been measured yet
+    samples: Vec<f32>,
+
+    config: LoudnessMeterConfig,
+}
+
+impl LoudnessMeter {
+    /// Creates a new instance.
+    pub fn new<F: Fetcher>(processor: &SampleProcessor<F>, config: LoudnessMeterConfig) -> Self {
+        let sample_rate = processor.sample_rate() as f64;
+        let amount_channels = processor.amount_channels().get() as usize;
+
+        let window_len = |window: Duration| ((window.as_secs_f64() * sample_rate) as usize).max(1);
+        let channels = (0..amount_channels)
+            .map(|_| ChannelMeter {
+                k_weighting: KWeighting::new(sample_rate),
+                true_peak: TruePeak::new(),
+
+                squares: Window::new(window_len(config.rms_window)),
+                momentary: Window::new(window_len(MOMENTARY_WINDOW)),
+                short_term: Window::new(window_len(SHORT_TERM_WINDOW)),
+            })
+            .collect();
+
+        Self {
+            channels,
+            loudness: vec![ChannelLoudness::default(); amount_channels].into_boxed_slice(),
+
+            momentary: MIN_LOUDNESS,
+            short_term: MIN_LOUDNESS,
+
+            read_samples: reserve_sample_buffer(processor.fetcher()),
+            samples: Vec::new(),
+
+            config,
+        }
+    }
+
+    /// Measures the samples which the fetcher pushed since the previous processing.
+    ///
+    /// Returns the levels of each channel.
+    pub fn process_loudness<F: Fetcher>(
+        &mut self,
+        processor: &SampleProcessor<F>,
+    ) -> &[ChannelLoudness] {
+        let amount_channels = self.channels.len();
+
+        for loudness in self.loudness.iter_mut() {
+            loudness.true_peak = 0.;
+        }
+
+        self.read_samples = processor.read_samples_since(self.read_samples, &mut self.samples);
+        for frame in self.samples.chunks_exact(amount_channels) {
+            for ((sample, meter), loudness) in frame
+                .iter()
+                .zip(self.channels.iter_mut())
+                .zip(self.loudness.iter_mut())
+            {
+                loudness.true_peak = loudness.true_peak.max(meter.true_peak.process(*sample));
+
+                meter.squares.push((*sample as f64).powi(2));
+
+                let weighted = meter.k_weighting.process(*sample).powi(2);
+                meter.momentary.push(weighted);
+                meter.short_term.push(weighted);
+            }
+        }
+
+        for (meter, loudness) in self.channels.iter().zip(self.loudness.iter_mut()) {
+            loudness.rms = meter.squares.mean().sqrt() as f32;
+            loudness.momentary = to_lufs(meter.momentary.mean());
+            loudness.short_term = to_lufs(meter.short_term.mean());
+        }
+
+        // All channels are weighted equally since the channel layout isn't known.
+        self.momentary = to_lufs(self.channels.iter().map(|c| c.momentary.mean()).sum());
+        self.short_term = to_lufs(self.channels.iter().map(|c| c.short_term.mean()).sum());
+
+        &self.loudness
+    }
+
+    /// Returns the levels of each channel of the latest processing.
+    pub fn loudness(&self) -> &[ChannelLoudness] {
+        &self.loudness
+    }
+
+    /// Returns the momentary loudness of all channels together in LUFS.
+    pub fn momentary(&self) -> f32 {
+        self.momentary
+    }
+
+    /// Returns the short-term loudness of all channels together in LUFS.
+    pub fn short_term(&self) -> f32 {
+        self.short_term
+    }
+
+    /// Returns the current config of the loudness meter.
+    pub fn config(&self) -> &LoudnessMeterConfig {
+        &self.config
+    }
+}
+
+/// Converts the mean square of K-weighted samples into LUFS.
+fn to_lufs(mean_square: f64) -> f32 {
+    let loudness = -0.691 + 10. * mean_square.log10();
+    (loudness as f32).max(MIN_LOUDNESS)
+}
+
+/// The state of a channel of the [LoudnessMeter].
+struct ChannelMeter {
+    k_weighting: KWeighting,
+    true_peak: TruePeak,
+
+    squares: Window,
+    momentary: Window,
+    short_term: Window,
+}
+
+/// A sliding window over the latest values which keeps track of their sum.
+///
+/// Values which haven't been pushed yet count as zero.
+struct Window {
+    values: VecDeque<f64>,
+    len: usize,
+    sum: f64,
+}
+
+impl Window {
+    fn new(len: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(len),
+            len,
+            sum: 0.,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if self.values.len() >= self.len {
+            self.sum -= self.values.pop_front().unwrap();
+        }
+
+        self.values.push_back(value);
+        self.sum += value;
+    }
+
+    fn mean(&self) -> f64 {
+        // rounding errors could lead to tiny negative values
+        self.sum.max(0.) / self.len as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fetcher::Signal, test_util::signal_fetcher};
+
+    const SAMPLE_RATE: u32 = 48_000;
+    // 100 frames per second
+    const FRAMES_PER_STEP: usize = 480;
+
+    fn measure(signals: Vec<Signal>, amount_channels: u16, duration: Duration) -> LoudnessMeter {
+        measure_in_steps(signals, amount_channels, duration, FRAMES_PER_STEP)
+    }
+
+    fn measure_in_steps(
+        signals: Vec<Signal>,
+        amount_channels: u16,
+        duration: Duration,
+        frames_per_step: usize,
+    ) -> LoudnessMeter {
+        let mut processor = SampleProcessor::new(signal_fetcher(
+            signals,
+            SAMPLE_RATE,
+            amount_channels,
+            frames_per_step,
+        ));
+        let mut meter = LoudnessMeter::new(&processor, LoudnessMeterConfig::default());
+
+        let amount_steps =
+            (duration.as_secs_f64() * SAMPLE_RATE as f64 / frames_per_step as f64) as usize;
+        for _ in 0..amount_steps {
+            processor.process_next_samples();
+            meter.process_loudness(&processor);
+        }
+
+        meter
+    }
+
+    #[test]
+    fn silence() {
+        let meter = measure(vec![Signal::Silence], 2, Duration::from_secs(1));
+
+        for loudness in meter.loudness() {
+            assert_eq!(*loudness, ChannelLoudness::default());
+        }
+        assert_eq!(meter.momentary(), MIN_LOUDNESS);
+        assert_eq!(meter.short_term(), MIN_LOUDNESS);
+    }
+
+    /// EBU R128: A 1 kHz sine with 0 dBFS in one channel has a loudness of -3.01 LUFS.
+    #[test]
+    fn full_scale_sine_at_1khz() {
+        let meter = measure(
+            vec![Signal::Sine {
+                frequency: 1_000.,
+                amplitude: 1.,
+            }],
+            1,
+            Duration::from_secs(4),
+        );
+
+        let loudness = meter.loudness()[0];
+        assert!((loudness.momentary + 3.01).abs() < 0.05, "{:?}", loudness);
+        assert!((loudness.short_term + 3.01).abs() < 0.05, "{:?}", loudness);
+        assert!(
+            (loudness.rms - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-3,
+            "{:?}",
+            loudness
+        );
+        assert!((loudness.true_peak - 1.).abs() < 0.01, "{:?}", loudness);
+    }
+
+    #[test]
+    fn steps_longer_than_the_window() {
+        // 100ms per step while the window of the processor only holds about 20ms
+        let meter = measure_in_steps(
+            vec![Signal::Sine {
+                frequency: 1_000.,
+                amplitude: 1.,
+            }],
+            1,
+            Duration::from_secs(4),
+            4_800,
+        );
+
+        let loudness = meter.loudness()[0];
+        assert!((loudness.momentary + 3.01).abs() < 0.05, "{:?}", loudness);
+        assert!((loudness.short_term + 3.01).abs() < 0.05, "{:?}", loudness);
+        assert!((loudness.true_peak - 1.).abs() < 0.01, "{:?}", loudness);
+    }
+
+    #[test]
+    fn channels_are_summed_up() {
+        let meter = measure(
+            vec![Signal::Sine {
+                frequency: 1_000.,
+                amplitude: 0.5,
+            }],
+            2,
+            Duration::from_secs(4),
+        );
+
+        let channel = meter.loudness()[0].momentary;
+        // -6.02 dB due to the half amplitude
+        assert!((channel + 9.03).abs() < 0.05, "{}", channel);
+        // two equally loud channels are 3.01 dB louder than one of them
+        assert!((meter.momentary() - (channel + 3.01)).abs() < 0.05);
+    }
+
+    #[test]
+    fn momentary_reacts_faster_than_short_term() {
+        let meter = measure(
+            vec![Signal::Sine {
+                frequency: 1_000.,
+                amplitude: 1.,
+            }],
+            1,
+            Duration::from_millis(500),
+        );
+
+        let loudness = meter.loudness()[0];
+        assert!((loudness.momentary + 3.01).abs() < 0.05, "{:?}", loudness);
+        assert!(loudness.short_term < loudness.momentary - 5.);
+    }
+
+    #[test]
+    fn amplitude_in_db() {
+        assert_eq!(amplitude_to_db(1.), 0.);
+        assert!((amplitude_to_db(0.5) + 6.02).abs() < 0.01);
+    }
+}
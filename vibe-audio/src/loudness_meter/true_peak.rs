@@ -0,0 +1,88 @@
+//! True peak measurement by oversampling (see ITU-R BS.1770-4, Annex 2).
+use std::f64::consts::PI;
+
+const OVERSAMPLING: usize = 4;
+const TAPS_PER_PHASE: usize = 12;
+
+/// Finds the peaks between the samples by interpolating them with a polyphase FIR filter.
+#[derive(Debug, Clone)]
+pub struct TruePeak {
+    // phases[p][k] = the coefficient of the `k`-th previous sample for the `p`-th interpolated value
+    phases: [[f32; TAPS_PER_PHASE]; OVERSAMPLING],
+    // the latest samples, the newest at index `0`
+    history: [f32; TAPS_PER_PHASE],
+}
+
+impl TruePeak {
+    pub fn new() -> Self {
+        let amount_taps = OVERSAMPLING * TAPS_PER_PHASE;
+        let center = (amount_taps - 1) as f64 / 2.;
+
+        let mut phases = [[0f32; TAPS_PER_PHASE]; OVERSAMPLING];
+        for (phase_idx, phase) in phases.iter_mut().enumerate() {
+            for (tap_idx, coefficient) in phase.iter_mut().enumerate() {
+                let n = (phase_idx + tap_idx * OVERSAMPLING) as f64;
+                let x = (n - center) / OVERSAMPLING as f64;
+
+                let sinc = if x == 0. {
+                    1.
+                } else {
+                    (PI * x).sin() / (PI * x)
+                };
+                // hann window
+                let window = 0.5 - 0.5 * (2. * PI * (n + 0.5) / amount_taps as f64).cos();
+
+                *coefficient = (sinc * window) as f32;
+            }
+
+            // each phase should keep the level of a constant signal
+            let sum = phase.iter().sum::<f32>();
+            phase.iter_mut().for_each(|coefficient| *coefficient /= sum);
+        }
+
+        Self {
+            phases,
+            history: [0.; TAPS_PER_PHASE],
+        }
+    }
+
+    /// Returns the absolute peak of the given sample and the interpolated values before it.
+    pub fn process(&mut self, sample: f32) -> f32 {
+        self.history.copy_within(..TAPS_PER_PHASE - 1, 1);
+        self.history[0] = sample;
+
+        self.phases
+            .iter()
+            .map(|phase| {
+                phase
+                    .iter()
+                    .zip(self.history.iter())
+                    .map(|(coefficient, sample)| coefficient * sample)
+                    .sum::<f32>()
+                    .abs()
+            })
+            .fold(sample.abs(), f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_peak_between_samples() {
+        // a sine with a quarter of the sample rate whose samples miss the peaks
+        let samples = (0..200)
+            .map(|idx| (PI / 4. + idx as f64 * PI / 2.).sin() as f32)
+            .collect::<Vec<f32>>();
+
+        let sample_peak = samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+        let mut true_peak = TruePeak::new();
+        let peak = samples
+            .iter()
+            .fold(0f32, |peak, &s| peak.max(true_peak.process(s)));
+
+        assert!((sample_peak - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-3);
+        assert!((peak - 1.).abs() < 0.05, "True peak: {}", peak);
+    }
+}
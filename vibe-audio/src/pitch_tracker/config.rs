@@ -0,0 +1,34 @@
+use std::{num::NonZero, ops::Range};
+
+/// The config options for [crate::PitchTracker].
+#[derive(Debug, Clone)]
+pub struct PitchTrackerConfig {
+    /// The range of the fundamental frequency (in Hz) which the tracker searches in.
+    ///
+    /// The lowest frequency needs at least two of its periods within [crate::SampleProcessor::window_len],
+    /// so the range gets narrowed if the window is too short (see [crate::PitchTracker::detectable_range]).
+    pub freq_range: Range<NonZero<u16>>,
+
+    /// How periodic the samples have to be to report a pitch (the threshold of the YIN algorithm).
+    /// Must be within `(0, 1]`. The lower the value, the fewer but more reliable pitches are reported.
+    pub threshold: f32,
+
+    /// The level (in dBFS) which the samples need to exceed, quieter samples have no pitch.
+    pub min_level: f32,
+}
+
+impl Default for PitchTrackerConfig {
+    fn default() -> Self {
+        Self {
+            freq_range: default_pitch_freq_range(),
+            threshold: 0.15,
+            min_level: -50.,
+        }
+    }
+}
+
+/// Returns the default value for [PitchTrackerConfig::freq_range]:
+/// The range of the singing voice, from a low bass (`70` Hz) up to a high soprano (`1_100` Hz).
+pub fn default_pitch_freq_range() -> Range<NonZero<u16>> {
+    NonZero::new(70).unwrap()..NonZero::new(1_100).unwrap()
+}
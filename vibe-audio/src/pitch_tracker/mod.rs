@@ -0,0 +1,275 @@
+mod config;
+
+use crate::{amplitude_to_db, fetcher::Fetcher, SampleProcessor};
+use std::ops::{Range, RangeInclusive};
+
+pub use config::*;
+
+/// The pitch of a channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pitch {
+    /// The fundamental frequency (in Hz).
+    ///
+    /// `None` if the samples are too quiet or not periodic enough (for example noise or several notes at once).
+    pub frequency: Option<f32>,
+
+    /// How periodic the samples are, within `[0, 1]`.
+    /// Values close to `1` stand for a clear tone.
+    pub confidence: f32,
+}
+
+/// Tracks the fundamental frequency of a single voice or instrument within the samples of the [SampleProcessor].
+///
+/// Uses the YIN algorithm, which searches the period of the samples in the time domain.
+/// It works best with monophonic audio, so [crate::ChannelMix::Mono] is recommended for stereo sources.
+///
+/// # Example
+/// ```
+/// use vibe_audio::{SampleProcessor, PitchTracker, PitchTrackerConfig, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut pitch_tracker = PitchTracker::new(&sample_processor, PitchTrackerConfig::default());
+///
+/// sample_processor.process_next_samples();
+/// let pitches = pitch_tracker.process_pitch(&sample_processor);
+///
+/// // one result for each channel
+/// assert_eq!(pitches.len(), 2);
+/// // the dummy fetcher is silent
+/// assert_eq!(pitches[0].frequency, None);
+/// ```
+pub struct PitchTracker {
+    // the periods (in samples) which are searched
+    lags: RangeInclusive<usize>,
+    // reused buffer for the cumulative mean normalized difference of each lag
+    differences: Box<[f32]>,
+
+    pitches: Box<[Pitch]>,
+
+    sample_rate: f32,
+    config: PitchTrackerConfig,
+}
+
+impl PitchTracker {
+    /// Creates a new instance.
+    ///
+    /// # Panics
+    /// Panics if [PitchTrackerConfig::threshold] isn't within `(0, 1]`.
+    pub fn new<F: Fetcher>(processor: &SampleProcessor<F>, config: PitchTrackerConfig) -> Self {
+        assert!(
+            config.threshold > 0. && config.threshold <= 1.,
+            "The threshold of the pitch tracker ({}) must be within (0, 1].",
+            config.threshold
+        );
+
+        let sample_rate = processor.sample_rate() as f32;
+
+        // the lowest frequency needs two periods within the window
+        let max_lag = ((sample_rate / config.freq_range.start.get() as f32).ceil() as usize)
+            .min(processor.window_len() / 2)
+            .max(2);
+        let min_lag =
+            ((sample_rate / config.freq_range.end.get() as f32) as usize).clamp(2, max_lag);
+
+        let amount_channels = processor.amount_channels().get() as usize;
+
+        Self {
+            lags: min_lag..=max_lag,
+            differences: vec![0f32; max_lag + 1].into_boxed_slice(),
+
+            pitches: vec![Pitch::default(); amount_channels].into_boxed_slice(),
+
+            sample_rate,
+            config,
+        }
+    }
+
+    /// Computes the pitch of the latest samples of the processor.
+    ///
+    /// Returns the pitch of each channel.
+    pub fn process_pitch<F: Fetcher>(&mut self, processor: &SampleProcessor<F>) -> &[Pitch] {
+        for (channel_idx, pitch) in self.pitches.iter_mut().enumerate() {
+            let samples = processor.samples(channel_idx);
+
+            let rms = (samples.iter().map(|sample| sample * sample).sum::<f32>()
+                / samples.len() as f32)
+                .sqrt();
+
+            *pitch = if amplitude_to_db(rms) < self.config.min_level {
+                Pitch::default()
+            } else {
+                yin(
+                    samples,
+                    self.lags.clone(),
+                    &mut self.differences,
+                    self.config.threshold,
+                    self.sample_rate,
+                )
+            };
+        }
+
+        &self.pitches
+    }
+
+    /// Returns the results of the latest call of [PitchTracker::process_pitch].
+    pub fn pitches(&self) -> &[Pitch] {
+        &self.pitches
+    }
+
+    /// Returns the range of the frequencies (in Hz) which can be detected with the window length of the processor.
+    pub fn detectable_range(&self) -> Range<f32> {
+        self.sample_rate / *self.lags.end() as f32..self.sample_rate / *self.lags.start() as f32
+    }
+
+    /// Returns the current config of the pitch tracker.
+    pub fn config(&self) -> &PitchTrackerConfig {
+        &self.config
+    }
+}
+
+/// Estimates the pitch of the samples with the YIN algorithm.
+///
+/// See: <http://audition.ens.fr/adc/pdf/2002_JASA_YIN.pdf>
+fn yin(
+    samples: &[f32],
+    lags: RangeInclusive<usize>,
+    differences: &mut [f32],
+    threshold: f32,
+    sample_rate: f32,
+) -> Pitch {
+    let max_lag = *lags.end();
+    let len = samples.len() - max_lag;
+
+    // the difference function, normalized by its mean over the smaller lags
+    differences[0] = 1.;
+    let mut sum = 0.;
+    for lag in 1..=max_lag {
+        let difference = samples[..len]
+            .iter()
+            .zip(&samples[lag..lag + len])
+            .map(|(sample, shifted)| (sample - shifted) * (sample - shifted))
+            .sum::<f32>();
+
+        sum += difference;
+        differences[lag] = if sum > 0. {
+            difference * lag as f32 / sum
+        } else {
+            1.
+        };
+    }
+
+    // take the first dip below the threshold to avoid picking a multiple of the period
+    let Some(mut lag) = lags.clone().find(|&lag| differences[lag] < threshold) else {
+        let min_difference = differences[lags].iter().copied().fold(1., f32::min);
+
+        return Pitch {
+            frequency: None,
+            confidence: (1. - min_difference).clamp(0., 1.),
+        };
+    };
+
+    while lag < max_lag && differences[lag + 1] < differences[lag] {
+        lag += 1;
+    }
+
+    let [left, center, right] = [lag - 1, lag, lag + 1].map(|lag| differences[lag.min(max_lag)]);
+    let offset = 0.5 * (left - right) / (left - 2. * center + right);
+    let period = if offset.is_finite() && offset.abs() < 1. {
+        lag as f32 + offset
+    } else {
+        lag as f32
+    };
+
+    Pitch {
+        frequency: Some(sample_rate / period),
+        confidence: (1. - center).clamp(0., 1.),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fetcher::{Signal, SignalFetcher},
+        test_util::{self, SAMPLE_RATE},
+        SampleProcessorConfig,
+    };
+    use std::num::NonZero;
+
+    fn processor(signals: Vec<Signal>, window_len: usize) -> SampleProcessor<SignalFetcher> {
+        test_util::processor_with_config(
+            signals,
+            SampleProcessorConfig {
+                window_len: NonZero::new(window_len),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Returns the pitch after the window got filled.
+    fn track(signals: Vec<Signal>) -> Pitch {
+        let mut processor = processor(signals, 2048);
+        let mut tracker = PitchTracker::new(&processor, PitchTrackerConfig::default());
+
+        for _ in 0..4 {
+            processor.process_next_samples();
+            tracker.process_pitch(&processor);
+        }
+
+        tracker.pitches()[0]
+    }
+
+    fn sine(frequency: f32, amplitude: f32) -> Signal {
+        Signal::Sine {
+            frequency,
+            amplitude,
+        }
+    }
+
+    #[test]
+    fn sine_tones() {
+        for frequency in [82.41, 220., 659.25] {
+            let pitch = track(vec![sine(frequency, 0.5)]);
+
+            let detected = pitch.frequency.unwrap();
+            assert!(
+                (detected - frequency).abs() < frequency * 0.005,
+                "{:?}",
+                pitch
+            );
+            assert!(pitch.confidence > 0.95, "{:?}", pitch);
+        }
+    }
+
+    #[test]
+    fn harmonics_dont_change_the_pitch() {
+        let pitch = track(vec![sine(220., 0.3), sine(440., 0.4), sine(660., 0.2)]);
+
+        let detected = pitch.frequency.unwrap();
+        assert!((detected - 220.).abs() < 1., "{:?}", pitch);
+    }
+
+    #[test]
+    fn silence_has_no_pitch() {
+        assert_eq!(track(vec![Signal::Silence]), Pitch::default());
+        assert_eq!(track(vec![sine(220., 0.001)]), Pitch::default());
+    }
+
+    #[test]
+    fn noise_has_no_pitch() {
+        let pitch = track(vec![Signal::WhiteNoise { amplitude: 0.5 }]);
+
+        assert_eq!(pitch.frequency, None);
+        assert!(pitch.confidence < 0.5, "{:?}", pitch);
+    }
+
+    #[test]
+    fn range_is_limited_by_the_window() {
+        let processor = processor(vec![Signal::Silence], 1024);
+        let tracker = PitchTracker::new(&processor, PitchTrackerConfig::default());
+
+        let range = tracker.detectable_range();
+        assert_eq!(range.start, SAMPLE_RATE as f32 / 512.);
+        assert!(range.end >= 1_100.);
+    }
+}
@@ -0,0 +1,74 @@
+use cpal::SampleRate;
+
+/// Describes the frequencies of the bins of the spectrum which [crate::SampleProcessor] computes
+/// for the [crate::BarProcessor].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BinLayout {
+    /// The bins of the fft output: the `i`-th bin belongs to `i * freq_resolution` Hz.
+    Linear {
+        freq_resolution: f32,
+        amount_bins: usize,
+    },
+
+    /// The bins of the constant-Q transform: the `i`-th bin belongs to `min_freq * 2^(i / bins_per_octave)` Hz.
+    Logarithmic {
+        min_freq: f32,
+        bins_per_octave: f32,
+        amount_bins: usize,
+    },
+}
+
+impl BinLayout {
+    /// Returns the layout of the output of a fft with the given size.
+    pub fn linear(sample_rate: SampleRate, fft_size: usize) -> Self {
+        Self::Linear {
+            freq_resolution: sample_rate as f32 / fft_size as f32,
+            amount_bins: fft_size / 2 + 1,
+        }
+    }
+
+    pub fn amount_bins(&self) -> usize {
+        match *self {
+            Self::Linear { amount_bins, .. } | Self::Logarithmic { amount_bins, .. } => amount_bins,
+        }
+    }
+
+    /// Returns the index of the first bin which carries information about the frequencies.
+    pub fn first_bin(&self) -> usize {
+        match self {
+            // skip the DC bin
+            Self::Linear { .. } => 1,
+            Self::Logarithmic { .. } => 0,
+        }
+    }
+
+    /// Returns the frequency (in Hz) of the given (fractional) bin index.
+    pub fn freq_of(&self, bin: f32) -> f32 {
+        match *self {
+            Self::Linear {
+                freq_resolution, ..
+            } => bin * freq_resolution,
+            Self::Logarithmic {
+                min_freq,
+                bins_per_octave,
+                ..
+            } => min_freq * (bin / bins_per_octave).exp2(),
+        }
+    }
+
+    /// Returns the (fractional) bin index of the given frequency (in Hz).
+    ///
+    /// The index can be negative or beyond the last bin if the frequency isn't covered by the bins.
+    pub fn bin_of(&self, freq: f32) -> f32 {
+        match *self {
+            Self::Linear {
+                freq_resolution, ..
+            } => freq / freq_resolution,
+            Self::Logarithmic {
+                min_freq,
+                bins_per_octave,
+                ..
+            } => bins_per_octave * (freq / min_freq).log2(),
+        }
+    }
+}
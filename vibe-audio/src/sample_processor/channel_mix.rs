@@ -0,0 +1,181 @@
+use super::ChannelMix;
+use std::f32::consts::FRAC_1_SQRT_2;
+
+/// The rows of the matrix which mixes the input channels into the output channels:
+/// `output[i] = sum(matrix[i][j] * input[j])`.
+pub type MixMatrix = Box<[Box<[f32]>]>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Speaker {
+    Left,
+    Right,
+    Center,
+    Lfe,
+}
+
+/// The speaker of each channel of a multichannel stream (the order of ALSA).
+#[cfg(target_os = "linux")]
+const SPEAKER_LAYOUT: [Speaker; 8] = [
+    Speaker::Left,   // front left
+    Speaker::Right,  // front right
+    Speaker::Left,   // rear left
+    Speaker::Right,  // rear right
+    Speaker::Center, // front center
+    Speaker::Lfe,    // low frequency
+    Speaker::Left,   // side left
+    Speaker::Right,  // side right
+];
+
+/// The speaker of each channel of a multichannel stream (the order of WAVE).
+#[cfg(not(target_os = "linux"))]
+const SPEAKER_LAYOUT: [Speaker; 8] = [
+    Speaker::Left,   // front left
+    Speaker::Right,  // front right
+    Speaker::Center, // front center
+    Speaker::Lfe,    // low frequency
+    Speaker::Left,   // back left
+    Speaker::Right,  // back right
+    Speaker::Left,   // side left
+    Speaker::Right,  // side right
+];
+
+impl ChannelMix {
+    /// Returns the matrix which mixes `amount_input_channels` channels into the output channels.
+    ///
+    /// # Panics
+    /// Panics if [ChannelMix::Channel] refers to a channel which doesn't exist.
+    pub(crate) fn matrix(&self, amount_input_channels: usize) -> MixMatrix {
+        let [left, right] = stereo_downmix(amount_input_channels);
+
+        let rows: Vec<Vec<f32>> = match *self {
+            Self::Passthrough => (0..amount_input_channels)
+                .map(|channel_idx| one_hot(amount_input_channels, channel_idx))
+                .collect(),
+            Self::Mono => vec![combine(&left, &right, 0.5)],
+            Self::Stereo => vec![left, right],
+            Self::MidSide => vec![combine(&left, &right, 0.5), combine(&left, &right, -0.5)],
+            Self::Swap => vec![right, left],
+            Self::Channel(channel_idx) => {
+                let channel_idx = channel_idx as usize;
+                assert!(
+                    channel_idx < amount_input_channels,
+                    "Can't pick the channel with the index {} since the fetcher has only {} channels.",
+                    channel_idx,
+                    amount_input_channels
+                );
+
+                vec![one_hot(amount_input_channels, channel_idx)]
+            }
+        };
+
+        rows.into_iter().map(|row| row.into_boxed_slice()).collect()
+    }
+}
+
+/// Returns the rows of the left and right channel to downmix the input channels to stereo.
+///
+/// The center and surround channels are attenuated by 3dB, the low frequency channel is dropped
+/// and channels beyond 7.1 are ignored.
+/// Each row is normalized so that the downmix can't clip.
+fn stereo_downmix(amount_input_channels: usize) -> [Vec<f32>; 2] {
+    match amount_input_channels {
+        1 => [vec![1.], vec![1.]],
+        2 => [one_hot(2, 0), one_hot(2, 1)],
+        _ => [Speaker::Left, Speaker::Right].map(|side| {
+            let mut row = (0..amount_input_channels)
+                .map(|channel_idx| match SPEAKER_LAYOUT.get(channel_idx) {
+                    // the front channels
+                    Some(speaker) if channel_idx < 2 => {
+                        if *speaker == side {
+                            1.
+                        } else {
+                            0.
+                        }
+                    }
+                    Some(Speaker::Center) => FRAC_1_SQRT_2,
+                    Some(speaker) if *speaker == side => FRAC_1_SQRT_2,
+                    _ => 0.,
+                })
+                .collect::<Vec<f32>>();
+
+            let sum = row.iter().sum::<f32>();
+            row.iter_mut().for_each(|weight| *weight /= sum);
+            row
+        }),
+    }
+}
+
+fn one_hot(len: usize, idx: usize) -> Vec<f32> {
+    let mut row = vec![0.; len];
+    row[idx] = 1.;
+    row
+}
+
+/// Returns `0.5 * a + factor * b`.
+fn combine(a: &[f32], b: &[f32], factor: f32) -> Vec<f32> {
+    a.iter().zip(b).map(|(a, b)| 0.5 * a + factor * b).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mix(mix: ChannelMix, frame: &[f32]) -> Vec<f32> {
+        mix.matrix(frame.len())
+            .iter()
+            .map(|row| row.iter().zip(frame).map(|(w, s)| w * s).sum())
+            .collect()
+    }
+
+    #[test]
+    fn stereo() {
+        let frame = [0.25, 0.75];
+
+        assert_eq!(mix(ChannelMix::Passthrough, &frame), [0.25, 0.75]);
+        assert_eq!(mix(ChannelMix::Mono, &frame), [0.5]);
+        assert_eq!(mix(ChannelMix::Stereo, &frame), [0.25, 0.75]);
+        assert_eq!(mix(ChannelMix::MidSide, &frame), [0.5, -0.25]);
+        assert_eq!(mix(ChannelMix::Swap, &frame), [0.75, 0.25]);
+        assert_eq!(mix(ChannelMix::Channel(1), &frame), [0.75]);
+    }
+
+    #[test]
+    fn mono() {
+        let frame = [0.5];
+
+        assert_eq!(mix(ChannelMix::Passthrough, &frame), [0.5]);
+        assert_eq!(mix(ChannelMix::Mono, &frame), [0.5]);
+        assert_eq!(mix(ChannelMix::Stereo, &frame), [0.5, 0.5]);
+        assert_eq!(mix(ChannelMix::MidSide, &frame), [0.5, 0.]);
+    }
+
+    #[test]
+    fn surround_downmix() {
+        // 7.1 where only the front left and the low frequency channel are set
+        let mut frame = [0.; 8];
+        frame[0] = 1.;
+        let lfe_idx = SPEAKER_LAYOUT
+            .iter()
+            .position(|speaker| *speaker == Speaker::Lfe)
+            .unwrap();
+        frame[lfe_idx] = 1.;
+
+        let stereo = mix(ChannelMix::Stereo, &frame);
+        assert_eq!(stereo[1], 0.);
+        assert!(0. < stereo[0] && stereo[0] < 1.);
+
+        // the downmix of full scale channels doesn't clip
+        for amount_channels in 3..=10 {
+            let frame = vec![1.; amount_channels];
+            for sample in mix(ChannelMix::Stereo, &frame) {
+                assert!((sample - 1.).abs() < 1e-6, "{}", sample);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_channel() {
+        ChannelMix::Channel(2).matrix(2);
+    }
+}
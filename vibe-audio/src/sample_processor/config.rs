@@ -0,0 +1,169 @@
+use std::{num::NonZero, ops::Range};
+
+/// The config options for [crate::SampleProcessor].
+///
+/// The default values keep the behaviour of [crate::SampleProcessor::new].
+#[derive(Debug, Clone, Default)]
+pub struct SampleProcessorConfig {
+    /// The amount of samples per channel which are analyzed in each processing.
+    ///
+    /// The sample buffer of the fetcher gets resized accordingly.
+    /// More samples improve the frequency resolution (especially of the bass) but increase the latency.
+    /// `None`: Use the size of the sample buffer of the fetcher.
+    pub window_len: Option<NonZero<usize>>,
+
+    /// The length of the fft input.
+    ///
+    /// The samples are padded with zeros if the fft size is bigger than [SampleProcessorConfig::window_len]
+    /// which interpolates the spectrum (but doesn't increase the actual resolution).
+    /// Values smaller than the window length are raised to it.
+    /// `None`: The size of the sample buffer of the fetcher (for all channels together).
+    pub fft_size: Option<NonZero<usize>>,
+
+    /// The window function which is applied to the samples before the fft.
+    pub window: WindowFunction,
+
+    /// Decides how often a new fft is computed.
+    pub hop: Hop,
+
+    /// Decides how the channels of the fetcher are mixed before they are analyzed.
+    pub channel_mix: ChannelMix,
+
+    /// Decides how the spectrum for the [crate::BarProcessor] is computed.
+    pub backend: SpectralBackend,
+}
+
+/// The window functions which can be applied to the samples before the fft.
+///
+/// The windows are scaled to have the same average (coherent gain) as the [WindowFunction::Hann] window,
+/// so the magnitudes of the fft output are comparable.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum WindowFunction {
+    /// A good compromise between frequency resolution and leakage.
+    #[default]
+    Hann,
+
+    /// Less leakage than [WindowFunction::Hann] at the cost of wider peaks.
+    BlackmanHarris,
+
+    /// Measures the amplitudes of the frequencies precisely but has very wide peaks.
+    FlatTop,
+
+    /// Allows to choose the trade-off between resolution and leakage with `beta`.
+    /// `0` equals no window at all, `8.6` is similar to [WindowFunction::BlackmanHarris].
+    Kaiser { beta: f32 },
+}
+
+/// Decides how often [crate::SampleProcessor::process_next_samples] computes a new fft.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Hop {
+    /// Compute a new fft on each call.
+    #[default]
+    EveryCall,
+
+    /// Compute a new fft only if the fetcher pushed at least the given amount of frames since the last fft.
+    Frames(NonZero<usize>),
+
+    /// Compute a new fft only if the new samples would overlap with the previous fft input by at most the given
+    /// ratio (within `[0, 1)`). For example `0.75` computes a new fft after a quarter of the window length.
+    Overlap(f32),
+}
+
+/// Decides how the channels of the fetcher are mixed into the channels which the [crate::SampleProcessor]
+/// (and everything which uses it) works with.
+///
+/// Sources with more than two channels (for example 5.1 or 7.1) are downmixed to stereo first
+/// by every mode except [ChannelMix::Passthrough] and [ChannelMix::Channel].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ChannelMix {
+    /// Keep the channels of the fetcher.
+    #[default]
+    Passthrough,
+
+    /// Mix all channels into one channel.
+    Mono,
+
+    /// Mix all channels into two channels. Mono sources are duplicated.
+    Stereo,
+
+    /// Two channels: The mid signal (`(left + right) / 2`) and the side signal (`(left - right) / 2`).
+    MidSide,
+
+    /// Two channels where the left and the right channel are swapped.
+    Swap,
+
+    /// Only use the channel with the given index.
+    Channel(u16),
+}
+
+/// Decides how [crate::SampleProcessor] computes the spectrum which the [crate::BarProcessor] works with.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum SpectralBackend {
+    /// One fft over the whole window, so every frequency gets the same resolution (in Hz).
+    ///
+    /// With logarithmically spaced bars, the bass bars share only a few bins while the treble bars
+    /// react as slow as the bass because of the long window.
+    #[default]
+    Fft,
+
+    /// A constant-Q (or variable-Q) transform: The bins are spaced logarithmically and the window of each bin
+    /// covers the same amount of periods, so the bass gets long windows and the treble short ones.
+    ///
+    /// The windows can't be longer than [SampleProcessorConfig::window_len], so the lowest bins need a long window
+    /// to reach their full resolution.
+    /// The fft is still computed for everything else which works with the spectrum (for example [crate::BeatDetector]).
+    ConstantQ(ConstantQConfig),
+
+    /// The fft over the whole window plus further ffts over shorter windows of the newest samples.
+    ///
+    /// The [crate::BarProcessor] takes the bass from the long window and switches to the shorter windows
+    /// wherever their (coarser) bins are still narrower than the bars, so the treble reacts faster.
+    /// Cheaper than [SpectralBackend::ConstantQ], but the resolution changes in steps instead of smoothly.
+    MultiResolution(MultiResolutionConfig),
+}
+
+/// The config options of [SpectralBackend::ConstantQ].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantQConfig {
+    /// The range of the frequencies (in Hz) of the bins.
+    pub freq_range: Range<NonZero<u16>>,
+
+    /// The amount of bins per octave.
+    /// More bins need longer windows for the same frequency.
+    pub bins_per_octave: NonZero<u16>,
+
+    /// Widens the bandwidth of each bin by the given amount (in Hz), which shortens the windows of the low bins
+    /// (also known as variable-Q transform). `0` keeps the ratio between frequency and bandwidth constant.
+    pub bandwidth_offset: f32,
+}
+
+impl Default for ConstantQConfig {
+    fn default() -> Self {
+        Self {
+            freq_range: crate::default_freq_range(),
+            bins_per_octave: NonZero::new(24).unwrap(),
+            bandwidth_offset: 0.,
+        }
+    }
+}
+
+/// The config options of [SpectralBackend::MultiResolution].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiResolutionConfig {
+    /// The amount of ffts, including the one over the whole window.
+    /// Windows which would get shorter than `64` samples are skipped.
+    pub amount_resolutions: NonZero<u8>,
+
+    /// Each further fft uses a window which is this many times shorter than the previous one.
+    /// Values below `2` are raised to `2`.
+    pub ratio: NonZero<u8>,
+}
+
+impl Default for MultiResolutionConfig {
+    fn default() -> Self {
+        Self {
+            amount_resolutions: NonZero::new(3).unwrap(),
+            ratio: NonZero::new(4).unwrap(),
+        }
+    }
+}
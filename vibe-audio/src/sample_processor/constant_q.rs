@@ -0,0 +1,287 @@
+use super::{bin_layout::BinLayout, ConstantQConfig};
+use cpal::SampleRate;
+use realfft::{num_complex::Complex32, RealFftPlanner, RealToComplex};
+use std::{f32::consts::TAU, ops::Range};
+
+/// Coefficients of the spectral kernels below this ratio of their peak are dropped.
+/// See: Brown and Puckette, "An efficient algorithm for the calculation of a constant Q transform" (1992)
+const KERNEL_THRESHOLD: f32 = 0.0054;
+
+/// Computes the constant-Q transform of the samples of each channel.
+///
+/// Each bin correlates the samples with a windowed complex sinusoid (its temporal kernel) whose window covers
+/// the same amount of periods for every bin. All windows end with the newest sample, so the treble bins
+/// only look at the latest few milliseconds while the bass bins look at the whole window.
+///
+/// Instead of correlating in the time domain, the samples get transformed by a fft and multiplied with the
+/// precomputed spectra of the kernels, which are only significant around the frequency of their bin.
+pub(crate) struct ConstantQ {
+    kernels: Box<[SpectralKernel]>,
+    layout: BinLayout,
+
+    channels: Box<[ConstantQChannel]>,
+}
+
+/// The spectrum of a temporal kernel, reduced to its significant coefficients.
+struct SpectralKernel {
+    // the fft bins of the coefficients
+    bins: Range<usize>,
+    // the complex conjugates of the coefficients, divided by the fft size
+    coefficients: Box<[Complex32]>,
+}
+
+#[derive(Clone)]
+struct ConstantQChannel {
+    fft_in: Box<[f32]>,
+    fft_out: Box<[Complex32]>,
+    scratch_buffer: Box<[Complex32]>,
+
+    cq_out: Box<[Complex32]>,
+}
+
+impl ConstantQ {
+    pub fn new(
+        config: &ConstantQConfig,
+        sample_rate: SampleRate,
+        window_len: usize,
+        fft_size: usize,
+        amount_channels: usize,
+        planner: &mut RealFftPlanner<f32>,
+    ) -> Self {
+        let sample_rate = sample_rate as f32;
+        let bins_per_octave = config.bins_per_octave.get() as f32;
+
+        let min_freq = (config.freq_range.start.get() as f32).min(sample_rate / 2.);
+        let max_freq = (config.freq_range.end.get() as f32).clamp(min_freq, sample_rate / 2.);
+        let layout = BinLayout::Logarithmic {
+            min_freq,
+            bins_per_octave,
+            amount_bins: (bins_per_octave * (max_freq / min_freq).log2()) as usize + 1,
+        };
+
+        let fft = planner.plan_fft_forward(fft_size);
+        let mut real_in = fft.make_input_vec();
+        let mut imag_in = fft.make_input_vec();
+        let mut real_out = fft.make_output_vec();
+        let mut imag_out = fft.make_output_vec();
+
+        let kernels = (0..layout.amount_bins())
+            .map(|bin| {
+                let freq = layout.freq_of(bin as f32);
+                let kernel_len = kernel_len(config, freq, sample_rate, window_len);
+                let start = window_len - kernel_len;
+
+                let window = (0..kernel_len)
+                    .map(|idx| 0.5 - 0.5 * (TAU * (idx as f32 + 0.5) / kernel_len as f32).cos())
+                    .collect::<Vec<f32>>();
+                // scale the kernels to the coherent gain of a hann window over the whole window,
+                // so the magnitudes are comparable to the ones of the fft
+                let scale = 0.5 * window_len as f32 / window.iter().sum::<f32>();
+
+                real_in.fill(0.);
+                imag_in.fill(0.);
+                for (idx, weight) in window.iter().enumerate() {
+                    let sample_idx = start + idx;
+                    // the phase grows large for long windows, so it's computed with a higher precision
+                    let phase = (std::f64::consts::TAU * freq as f64 * sample_idx as f64
+                        / sample_rate as f64) as f32;
+
+                    real_in[sample_idx] = scale * weight * phase.cos();
+                    imag_in[sample_idx] = scale * weight * phase.sin();
+                }
+
+                fft.process(&mut real_in, &mut real_out).unwrap();
+                fft.process(&mut imag_in, &mut imag_out).unwrap();
+
+                // the positive frequencies of the spectrum of the complex kernel
+                let spectrum = real_out
+                    .iter()
+                    .zip(imag_out.iter())
+                    .map(|(real, imag)| real + Complex32::i() * imag)
+                    .collect::<Vec<Complex32>>();
+
+                SpectralKernel::new(&spectrum, fft_size)
+            })
+            .collect();
+
+        let fft_out_size = fft_size / 2 + 1;
+        let channel = ConstantQChannel {
+            fft_in: vec![0f32; fft_size].into_boxed_slice(),
+            fft_out: vec![Complex32::ZERO; fft_out_size].into_boxed_slice(),
+            scratch_buffer: vec![Complex32::ZERO; fft.get_scratch_len()].into_boxed_slice(),
+
+            cq_out: vec![Complex32::ZERO; layout.amount_bins()].into_boxed_slice(),
+        };
+
+        Self {
+            kernels,
+            layout,
+
+            channels: vec![channel; amount_channels].into_boxed_slice(),
+        }
+    }
+
+    /// Transforms the (unwindowed) samples of each channel.
+    pub fn process(&mut self, samples: &[Box<[f32]>], fft: &dyn RealToComplex<f32>) {
+        for (channel, samples) in self.channels.iter_mut().zip(samples.iter()) {
+            let (window, padding) = channel.fft_in.split_at_mut(samples.len());
+            window.copy_from_slice(samples);
+            padding.fill(0.);
+
+            fft.process_with_scratch(
+                channel.fft_in.as_mut(),
+                channel.fft_out.as_mut(),
+                channel.scratch_buffer.as_mut(),
+            )
+            .unwrap();
+
+            for (out, kernel) in channel.cq_out.iter_mut().zip(self.kernels.iter()) {
+                *out = channel.fft_out[kernel.bins.clone()]
+                    .iter()
+                    .zip(kernel.coefficients.iter())
+                    .map(|(value, coefficient)| value * coefficient)
+                    .sum();
+            }
+        }
+    }
+
+    pub fn layout(&self) -> BinLayout {
+        self.layout
+    }
+
+    /// Returns the constant-Q transform of the given channel.
+    pub fn cq_out(&self, channel_idx: usize) -> &[Complex32] {
+        &self.channels[channel_idx].cq_out
+    }
+}
+
+/// Returns the length (in samples) of the window of the temporal kernel of the given frequency.
+fn kernel_len(config: &ConstantQConfig, freq: f32, sample_rate: f32, window_len: usize) -> usize {
+    // the ratio between the frequency of a bin and its bandwidth
+    let q = 1. / ((1. / config.bins_per_octave.get() as f32).exp2() - 1.);
+    let bandwidth = freq / q + config.bandwidth_offset;
+
+    ((sample_rate / bandwidth).round() as usize).clamp(1, window_len)
+}
+
+impl SpectralKernel {
+    fn new(spectrum: &[Complex32], fft_size: usize) -> Self {
+        let peak = spectrum
+            .iter()
+            .fold(0f32, |peak, value| peak.max(value.norm()));
+        let is_significant = |value: &Complex32| value.norm() >= peak * KERNEL_THRESHOLD;
+
+        let start = spectrum.iter().position(is_significant).unwrap_or(0);
+        let end = spectrum
+            .iter()
+            .rposition(is_significant)
+            .map(|idx| idx + 1)
+            .unwrap_or(start);
+
+        Self {
+            coefficients: spectrum[start..end]
+                .iter()
+                .map(|value| value.conj() / fft_size as f32)
+                .collect(),
+            bins: start..end,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZero;
+
+    const SAMPLE_RATE: SampleRate = 44_100;
+    const WINDOW_LEN: usize = 8192;
+
+    fn constant_q(config: &ConstantQConfig, planner: &mut RealFftPlanner<f32>) -> ConstantQ {
+        ConstantQ::new(config, SAMPLE_RATE, WINDOW_LEN, WINDOW_LEN, 1, planner)
+    }
+
+    /// Returns the length of the window of each bin.
+    fn window_lens(config: &ConstantQConfig) -> Vec<usize> {
+        let mut planner = RealFftPlanner::new();
+        let layout = constant_q(config, &mut planner).layout();
+
+        (0..layout.amount_bins())
+            .map(|bin| {
+                kernel_len(
+                    config,
+                    layout.freq_of(bin as f32),
+                    SAMPLE_RATE as f32,
+                    WINDOW_LEN,
+                )
+            })
+            .collect()
+    }
+
+    fn sine(frequency: f32) -> Box<[f32]> {
+        (0..WINDOW_LEN)
+            .map(|idx| (TAU * frequency * idx as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn sine_lands_in_its_bin() {
+        let mut planner = RealFftPlanner::new();
+        let mut constant_q = constant_q(&ConstantQConfig::default(), &mut planner);
+        let fft = planner.plan_fft_forward(WINDOW_LEN);
+
+        for bin in [10, 100, 180] {
+            let frequency = constant_q.layout().freq_of(bin as f32);
+            constant_q.process(&[sine(frequency)], fft.as_ref());
+
+            let magnitudes = constant_q
+                .cq_out(0)
+                .iter()
+                .map(|value| value.norm())
+                .collect::<Vec<f32>>();
+            let loudest_bin = magnitudes
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(idx, _)| idx)
+                .unwrap();
+
+            assert_eq!(loudest_bin, bin);
+            // same magnitude as the peak of a fft with a hann window
+            let expected = WINDOW_LEN as f32 / 4.;
+            assert!(
+                (magnitudes[bin] - expected).abs() < expected * 0.05,
+                "{}: {}",
+                bin,
+                magnitudes[bin]
+            );
+        }
+    }
+
+    #[test]
+    fn bass_gets_longer_windows() {
+        let window_lens = window_lens(&ConstantQConfig::default());
+
+        // the lowest bins are limited by the window length of the processor
+        assert_eq!(window_lens[0], WINDOW_LEN);
+        assert!(window_lens.windows(2).all(|pair| pair[1] <= pair[0]));
+        // 24 bins per octave at 10 kHz => roughly 34 periods
+        assert!(*window_lens.last().unwrap() < 200);
+    }
+
+    #[test]
+    fn bandwidth_offset_shortens_the_windows() {
+        let config = ConstantQConfig {
+            freq_range: NonZero::new(100).unwrap()..NonZero::new(10_000).unwrap(),
+            ..Default::default()
+        };
+
+        let constant = window_lens(&config);
+        let variable = window_lens(&ConstantQConfig {
+            bandwidth_offset: 10.,
+            ..config
+        });
+
+        assert!(variable[0] < constant[0] / 2);
+        assert!(variable.last().unwrap() <= constant.last().unwrap());
+    }
+}
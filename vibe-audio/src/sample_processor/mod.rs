@@ -0,0 +1,663 @@
+mod bin_layout;
+mod channel_mix;
+mod config;
+mod constant_q;
+mod multi_resolution;
+mod window;
+
+use std::{num::NonZero, ops::Range};
+
+use cpal::SampleRate;
+use realfft::{num_complex::Complex32, RealFftPlanner};
+
+use crate::fetcher::{Fetcher, SampleBuffer};
+use channel_mix::MixMatrix;
+use constant_q::ConstantQ;
+use multi_resolution::MultiResolution;
+
+pub(crate) use bin_layout::BinLayout;
+
+pub use config::*;
+
+/// Prepares the samples of the fetcher for the [crate::BarProcessor].
+pub struct SampleProcessor<F: Fetcher> {
+    planner: RealFftPlanner<f32>,
+    window: Box<[f32]>,
+
+    // The fft context (fft output + additional buffers) per channel
+    channels: Box<[FftContext]>,
+    // The raw samples (without any window function) per channel
+    samples: Box<[Box<[f32]>]>,
+    // only set for `SpectralBackend::ConstantQ`
+    constant_q: Option<ConstantQ>,
+    // only set for `SpectralBackend::MultiResolution`
+    multi_resolution: Option<MultiResolution>,
+    sample_buffer: SampleBuffer,
+    // the latest (interleaved) samples of the sample buffer, the oldest sample comes first
+    snapshot: Box<[f32]>,
+
+    // mixes the channels of the fetcher into the channels of the processor
+    mix: MixMatrix,
+    amount_input_channels: usize,
+
+    // aka fft input length
+    fft_size: usize,
+    // the minimal amount of new frames between two ffts
+    hop: usize,
+    // the amount of frames which the fetcher pushed since the last fft
+    frames_since_fft: usize,
+
+    // the value of `SampleBuffer::amount_pushed_samples` during the last processing
+    amount_pushed_samples: u64,
+    // the amount of frames which the fetcher pushed between the last two processings
+    amount_new_frames: usize,
+
+    config: SampleProcessorConfig,
+
+    // Relevant for the system audio fetcher, otherwise it will get dropped and so the stream as well.
+    fetcher: F,
+}
+
+impl<F: Fetcher> SampleProcessor<F> {
+    /// Creates a new instance with the given fetcher where the audio samples are fetched from.
+    pub fn new(fetcher: F) -> Self {
+        Self::with_config(fetcher, SampleProcessorConfig::default())
+    }
+
+    /// Creates a new instance with the given fetcher and config.
+    ///
+    /// # Panics
+    /// Panics if [ChannelMix::Channel] refers to a channel which the fetcher doesn't have.
+    ///
+    /// # Example
+    /// ```
+    /// use std::num::NonZero;
+    /// use vibe_audio::{SampleProcessor, SampleProcessorConfig, WindowFunction, Hop, ChannelMix, SpectralBackend, fetcher::DummyFetcher};
+    ///
+    /// let sample_processor = SampleProcessor::with_config(
+    ///     DummyFetcher::new(2),
+    ///     SampleProcessorConfig {
+    ///         // better bass resolution...
+    ///         window_len: Some(NonZero::new(4096).unwrap()),
+    ///         // ... and a smoother spectrum
+    ///         fft_size: Some(NonZero::new(16384).unwrap()),
+    ///         window: WindowFunction::BlackmanHarris,
+    ///         hop: Hop::Overlap(0.75),
+    ///         // only look at the left channel
+    ///         channel_mix: ChannelMix::Channel(0),
+    ///         backend: SpectralBackend::Fft,
+    ///     },
+    /// );
+    ///
+    /// assert_eq!(sample_processor.window_len(), 4096);
+    /// assert_eq!(sample_processor.fft_size(), 16384);
+    /// assert_eq!(sample_processor.amount_channels().get(), 1);
+    /// ```
+    pub fn with_config(fetcher: F, config: SampleProcessorConfig) -> Self {
+        let sample_buffer = fetcher.sample_buffer();
+        let amount_channels = fetcher.channels() as usize;
+
+        if let Some(window_len) = config.window_len {
+            sample_buffer.set_capacity(window_len.get() * amount_channels);
+        }
+        let window_len = sample_buffer.capacity() / amount_channels;
+
+        let fft_size = config
+            .fft_size
+            .map(|size| size.get())
+            .unwrap_or(window_len * amount_channels)
+            .max(window_len);
+
+        let fft_out_size = fft_size / 2 + 1;
+
+        let hop = match config.hop {
+            Hop::EveryCall => 0,
+            Hop::Frames(frames) => frames.get(),
+            Hop::Overlap(overlap) => {
+                let overlap = overlap.clamp(0., 1.);
+                ((window_len as f32 * (1. - overlap)) as usize).max(1)
+            }
+        };
+
+        let mix = config.channel_mix.matrix(amount_channels);
+        let amount_output_channels = mix.len();
+
+        let channels = vec![FftContext::new(fft_size, fft_out_size); amount_output_channels]
+            .into_boxed_slice();
+
+        let samples = vec![vec![0f32; window_len].into_boxed_slice(); amount_output_channels]
+            .into_boxed_slice();
+
+        let mut planner = RealFftPlanner::new();
+        let constant_q = match &config.backend {
+            SpectralBackend::Fft | SpectralBackend::MultiResolution(_) => None,
+            SpectralBackend::ConstantQ(constant_q_config) => Some(ConstantQ::new(
+                constant_q_config,
+                sample_buffer.sample_rate(),
+                window_len,
+                fft_size,
+                amount_output_channels,
+                &mut planner,
+            )),
+        };
+        let multi_resolution = match &config.backend {
+            SpectralBackend::MultiResolution(multi_resolution_config) => {
+                Some(MultiResolution::new(
+                    multi_resolution_config,
+                    config.window,
+                    window_len,
+                    fft_size,
+                    amount_output_channels,
+                ))
+            }
+            SpectralBackend::Fft | SpectralBackend::ConstantQ(_) => None,
+        };
+
+        Self {
+            planner,
+            window: config.window.coefficients(window_len),
+
+            channels,
+            samples,
+            constant_q,
+            multi_resolution,
+
+            sample_buffer,
+            snapshot: vec![0f32; window_len * amount_channels].into_boxed_slice(),
+            mix,
+            amount_input_channels: amount_channels,
+
+            fft_size,
+            hop,
+            frames_since_fft: 0,
+
+            amount_pushed_samples: 0,
+            amount_new_frames: 0,
+
+            config,
+            fetcher,
+        }
+    }
+
+    /// Tell the processor to take some samples of the fetcher and prepare them
+    /// for the [crate::BarProcessor]s.
+    pub fn process_next_samples(&mut self) {
+        let amount_channels = self.amount_input_channels;
+
+        self.fetcher.advance();
+
+        // fetch the latest data
+        let amount_pushed_samples = self.sample_buffer.snapshot(&mut self.snapshot);
+        self.amount_new_frames = ((amount_pushed_samples - self.amount_pushed_samples)
+            / amount_channels as u64) as usize;
+        self.amount_pushed_samples = amount_pushed_samples;
+
+        self.frames_since_fft += self.amount_new_frames;
+        if self.frames_since_fft < self.hop {
+            return;
+        }
+        self.frames_since_fft = 0;
+
+        for (sample_idx, frame) in self.snapshot.chunks_exact(amount_channels).enumerate() {
+            for ((channel, channel_samples), weights) in self
+                .channels
+                .iter_mut()
+                .zip(self.samples.iter_mut())
+                .zip(self.mix.iter())
+            {
+                let sample = mix_frame(frame, weights);
+
+                channel_samples[sample_idx] = sample;
+                channel.fft_in[sample_idx] = sample * self.window[sample_idx];
+            }
+        }
+
+        let fft = self.planner.plan_fft_forward(self.fft_size);
+        for channel in self.channels.iter_mut() {
+            // the fft uses its input as scratch space, so the zero padding needs to be restored
+            channel.fft_in[self.window.len()..].fill(0.);
+
+            fft.process_with_scratch(
+                channel.fft_in.as_mut(),
+                channel.fft_out.as_mut(),
+                channel.scratch_buffer.as_mut(),
+            )
+            .unwrap();
+        }
+
+        if let Some(constant_q) = self.constant_q.as_mut() {
+            constant_q.process(&self.samples, fft.as_ref());
+        }
+
+        if let Some(multi_resolution) = self.multi_resolution.as_mut() {
+            multi_resolution.process(&self.samples, &mut self.planner);
+        }
+    }
+}
+
+impl<F: Fetcher> SampleProcessor<F> {
+    /// Returns the length of the fft input (including the zero padding).
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// Returns the amount of samples per channel which are analyzed in each processing.
+    pub fn window_len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Returns the config of the processor.
+    pub fn config(&self) -> &SampleProcessorConfig {
+        &self.config
+    }
+
+    pub(crate) fn fft_out(&self) -> &[FftContext] {
+        &self.channels
+    }
+
+    /// Returns the magnitudes of the given bins of the fft output of the channel.
+    ///
+    /// The magnitudes are normalized so that they don't depend on the fft size.
+    pub(crate) fn magnitudes(
+        &self,
+        channel_idx: usize,
+        bins: Range<usize>,
+    ) -> impl Iterator<Item = f32> + '_ {
+        let factor = 2. / self.fft_size as f32;
+
+        self.channels[channel_idx].fft_out[bins]
+            .iter()
+            .map(move |out| out.norm() * factor)
+    }
+
+    /// Returns the spectrum of the given channel and resolution which has been computed by the configured [SpectralBackend].
+    ///
+    /// See [SampleProcessor::bin_layouts] for the available resolutions and the frequencies of their bins.
+    pub(crate) fn spectrum(&self, channel_idx: usize, resolution_idx: usize) -> &[Complex32] {
+        match (resolution_idx, &self.constant_q, &self.multi_resolution) {
+            (0, Some(constant_q), _) => constant_q.cq_out(channel_idx),
+            (0, None, _) => &self.channels[channel_idx].fft_out,
+            (_, _, Some(multi_resolution)) => {
+                multi_resolution.fft_out(resolution_idx - 1, channel_idx)
+            }
+            (_, _, None) => panic!("There's only one resolution without multi-resolution ffts."),
+        }
+    }
+
+    /// Returns the frequencies of the bins of each resolution of [SampleProcessor::spectrum],
+    /// ordered from the finest to the coarsest bins.
+    ///
+    /// Only [SpectralBackend::MultiResolution] has more than one resolution.
+    pub(crate) fn bin_layouts(&self) -> Box<[BinLayout]> {
+        let main = match &self.constant_q {
+            Some(constant_q) => constant_q.layout(),
+            None => BinLayout::linear(self.sample_rate(), self.fft_size),
+        };
+
+        std::iter::once(main)
+            .chain(
+                self.multi_resolution
+                    .iter()
+                    .flat_map(|multi_resolution| multi_resolution.layouts(self.sample_rate())),
+            )
+            .collect()
+    }
+
+    /// Calls `f` with the (interleaved and mixed) samples which the fetcher pushed between the last two processings.
+    ///
+    /// Only the samples which are still within the sample buffer are given.
+    pub(crate) fn with_new_samples<R>(&self, f: impl FnOnce(&[f32]) -> R) -> R {
+        let amount_samples =
+            (self.amount_new_frames * self.amount_input_channels).min(self.snapshot.len());
+        let new_samples = &self.snapshot[self.snapshot.len() - amount_samples..];
+
+        if self.config.channel_mix == ChannelMix::Passthrough {
+            return f(new_samples);
+        }
+
+        let mixed = new_samples
+            .chunks_exact(self.amount_input_channels)
+            .flat_map(|frame| self.mix.iter().map(|weights| mix_frame(frame, weights)))
+            .collect::<Vec<f32>>();
+
+        f(&mixed)
+    }
+
+    /// Replaces the content of `out` with the (interleaved and mixed) samples which the fetcher pushed
+    /// after `since` samples (see [SampleBuffer::amount_pushed_samples]).
+    ///
+    /// In contrast to [SampleProcessor::with_new_samples], the samples aren't limited to the window,
+    /// so a reader with its own cursor gets every sample as long as the sample buffer still holds it.
+    ///
+    /// Returns the value of `since` for the next call.
+    pub(crate) fn read_samples_since(&self, since: u64, out: &mut Vec<f32>) -> u64 {
+        let read = self.sample_buffer.read_since(since, out);
+
+        if self.config.channel_mix != ChannelMix::Passthrough {
+            *out = out
+                .chunks_exact(self.amount_input_channels)
+                .flat_map(|frame| self.mix.iter().map(|weights| mix_frame(frame, weights)))
+                .collect();
+        }
+
+        read
+    }
+
+    pub(crate) fn sample_rate(&self) -> SampleRate {
+        self.sample_buffer.sample_rate()
+    }
+
+    /// Returns the amount of frames which the fetcher pushed between the last two calls of
+    /// [SampleProcessor::process_next_samples].
+    ///
+    /// Divided by the sample rate, this is the amount of audio time which passed in between.
+    pub fn amount_new_frames(&self) -> usize {
+        self.amount_new_frames
+    }
+
+    /// Returns the samples of the given channel which were used for the latest processing,
+    /// before any window function got applied.
+    ///
+    /// The oldest sample comes first, so the most recently pushed samples are at the end.
+    ///
+    /// # Panics
+    /// Panics if `channel_idx` isn't smaller than [SampleProcessor::amount_channels].
+    ///
+    /// # Example
+    /// ```
+    /// use vibe_audio::{SampleProcessor, fetcher::{DummyFetcher, Fetcher}};
+    ///
+    /// let fetcher = DummyFetcher::new(2);
+    /// // interleaved samples: left, right, left, right
+    /// fetcher.sample_buffer().push(&[0.1, 0.2, 0.3, 0.4]);
+    ///
+    /// let mut sample_processor = SampleProcessor::new(fetcher);
+    /// sample_processor.process_next_samples();
+    ///
+    /// let window_len = sample_processor.window_len();
+    /// assert_eq!(sample_processor.samples(0)[window_len - 2..], [0.1, 0.3]);
+    /// assert_eq!(sample_processor.samples(1)[window_len - 2..], [0.2, 0.4]);
+    /// ```
+    pub fn samples(&self, channel_idx: usize) -> &[f32] {
+        &self.samples[channel_idx]
+    }
+
+    /// Writes the samples of the given channel (see [SampleProcessor::samples]) into `out`,
+    /// where each value is the average of `factor` consecutive samples.
+    ///
+    /// Useful to reduce the amount of points of a waveform.
+    /// Remaining samples which don't fill up a whole group are ignored.
+    ///
+    /// # Panics
+    /// Panics if `channel_idx` isn't smaller than [SampleProcessor::amount_channels].
+    pub fn decimated_samples(
+        &self,
+        channel_idx: usize,
+        factor: NonZero<usize>,
+        out: &mut Vec<f32>,
+    ) {
+        let factor = factor.get();
+
+        out.clear();
+        out.extend(
+            self.samples[channel_idx]
+                .chunks_exact(factor)
+                .map(|group| group.iter().sum::<f32>() / factor as f32),
+        );
+    }
+
+    pub fn amount_channels(&self) -> NonZero<u8> {
+        NonZero::new(self.channels.len() as u8).unwrap()
+    }
+
+    /// Returns the fetcher where the samples are fetched from.
+    pub fn fetcher(&self) -> &F {
+        &self.fetcher
+    }
+
+    /// Returns the fetcher where the samples are fetched from.
+    ///
+    /// Useful to control fetchers like [crate::fetcher::FileFetcher] (for example to seek within the file).
+    pub fn fetcher_mut(&mut self) -> &mut F {
+        &mut self.fetcher
+    }
+}
+
+/// Returns the weighted sum of the samples of the frame.
+fn mix_frame(frame: &[f32], weights: &[f32]) -> f32 {
+    frame
+        .iter()
+        .zip(weights)
+        .map(|(sample, weight)| sample * weight)
+        .sum()
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FftContext {
+    fft_in: Box<[f32]>,
+    pub fft_out: Box<[Complex32]>,
+    scratch_buffer: Box<[Complex32]>,
+}
+
+impl FftContext {
+    fn new(fft_size: usize, fft_out_size: usize) -> Self {
+        let fft_in = vec![0.; fft_size].into_boxed_slice();
+        let fft_out = vec![Complex32::ZERO; fft_out_size].into_boxed_slice();
+        let scratch_buffer = fft_out.clone();
+
+        Self {
+            fft_in,
+            fft_out,
+            scratch_buffer,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::{DummyFetcher, Pacing, Signal, SignalFetcher, SignalFetcherDescriptor};
+
+    #[test]
+    fn samples_are_not_windowed() {
+        let mut processor = SampleProcessor::new(
+            SignalFetcher::new(&SignalFetcherDescriptor {
+                signals: vec![Signal::Sine {
+                    frequency: 441.,
+                    amplitude: 1.,
+                }],
+                sample_rate: 44_100,
+                amount_channels: 1,
+                pacing: Pacing::Offline {
+                    frames_per_step: NonZero::new(2048).unwrap(),
+                },
+            })
+            .unwrap(),
+        );
+        processor.process_next_samples();
+
+        let samples = processor.samples(0);
+        assert_eq!(samples.len(), processor.window_len());
+
+        // the hann window would bring the borders to zero
+        let peak = |samples: &[f32]| samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak(&samples[..100]) > 0.99);
+        assert!(peak(&samples[samples.len() - 100..]) > 0.99);
+    }
+
+    fn sine_processor(
+        config: SampleProcessorConfig,
+        frames_per_step: usize,
+    ) -> SampleProcessor<SignalFetcher> {
+        SampleProcessor::with_config(
+            SignalFetcher::new(&SignalFetcherDescriptor {
+                signals: vec![Signal::Sine {
+                    frequency: 1_000.,
+                    amplitude: 1.,
+                }],
+                sample_rate: 44_100,
+                amount_channels: 2,
+                pacing: Pacing::Offline {
+                    frames_per_step: NonZero::new(frames_per_step).unwrap(),
+                },
+            })
+            .unwrap(),
+            config,
+        )
+    }
+
+    fn loudest_bin(processor: &SampleProcessor<SignalFetcher>) -> usize {
+        processor.fft_out()[0]
+            .fft_out
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.norm().total_cmp(&b.norm()))
+            .map(|(idx, _)| idx)
+            .unwrap()
+    }
+
+    #[test]
+    fn window_len_resizes_the_sample_buffer() {
+        let processor = sine_processor(
+            SampleProcessorConfig {
+                window_len: Some(NonZero::new(4096).unwrap()),
+                ..Default::default()
+            },
+            512,
+        );
+
+        assert_eq!(processor.window_len(), 4096);
+        assert_eq!(processor.samples(1).len(), 4096);
+        // two channels
+        assert_eq!(processor.fft_size(), 8192);
+        assert_eq!(processor.fetcher().sample_buffer().capacity(), 8192);
+    }
+
+    #[test]
+    fn zero_padding() {
+        for window in [
+            WindowFunction::Hann,
+            WindowFunction::BlackmanHarris,
+            WindowFunction::FlatTop,
+            WindowFunction::Kaiser { beta: 8.6 },
+        ] {
+            let mut processor = sine_processor(
+                SampleProcessorConfig {
+                    window_len: Some(NonZero::new(1024).unwrap()),
+                    fft_size: Some(NonZero::new(8192).unwrap()),
+                    window,
+                    ..Default::default()
+                },
+                1024,
+            );
+            assert_eq!(processor.fft_size(), 8192);
+
+            processor.process_next_samples();
+
+            let freq_resolution = 44_100f32 / 8192.;
+            let expected_bin = (1_000. / freq_resolution).round() as usize;
+            assert!(
+                // within the resolution of the window without zero padding
+                loudest_bin(&processor).abs_diff(expected_bin) <= 8192 / 1024,
+                "{:?}",
+                window
+            );
+        }
+    }
+
+    #[test]
+    fn fft_size_is_at_least_the_window_len() {
+        let processor = sine_processor(
+            SampleProcessorConfig {
+                window_len: Some(NonZero::new(2048).unwrap()),
+                fft_size: Some(NonZero::new(16).unwrap()),
+                ..Default::default()
+            },
+            512,
+        );
+
+        assert_eq!(processor.fft_size(), 2048);
+    }
+
+    #[test]
+    fn hop() {
+        let mut processor = sine_processor(
+            SampleProcessorConfig {
+                window_len: Some(NonZero::new(2048).unwrap()),
+                // a new fft after 1024 frames => every second step
+                hop: Hop::Overlap(0.5),
+                ..Default::default()
+            },
+            512,
+        );
+
+        let mut changes = Vec::new();
+        let mut prev = processor.samples(0).to_vec();
+        for _ in 0..6 {
+            processor.process_next_samples();
+            changes.push(processor.samples(0) != prev);
+            prev = processor.samples(0).to_vec();
+        }
+
+        assert_eq!(changes, [false, true, false, true, false, true]);
+    }
+
+    #[test]
+    fn channel_mix() {
+        let fetcher = DummyFetcher::new(2);
+        // interleaved samples: left, right, left, right
+        fetcher.sample_buffer().push(&[0.1, 0.3, 0.2, 0.4]);
+
+        let mut processor = SampleProcessor::with_config(
+            fetcher,
+            SampleProcessorConfig {
+                channel_mix: ChannelMix::Swap,
+                ..Default::default()
+            },
+        );
+        processor.process_next_samples();
+
+        assert_eq!(processor.amount_channels().get(), 2);
+        let window_len = processor.window_len();
+        assert_eq!(processor.samples(0)[window_len - 2..], [0.3, 0.4]);
+        assert_eq!(processor.samples(1)[window_len - 2..], [0.1, 0.2]);
+        processor.with_new_samples(|samples| assert_eq!(samples, [0.3, 0.1, 0.4, 0.2]));
+    }
+
+    #[test]
+    fn surround_gets_downmixed() {
+        let fetcher = DummyFetcher::new(6);
+        fetcher.sample_buffer().push(&[0.5; 6]);
+
+        let mut processor = SampleProcessor::with_config(
+            fetcher,
+            SampleProcessorConfig {
+                channel_mix: ChannelMix::Mono,
+                ..Default::default()
+            },
+        );
+        processor.process_next_samples();
+
+        assert_eq!(processor.amount_channels().get(), 1);
+        assert!((processor.samples(0).last().unwrap() - 0.5).abs() < 1e-6);
+        processor.with_new_samples(|samples| assert_eq!(samples.len(), 1));
+    }
+
+    #[test]
+    fn decimated_samples() {
+        let fetcher = DummyFetcher::new(2);
+        fetcher
+            .sample_buffer()
+            .push(&[1., -1., 3., -3., 5., -5., 7., -7., 9., -9.]);
+
+        let mut processor = SampleProcessor::new(fetcher);
+        processor.process_next_samples();
+
+        let mut out = Vec::new();
+        processor.decimated_samples(1, NonZero::new(2).unwrap(), &mut out);
+
+        assert_eq!(out.len(), processor.samples(1).len() / 2);
+        // the first group only contains the oldest pushed frame
+        assert_eq!(out[out.len() - 3..], [-0.5, -4., -8.]);
+    }
+}
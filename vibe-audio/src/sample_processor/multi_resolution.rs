@@ -0,0 +1,165 @@
+use super::{bin_layout::BinLayout, FftContext, MultiResolutionConfig, WindowFunction};
+use cpal::SampleRate;
+use realfft::{num_complex::Complex32, RealFftPlanner};
+
+/// Windows shorter than this (in samples) don't resolve anything useful anymore.
+const MIN_WINDOW_LEN: usize = 64;
+
+/// Computes the additional ffts of [super::SpectralBackend::MultiResolution].
+///
+/// Each fft only looks at the newest samples of the window, so the shorter the window,
+/// the faster its spectrum follows the audio (at the cost of coarser bins).
+pub(crate) struct MultiResolution {
+    // ordered from the longest to the shortest window
+    resolutions: Box<[Resolution]>,
+}
+
+struct Resolution {
+    window: Box<[f32]>,
+    fft_size: usize,
+
+    // The fft context per channel
+    channels: Box<[FftContext]>,
+}
+
+impl MultiResolution {
+    /// Prepares the ffts which come after the fft over the whole window (with the given length and fft size).
+    pub fn new(
+        config: &MultiResolutionConfig,
+        window: WindowFunction,
+        window_len: usize,
+        fft_size: usize,
+        amount_channels: usize,
+    ) -> Self {
+        let ratio = config.ratio.get().max(2) as usize;
+
+        let resolutions = (1..config.amount_resolutions.get() as u32)
+            .map(|idx| ratio.pow(idx))
+            .take_while(|divisor| window_len / divisor >= MIN_WINDOW_LEN)
+            .map(|divisor| {
+                let len = window_len / divisor;
+                // keep the zero padding of the fft over the whole window
+                let fft_size = (fft_size / divisor).max(len);
+
+                // a shorter window collects less energy, so it gets amplified
+                // to keep the magnitudes comparable to the ones of the whole window
+                let scale = window_len as f32 / len as f32;
+                let window = window
+                    .coefficients(len)
+                    .iter()
+                    .map(|coefficient| coefficient * scale)
+                    .collect();
+
+                Resolution {
+                    window,
+                    fft_size,
+                    channels: vec![FftContext::new(fft_size, fft_size / 2 + 1); amount_channels]
+                        .into_boxed_slice(),
+                }
+            })
+            .collect();
+
+        Self { resolutions }
+    }
+
+    /// Transforms the newest (unwindowed) samples of each channel.
+    pub fn process(&mut self, samples: &[Box<[f32]>], planner: &mut RealFftPlanner<f32>) {
+        for resolution in self.resolutions.iter_mut() {
+            let fft = planner.plan_fft_forward(resolution.fft_size);
+            let len = resolution.window.len();
+
+            for (channel, samples) in resolution.channels.iter_mut().zip(samples.iter()) {
+                let (window, padding) = channel.fft_in.split_at_mut(len);
+                for ((value, sample), coefficient) in window
+                    .iter_mut()
+                    .zip(samples[samples.len() - len..].iter())
+                    .zip(resolution.window.iter())
+                {
+                    *value = sample * coefficient;
+                }
+                padding.fill(0.);
+
+                fft.process_with_scratch(
+                    channel.fft_in.as_mut(),
+                    channel.fft_out.as_mut(),
+                    channel.scratch_buffer.as_mut(),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    /// Returns the layouts of the spectra, ordered from the finest to the coarsest bins.
+    pub fn layouts(&self, sample_rate: SampleRate) -> impl Iterator<Item = BinLayout> + '_ {
+        self.resolutions
+            .iter()
+            .map(move |resolution| BinLayout::linear(sample_rate, resolution.fft_size))
+    }
+
+    /// Returns the fft output of the given resolution and channel.
+    pub fn fft_out(&self, resolution_idx: usize, channel_idx: usize) -> &[Complex32] {
+        &self.resolutions[resolution_idx].channels[channel_idx].fft_out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{f32::consts::TAU, num::NonZero};
+
+    const SAMPLE_RATE: SampleRate = 44_100;
+    const WINDOW_LEN: usize = 4096;
+
+    fn build(amount_resolutions: u8, ratio: u8) -> MultiResolution {
+        MultiResolution::new(
+            &MultiResolutionConfig {
+                amount_resolutions: NonZero::new(amount_resolutions).unwrap(),
+                ratio: NonZero::new(ratio).unwrap(),
+            },
+            WindowFunction::Hann,
+            WINDOW_LEN,
+            WINDOW_LEN,
+            1,
+        )
+    }
+
+    #[test]
+    fn windows_get_shorter() {
+        let multi_resolution = build(3, 4);
+
+        let lens = multi_resolution
+            .resolutions
+            .iter()
+            .map(|resolution| resolution.window.len())
+            .collect::<Vec<usize>>();
+        assert_eq!(lens, [1024, 256]);
+
+        // 4096 / 8^2 = 64 is the last window which is long enough
+        assert_eq!(build(10, 8).resolutions.len(), 2);
+    }
+
+    #[test]
+    fn magnitudes_match_the_whole_window() {
+        let mut planner = RealFftPlanner::new();
+        let mut multi_resolution = build(3, 4);
+
+        let samples: Box<[f32]> = (0..WINDOW_LEN)
+            .map(|idx| (TAU * 3_000. * idx as f32 / SAMPLE_RATE as f32).sin())
+            .collect();
+        multi_resolution.process(&[samples], &mut planner);
+
+        for (resolution_idx, layout) in multi_resolution.layouts(SAMPLE_RATE).enumerate() {
+            let bin = layout.bin_of(3_000.).round() as usize;
+            let magnitude = multi_resolution.fft_out(resolution_idx, 0)[bin].norm();
+
+            // the peak of a sine in a fft with a hann window over the whole window
+            let expected = WINDOW_LEN as f32 / 4.;
+            assert!(
+                (magnitude - expected).abs() < expected * 0.3,
+                "{}: {}",
+                resolution_idx,
+                magnitude
+            );
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::WindowFunction;
+use std::f64::consts::PI;
+
+impl WindowFunction {
+    /// Returns the coefficients of the window for the given amount of samples.
+    pub(crate) fn coefficients(&self, len: usize) -> Box<[f32]> {
+        if len < 2 {
+            return vec![1.; len].into_boxed_slice();
+        }
+
+        let hann = apodize::hanning_iter(len)
+            .map(|val| val as f32)
+            .collect::<Box<[f32]>>();
+
+        let mut window = match *self {
+            Self::Hann => return hann,
+            Self::BlackmanHarris => apodize::cosine_iter(0.35875, 0.48829, 0.14128, 0.01168, len)
+                .map(|val| val as f32)
+                .collect::<Box<[f32]>>(),
+            Self::FlatTop => (0..len)
+                .map(|idx| {
+                    let x = 2. * PI * idx as f64 / (len - 1) as f64;
+
+                    (0.21557895 - 0.41663158 * x.cos() + 0.277263158 * (2. * x).cos()
+                        - 0.083578947 * (3. * x).cos()
+                        + 0.006947368 * (4. * x).cos()) as f32
+                })
+                .collect(),
+            Self::Kaiser { beta } => {
+                let beta = beta as f64;
+                let denominator = bessel_i0(beta);
+
+                (0..len)
+                    .map(|idx| {
+                        let x = 2. * idx as f64 / (len - 1) as f64 - 1.;
+                        (bessel_i0(beta * (1. - x * x).sqrt()) / denominator) as f32
+                    })
+                    .collect()
+            }
+        };
+
+        // keep the magnitudes comparable to the hann window
+        let factor = hann.iter().sum::<f32>() / window.iter().sum::<f32>();
+        window.iter_mut().for_each(|val| *val *= factor);
+
+        window
+    }
+}
+
+/// The modified bessel function of the first kind of order zero.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.;
+    let mut term = 1.;
+    let half_x = x / 2.;
+
+    for k in 1..50 {
+        term *= (half_x / k as f64).powi(2);
+        sum += term;
+
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEN: usize = 1024;
+
+    #[test]
+    fn same_coherent_gain_as_hann() {
+        let hann_gain = WindowFunction::Hann.coefficients(LEN).iter().sum::<f32>();
+
+        for window in [
+            WindowFunction::BlackmanHarris,
+            WindowFunction::FlatTop,
+            WindowFunction::Kaiser { beta: 8.6 },
+        ] {
+            let gain = window.coefficients(LEN).iter().sum::<f32>();
+            assert!((gain - hann_gain).abs() / hann_gain < 1e-4, "{:?}", window);
+        }
+    }
+
+    #[test]
+    fn kaiser_without_beta_is_rectangular() {
+        let window = WindowFunction::Kaiser { beta: 0. }.coefficients(LEN);
+        let first = window[0];
+
+        assert!(window.iter().all(|&val| (val - first).abs() < 1e-6));
+    }
+
+    #[test]
+    fn windows_are_symmetric() {
+        for window in [
+            WindowFunction::Hann,
+            WindowFunction::BlackmanHarris,
+            WindowFunction::FlatTop,
+            WindowFunction::Kaiser { beta: 5. },
+        ] {
+            let coefficients = window.coefficients(LEN);
+
+            for idx in 0..LEN / 2 {
+                let diff = coefficients[idx] - coefficients[LEN - 1 - idx];
+                assert!(diff.abs() < 1e-5, "{:?}", window);
+            }
+        }
+    }
+
+    #[test]
+    fn bessel() {
+        assert_eq!(bessel_i0(0.), 1.);
+        assert!((bessel_i0(1.) - 1.2660658777520082).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+/// The config options for [crate::SilenceDetector].
+#[derive(Debug, Clone)]
+pub struct SilenceDetectorConfig {
+    /// The level (in dBFS) which the samples need to exceed to count as audible.
+    pub threshold: f32,
+
+    /// How long the audio needs to stay below [SilenceDetectorConfig::threshold] before it counts as silent.
+    pub hold_time: Duration,
+}
+
+impl Default for SilenceDetectorConfig {
+    fn default() -> Self {
+        Self {
+            threshold: -60.,
+            hold_time: Duration::from_secs(3),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+mod config;
+
+use crate::{fetcher::Fetcher, SampleProcessor};
+use std::time::Duration;
+
+pub use config::*;
+
+/// Detects whether the audio of the [SampleProcessor] has been silent for a while.
+///
+/// Useful to stop rendering (or to fade out) while nothing is playing.
+/// The time is measured by the amount of processed samples, so the detector only notices the silence
+/// if the fetcher keeps pushing (silent) samples.
+///
+/// [SilenceDetector::process_samples] should be called after each call of [SampleProcessor::process_next_samples].
+///
+/// # Example
+/// ```
+/// use vibe_audio::{SampleProcessor, SilenceDetector, SilenceDetectorConfig, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut silence_detector = SilenceDetector::new(&sample_processor, SilenceDetectorConfig::default());
+///
+/// sample_processor.process_next_samples();
+/// silence_detector.process_samples(&sample_processor);
+///
+/// // the audio needs to be silent for the hold time first
+/// assert!(!silence_detector.is_silent());
+/// ```
+pub struct SilenceDetector {
+    // the threshold as linear amplitude
+    threshold: f32,
+    hold_time: Duration,
+    sample_rate: f64,
+
+    // the amount of frames since the latest audible sample
+    silent_frames: u64,
+}
+
+impl SilenceDetector {
+    /// Creates a new instance. The audio counts as audible until the hold time has passed.
+    pub fn new<F: Fetcher>(processor: &SampleProcessor<F>, config: SilenceDetectorConfig) -> Self {
+        Self {
+            threshold: 10f32.powf(config.threshold / 20.),
+            hold_time: config.hold_time,
+            sample_rate: processor.sample_rate() as f64,
+
+            silent_frames: 0,
+        }
+    }
+
+    /// Checks the samples which the fetcher pushed since the previous processing.
+    ///
+    /// Returns `true` if the audio has been silent for at least the hold time.
+    pub fn process_samples<F: Fetcher>(&mut self, processor: &SampleProcessor<F>) -> bool {
+        let is_audible = processor
+            .with_new_samples(|samples| samples.iter().any(|sample| sample.abs() > self.threshold));
+
+        if is_audible {
+            self.silent_frames = 0;
+        } else {
+            self.silent_frames += processor.amount_new_frames() as u64;
+        }
+
+        self.is_silent()
+    }
+
+    /// Returns `true` if the audio has been silent for at least the hold time.
+    pub fn is_silent(&self) -> bool {
+        self.silent_for() >= self.hold_time
+    }
+
+    /// Returns how long the audio has been silent.
+    pub fn silent_for(&self) -> Duration {
+        Duration::from_secs_f64(self.silent_frames as f64 / self.sample_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fetcher::{MixFetcher, MixFetcherDescriptor, MixSource, Signal, SignalFetcher},
+        test_util::signal_fetcher,
+    };
+
+    const SAMPLE_RATE: u32 = 48_000;
+    // 100 frames per second
+    const FRAMES_PER_STEP: usize = 480;
+
+    const CONFIG: SilenceDetectorConfig = SilenceDetectorConfig {
+        threshold: -60.,
+        hold_time: Duration::from_millis(500),
+    };
+
+    fn signal(signal: Signal) -> SignalFetcher {
+        signal_fetcher(vec![signal], SAMPLE_RATE, 1, FRAMES_PER_STEP)
+    }
+
+    fn sine(amplitude: f32) -> SignalFetcher {
+        signal(Signal::Sine {
+            frequency: 1_000.,
+            amplitude,
+        })
+    }
+
+    /// Returns the results of the detector for each step.
+    fn detect<F: Fetcher>(processor: &mut SampleProcessor<F>, amount_steps: usize) -> Vec<bool> {
+        let mut detector = SilenceDetector::new(processor, CONFIG);
+
+        (0..amount_steps)
+            .map(|_| {
+                processor.process_next_samples();
+                detector.process_samples(processor)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn silence_after_the_hold_time() {
+        let mut processor = SampleProcessor::new(signal(Signal::Silence));
+        let results = detect(&mut processor, 100);
+
+        // 500ms => 50 steps
+        assert!(results[..49].iter().all(|is_silent| !is_silent));
+        assert!(results[49..].iter().all(|is_silent| *is_silent));
+    }
+
+    #[test]
+    fn audio_is_never_silent() {
+        let mut processor = SampleProcessor::new(sine(0.1));
+
+        assert!(detect(&mut processor, 100)
+            .iter()
+            .all(|is_silent| !is_silent));
+    }
+
+    #[test]
+    fn audio_below_the_threshold_is_silent() {
+        // -80 dBFS
+        let mut processor = SampleProcessor::new(sine(1e-4));
+
+        assert!(*detect(&mut processor, 100).last().unwrap());
+    }
+
+    #[test]
+    fn audio_resets_the_silence() {
+        let mut processor = SampleProcessor::new(
+            MixFetcher::new(MixFetcherDescriptor {
+                sources: vec![MixSource {
+                    fetcher: Box::new(sine(0.1)),
+                    gain: 0.,
+                }],
+                sample_rate: None,
+            })
+            .unwrap(),
+        );
+        let mut detector = SilenceDetector::new(&processor, CONFIG);
+
+        for _ in 0..100 {
+            processor.process_next_samples();
+            detector.process_samples(&processor);
+        }
+        assert!(detector.is_silent());
+
+        processor.fetcher_mut().set_gain(0, 1.);
+        processor.process_next_samples();
+        detector.process_samples(&processor);
+
+        assert!(!detector.is_silent());
+        assert_eq!(detector.silent_for(), Duration::ZERO);
+    }
+}
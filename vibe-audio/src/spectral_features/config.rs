@@ -0,0 +1,23 @@
+use crate::{MAX_HUMAN_FREQUENCY, MIN_HUMAN_FREQUENCY};
+use std::{num::NonZero, ops::Range};
+
+/// The config options for [crate::SpectralFeatures].
+#[derive(Debug, Clone)]
+pub struct SpectralFeaturesConfig {
+    /// The frequency range (in Hz) of the spectrum which is described.
+    pub freq_range: Range<NonZero<u16>>,
+
+    /// The ratio of the energy which lies below [crate::ChannelFeatures::rolloff].
+    /// Must be within `(0, 1]`.
+    pub rolloff: f32,
+}
+
+impl Default for SpectralFeaturesConfig {
+    fn default() -> Self {
+        Self {
+            freq_range: NonZero::new(MIN_HUMAN_FREQUENCY).unwrap()
+                ..NonZero::new(MAX_HUMAN_FREQUENCY).unwrap(),
+            rolloff: 0.85,
+        }
+    }
+}
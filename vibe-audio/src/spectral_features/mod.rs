@@ -0,0 +1,339 @@
+mod config;
+
+use crate::{fetcher::Fetcher, SampleProcessor};
+use std::ops::Range;
+
+pub use config::*;
+
+/// The (normalized) energy below which a channel counts as silent.
+const MIN_ENERGY: f32 = 1e-10;
+
+/// The features which describe the shape of the spectrum of a channel.
+///
+/// All values are `0` while the channel is silent.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChannelFeatures {
+    /// The center of mass of the spectrum (in Hz).
+    /// Higher values stand for a "brighter" sound.
+    pub centroid: f32,
+
+    /// The standard deviation of the spectrum around [ChannelFeatures::centroid] (in Hz),
+    /// also known as bandwidth.
+    pub spread: f32,
+
+    /// The ratio between the geometric and the arithmetic mean of the power spectrum, within `[0, 1]`.
+    /// Close to `0` for clear tones and close to `1` for noise.
+    pub flatness: f32,
+
+    /// The frequency (in Hz) below which [SpectralFeaturesConfig::rolloff] of the energy lies.
+    pub rolloff: f32,
+
+    /// The average increase of the magnitudes compared to the previous frame.
+    /// Peaks on sudden changes of the sound.
+    pub flux: f32,
+
+    /// The ratio of consecutive samples which have different signs, within `[0, 1]`.
+    /// A sine tone with the frequency `f` has a rate of about `2 * f / sample_rate`.
+    pub zero_crossing_rate: f32,
+}
+
+/// Computes cheap descriptors of the spectrum of each channel of the [SampleProcessor],
+/// for example how bright or how noisy the audio is.
+///
+/// # Example
+/// ```
+/// use vibe_audio::{SampleProcessor, SpectralFeatures, SpectralFeaturesConfig, ChannelFeatures, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut spectral_features = SpectralFeatures::new(&sample_processor, SpectralFeaturesConfig::default());
+///
+/// sample_processor.process_next_samples();
+/// let features = spectral_features.process_features(&sample_processor);
+///
+/// // one result for each channel
+/// assert_eq!(features.len(), 2);
+/// // the dummy fetcher is silent
+/// assert_eq!(features[0], ChannelFeatures::default());
+/// ```
+pub struct SpectralFeatures {
+    // the fft bins which are described
+    bins: Range<usize>,
+    // the frequency of each bin of `bins`
+    frequencies: Box<[f32]>,
+
+    // the magnitudes of `bins` of each channel within the previous frame
+    prev_magnitudes: Box<[Box<[f32]>]>,
+    // reused buffer for the magnitudes of a channel
+    magnitudes: Box<[f32]>,
+
+    features: Box<[ChannelFeatures]>,
+
+    config: SpectralFeaturesConfig,
+}
+
+impl SpectralFeatures {
+    /// Creates a new instance.
+    ///
+    /// # Panics
+    /// Panics if [SpectralFeaturesConfig::rolloff] isn't within `(0, 1]`.
+    pub fn new<F: Fetcher>(processor: &SampleProcessor<F>, config: SpectralFeaturesConfig) -> Self {
+        assert!(
+            config.rolloff > 0. && config.rolloff <= 1.,
+            "The rolloff of the spectral features ({}) must be within (0, 1].",
+            config.rolloff
+        );
+
+        let fft_size = processor.fft_size();
+        let fft_out_size = fft_size / 2 + 1;
+        let freq_resolution = processor.sample_rate() as f32 / fft_size as f32;
+
+        let start = ((config.freq_range.start.get() as f32 / freq_resolution) as usize)
+            .clamp(1, fft_out_size - 1);
+        let end = ((config.freq_range.end.get() as f32 / freq_resolution).ceil() as usize)
+            .clamp(start + 1, fft_out_size);
+        let bins = start..end;
+
+        let frequencies = bins
+            .clone()
+            .map(|bin| bin as f32 * freq_resolution)
+            .collect();
+        let magnitudes = vec![0f32; bins.len()].into_boxed_slice();
+
+        let amount_channels = processor.fft_out().len();
+
+        Self {
+            bins,
+            frequencies,
+
+            prev_magnitudes: vec![magnitudes.clone(); amount_channels].into_boxed_slice(),
+            magnitudes,
+
+            features: vec![ChannelFeatures::default(); amount_channels].into_boxed_slice(),
+
+            config,
+        }
+    }
+
+    /// Computes the features of the latest samples of the processor.
+    ///
+    /// Returns the features of each channel.
+    pub fn process_features<F: Fetcher>(
+        &mut self,
+        processor: &SampleProcessor<F>,
+    ) -> &[ChannelFeatures] {
+        for (channel_idx, features) in self.features.iter_mut().enumerate() {
+            for (magnitude, value) in self
+                .magnitudes
+                .iter_mut()
+                .zip(processor.magnitudes(channel_idx, self.bins.clone()))
+            {
+                *magnitude = value;
+            }
+
+            let prev_magnitudes = &mut self.prev_magnitudes[channel_idx];
+            let flux = self
+                .magnitudes
+                .iter()
+                .zip(prev_magnitudes.iter())
+                .map(|(magnitude, prev_magnitude)| (magnitude - prev_magnitude).max(0.))
+                .sum::<f32>()
+                / self.magnitudes.len() as f32;
+            prev_magnitudes.copy_from_slice(&self.magnitudes);
+
+            *features = match describe(&self.magnitudes, &self.frequencies, self.config.rolloff) {
+                Some(shape) => ChannelFeatures {
+                    flux,
+                    zero_crossing_rate: zero_crossing_rate(processor.samples(channel_idx)),
+                    ..shape
+                },
+                None => ChannelFeatures::default(),
+            };
+        }
+
+        &self.features
+    }
+
+    /// Returns the results of the latest call of [SpectralFeatures::process_features].
+    pub fn features(&self) -> &[ChannelFeatures] {
+        &self.features
+    }
+
+    /// Returns the current config.
+    pub fn config(&self) -> &SpectralFeaturesConfig {
+        &self.config
+    }
+}
+
+/// Computes the features which only depend on the current magnitudes.
+///
+/// Returns `None` if the channel is silent.
+fn describe(magnitudes: &[f32], frequencies: &[f32], rolloff: f32) -> Option<ChannelFeatures> {
+    let energy = magnitudes
+        .iter()
+        .map(|magnitude| magnitude * magnitude)
+        .sum::<f32>();
+    if energy < MIN_ENERGY {
+        return None;
+    }
+
+    let magnitude_sum = magnitudes.iter().sum::<f32>();
+    let centroid = magnitudes
+        .iter()
+        .zip(frequencies)
+        .map(|(magnitude, frequency)| magnitude * frequency)
+        .sum::<f32>()
+        / magnitude_sum;
+
+    let spread = (magnitudes
+        .iter()
+        .zip(frequencies)
+        .map(|(magnitude, frequency)| magnitude * (frequency - centroid).powi(2))
+        .sum::<f32>()
+        / magnitude_sum)
+        .sqrt();
+
+    let mean_log_power = magnitudes
+        .iter()
+        .map(|magnitude| (magnitude * magnitude).max(f32::MIN_POSITIVE).ln())
+        .sum::<f32>()
+        / magnitudes.len() as f32;
+    let flatness = (mean_log_power.exp() / (energy / magnitudes.len() as f32)).clamp(0., 1.);
+
+    let mut rolloff_energy = 0.;
+    let rolloff = magnitudes
+        .iter()
+        .zip(frequencies)
+        .find(|(magnitude, _)| {
+            rolloff_energy += *magnitude * *magnitude;
+            rolloff_energy >= rolloff * energy
+        })
+        .map(|(_, frequency)| *frequency)
+        .unwrap_or(*frequencies.last().unwrap());
+
+    Some(ChannelFeatures {
+        centroid,
+        spread,
+        flatness,
+        rolloff,
+        ..Default::default()
+    })
+}
+
+fn zero_crossing_rate(samples: &[f32]) -> f32 {
+    if samples.len() < 2 {
+        return 0.;
+    }
+
+    let crossings = samples
+        .windows(2)
+        .filter(|pair| (pair[0] < 0.) != (pair[1] < 0.))
+        .count();
+
+    crossings as f32 / (samples.len() - 1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fetcher::Signal,
+        test_util::{processor, SAMPLE_RATE},
+    };
+
+    /// Returns the features of the first channel after each step.
+    fn describe_signal(signal: Signal, amount_steps: usize) -> Vec<ChannelFeatures> {
+        let mut processor = processor(vec![signal]);
+        let mut spectral_features =
+            SpectralFeatures::new(&processor, SpectralFeaturesConfig::default());
+
+        (0..amount_steps)
+            .map(|_| {
+                processor.process_next_samples();
+                spectral_features.process_features(&processor)[0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn silence() {
+        let steps = describe_signal(Signal::Silence, 5);
+
+        assert!(steps
+            .iter()
+            .all(|features| *features == ChannelFeatures::default()));
+    }
+
+    #[test]
+    fn inaudible_sine_counts_as_silence() {
+        let steps = describe_signal(
+            Signal::Sine {
+                frequency: 1_000.,
+                amplitude: 1e-7,
+            },
+            5,
+        );
+
+        assert!(
+            steps
+                .iter()
+                .all(|features| *features == ChannelFeatures::default()),
+            "{:?}",
+            steps
+        );
+    }
+
+    #[test]
+    fn sine() {
+        let steps = describe_signal(
+            Signal::Sine {
+                frequency: 1_000.,
+                amplitude: 0.5,
+            },
+            10,
+        );
+        let features = steps.last().unwrap();
+
+        assert!((features.centroid - 1_000.).abs() < 50., "{:?}", features);
+        assert!(features.spread < 200., "{:?}", features);
+        assert!(features.flatness < 0.01, "{:?}", features);
+        assert!((features.rolloff - 1_000.).abs() < 50., "{:?}", features);
+        assert!(
+            (features.zero_crossing_rate - 2_000. / SAMPLE_RATE as f32).abs() < 0.005,
+            "{:?}",
+            features
+        );
+    }
+
+    #[test]
+    fn noise_is_flat_and_bright() {
+        let steps = describe_signal(Signal::WhiteNoise { amplitude: 0.5 }, 10);
+        let features = steps.last().unwrap();
+
+        assert!(features.flatness > 0.4, "{:?}", features);
+        assert!(
+            (8_000. ..12_000.).contains(&features.centroid),
+            "{:?}",
+            features
+        );
+        assert!(features.rolloff > 15_000., "{:?}", features);
+        assert!(features.zero_crossing_rate > 0.3, "{:?}", features);
+    }
+
+    #[test]
+    fn flux_peaks_when_the_sound_starts() {
+        let steps = describe_signal(
+            Signal::Sine {
+                frequency: 440.,
+                amplitude: 0.5,
+            },
+            10,
+        );
+
+        assert!(steps[0].flux > 0.);
+        assert!(
+            steps.last().unwrap().flux < steps[0].flux * 0.01,
+            "{:?}",
+            steps
+        );
+    }
+}
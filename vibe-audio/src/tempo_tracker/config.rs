@@ -0,0 +1,31 @@
+use crate::BeatDetectorConfig;
+use std::{ops::Range, time::Duration};
+
+/// The config options for [crate::TempoTracker].
+#[derive(Debug, Clone)]
+pub struct TempoTrackerConfig {
+    /// The range of the tempo (in beats per minute) which the tracker searches in.
+    pub bpm_range: Range<f32>,
+
+    /// The duration of the audio which is considered to estimate the tempo.
+    /// Longer windows are more stable but adjust slower to tempo changes.
+    pub window: Duration,
+
+    /// The config of the beat detector whose onsets are used to estimate the tempo.
+    pub beat_detector: BeatDetectorConfig,
+}
+
+impl Default for TempoTrackerConfig {
+    fn default() -> Self {
+        Self {
+            bpm_range: default_bpm_range(),
+            window: Duration::from_secs(8),
+            beat_detector: BeatDetectorConfig::default(),
+        }
+    }
+}
+
+/// Returns the default value for [TempoTrackerConfig::bpm_range].
+pub fn default_bpm_range() -> Range<f32> {
+    60. ..180.
+}
@@ -0,0 +1,475 @@
+mod config;
+
+use crate::{fetcher::Fetcher, BeatDetector, SampleProcessor};
+use std::{collections::VecDeque, ops::Range, time::Duration};
+
+pub use config::*;
+
+/// The amount of values per second of the onset envelope.
+const ENVELOPE_RATE: f64 = 100.;
+
+/// How often the tempo is estimated again.
+const ESTIMATE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Tempos around this value (in BPM) are preferred if the onsets fit to multiple tempos
+/// (for example 60, 120 and 240 BPM).
+const PREFERRED_BPM: f64 = 120.;
+/// The width (in octaves) of the preference around [PREFERRED_BPM].
+const PREFERENCE_WIDTH: f64 = 1.;
+
+/// How much a new estimation which is close to the current tempo changes the tempo.
+const TEMPO_SMOOTHING: f64 = 0.3;
+/// The relative difference of a new estimation to the current tempo where the tracker
+/// switches to the new tempo immediately.
+const TEMPO_JUMP: f64 = 0.1;
+
+/// The maximal change of the speed of the beat clock (relative to the tempo) while it gets
+/// shifted into phase with the beats.
+const MAX_PHASE_CORRECTION: f64 = 0.5;
+
+/// Errors which can occur while creating a [TempoTracker].
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum TempoTrackerError {
+    #[error("The BPM range of the tempo tracker ({0:?}) must be positive and not empty.")]
+    InvalidBpmRange(Range<f32>),
+
+    #[error("The window of the tempo tracker ({window:?}) must be at least {min_window:?} long to detect tempos down to {min_bpm} BPM.")]
+    WindowTooShort {
+        window: Duration,
+        min_window: Duration,
+        min_bpm: f32,
+    },
+}
+
+/// Estimates the tempo (in BPM) and the beat phase of the samples of the [SampleProcessor].
+///
+/// The onset envelope of a [BeatDetector] is autocorrelated to find the most likely beat period
+/// and the beats are aligned with the onsets to find the phase.
+/// Both are estimated again every half second and tracked in between.
+///
+/// The tracker measures the time with the amount of samples which the fetcher pushes,
+/// so it doesn't matter how often [TempoTracker::process_tempo] gets called.
+///
+/// # Example
+/// ```
+/// use vibe_audio::{SampleProcessor, TempoTracker, TempoTrackerConfig, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let mut tempo_tracker = TempoTracker::new(&sample_processor, TempoTrackerConfig::default()).unwrap();
+///
+/// sample_processor.process_next_samples();
+/// tempo_tracker.process_tempo(&sample_processor);
+///
+/// // the dummy fetcher doesn't have a tempo...
+/// assert_eq!(tempo_tracker.bpm(), None);
+/// // ... but the phase can still be used
+/// assert!((0. ..1.).contains(&tempo_tracker.beat_phase()));
+/// ```
+pub struct TempoTracker {
+    beat_detector: BeatDetector,
+
+    // The summed up spectral flux of all bands with `ENVELOPE_RATE` values per second
+    envelope: VecDeque<f32>,
+    envelope_capacity: usize,
+    amount_envelope_values: u64,
+
+    // the duration of the processed audio (in seconds)
+    time: f64,
+    time_since_estimate: f64,
+    sample_rate: f64,
+
+    bpm: Option<f64>,
+    confidence: f32,
+
+    // the amount of beats which passed
+    beat_clock: f64,
+    // the part of a beat which still needs to be added to `beat_clock` to be in phase with the beats
+    pending_phase_correction: f64,
+
+    config: TempoTrackerConfig,
+}
+
+impl TempoTracker {
+    /// Creates a new instance.
+    ///
+    /// Fails if [TempoTrackerConfig::bpm_range] is empty or doesn't start above zero or
+    /// if [TempoTrackerConfig::window] can't hold two beats of the slowest tempo.
+    pub fn new<F: Fetcher>(
+        processor: &SampleProcessor<F>,
+        config: TempoTrackerConfig,
+    ) -> Result<Self, TempoTrackerError> {
+        if !(0. < config.bpm_range.start && config.bpm_range.start < config.bpm_range.end) {
+            return Err(TempoTrackerError::InvalidBpmRange(config.bpm_range));
+        }
+
+        let envelope_capacity = (config.window.as_secs_f64() * ENVELOPE_RATE).ceil() as usize;
+        // the autocorrelation needs at least two periods of the slowest tempo
+        let min_envelope_len = 2 * max_lag(&config.bpm_range);
+        if envelope_capacity < min_envelope_len {
+            return Err(TempoTrackerError::WindowTooShort {
+                window: config.window,
+                min_window: Duration::from_secs_f64(min_envelope_len as f64 / ENVELOPE_RATE),
+                min_bpm: config.bpm_range.start,
+            });
+        }
+
+        let beat_detector = BeatDetector::new(processor, config.beat_detector.clone());
+
+        Ok(Self {
+            beat_detector,
+
+            envelope: VecDeque::with_capacity(envelope_capacity),
+            envelope_capacity,
+            amount_envelope_values: 0,
+
+            time: 0.,
+            time_since_estimate: 0.,
+            sample_rate: processor.sample_rate() as f64,
+
+            bpm: None,
+            confidence: 0.,
+
+            beat_clock: 0.,
+            pending_phase_correction: 0.,
+
+            config,
+        })
+    }
+
+    /// Processes the latest samples of the processor.
+    ///
+    /// Returns the current tempo (in BPM) if it's known.
+    pub fn process_tempo<F: Fetcher>(&mut self, processor: &SampleProcessor<F>) -> Option<f32> {
+        let flux = self
+            .beat_detector
+            .process_beats(processor)
+            .iter()
+            .map(|beat| beat.flux)
+            .sum::<f32>();
+
+        let elapsed = processor.amount_new_frames() as f64 / self.sample_rate;
+        self.time += elapsed;
+
+        // the flux belongs to all of the new audio
+        let amount_envelope_values = (self.time * ENVELOPE_RATE) as u64;
+        while self.amount_envelope_values < amount_envelope_values {
+            if self.envelope.len() >= self.envelope_capacity {
+                self.envelope.pop_front();
+            }
+            self.envelope.push_back(flux);
+            self.amount_envelope_values += 1;
+        }
+
+        if let Some(bpm) = self.bpm {
+            let advance = elapsed * bpm / 60.;
+            let max_correction = advance * MAX_PHASE_CORRECTION;
+            let correction = self
+                .pending_phase_correction
+                .clamp(-max_correction, max_correction);
+
+            self.beat_clock += advance + correction;
+            self.pending_phase_correction -= correction;
+        }
+
+        self.time_since_estimate += elapsed;
+        if self.time_since_estimate >= ESTIMATE_INTERVAL.as_secs_f64() {
+            self.time_since_estimate = 0.;
+            self.estimate();
+        }
+
+        self.bpm()
+    }
+
+    /// Returns the current tempo (in BPM) if it's known.
+    pub fn bpm(&self) -> Option<f32> {
+        self.bpm.map(|bpm| bpm as f32)
+    }
+
+    /// Returns how clearly the onsets of the current window follow the tempo.
+    ///
+    /// `0`: No periodic onsets (for example during silence), `1`: Perfectly periodic onsets.
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    /// Returns the position within the current beat within `[0, 1)`.
+    /// `0` is the moment of the beat.
+    ///
+    /// Stays at `0` as long as the tempo isn't known.
+    pub fn beat_phase(&self) -> f32 {
+        (self.beat_clock.fract() as f32).min(1. - f32::EPSILON)
+    }
+
+    /// Returns the amount of beats which passed since the tempo is known.
+    ///
+    /// The value only increases, so it can be used as a tempo-locked clock for animations.
+    pub fn beat_clock(&self) -> f64 {
+        self.beat_clock
+    }
+
+    /// Returns the beat detector whose onsets are used by the tracker.
+    pub fn beat_detector(&self) -> &BeatDetector {
+        &self.beat_detector
+    }
+
+    /// Returns the current config of the tempo tracker.
+    pub fn config(&self) -> &TempoTrackerConfig {
+        &self.config
+    }
+
+    fn estimate(&mut self) {
+        let Some((period, confidence)) = self.estimate_period() else {
+            self.confidence = 0.;
+            return;
+        };
+        self.confidence = confidence;
+
+        let estimated_bpm = 60. * ENVELOPE_RATE / period;
+        let bpm = match self.bpm {
+            Some(bpm) if ((estimated_bpm - bpm) / bpm).abs() < TEMPO_JUMP => {
+                bpm + (estimated_bpm - bpm) * TEMPO_SMOOTHING
+            }
+            _ => estimated_bpm,
+        };
+
+        let target_phase = self.estimate_phase(60. * ENVELOPE_RATE / bpm);
+        if self.bpm.is_none() {
+            self.beat_clock = target_phase;
+        } else {
+            let phase_error = target_phase - self.beat_clock.fract();
+            // take the shorter way to the target phase
+            self.pending_phase_correction = phase_error - phase_error.round();
+        }
+
+        self.bpm = Some(bpm);
+    }
+
+    /// Returns the most likely beat period (in envelope values) and the confidence of it.
+    fn estimate_period(&self) -> Option<(f64, f32)> {
+        let min_lag = ((60. * ENVELOPE_RATE / self.config.bpm_range.end as f64) as usize).max(1);
+        let max_lag = max_lag(&self.config.bpm_range);
+
+        let amount_values = self.envelope.len();
+        if amount_values < 2 * max_lag {
+            return None;
+        }
+
+        let mean = self.envelope.iter().sum::<f32>() / amount_values as f32;
+        let envelope = self
+            .envelope
+            .iter()
+            .map(|value| value - mean)
+            .collect::<Vec<f32>>();
+
+        let autocorrelation = |lag: usize| {
+            envelope
+                .iter()
+                .zip(envelope[lag..].iter())
+                .map(|(a, b)| a * b)
+                .sum::<f32>()
+                / (amount_values - lag) as f32
+        };
+
+        let energy = autocorrelation(0);
+        if energy <= f32::EPSILON {
+            return None;
+        }
+
+        // includes the neighbours of the lag range for the refinement
+        let correlations = (min_lag - 1..=max_lag + 1)
+            .map(autocorrelation)
+            .collect::<Vec<f32>>();
+        let correlation = |lag: usize| correlations[lag + 1 - min_lag];
+
+        let preference = |lag: usize| {
+            let bpm = 60. * ENVELOPE_RATE / lag as f64;
+            let octaves = (bpm / PREFERRED_BPM).log2() / PREFERENCE_WIDTH;
+            (-0.5 * octaves * octaves).exp() as f32
+        };
+
+        let best_lag = (min_lag..=max_lag)
+            .max_by(|&a, &b| {
+                (correlation(a) * preference(a)).total_cmp(&(correlation(b) * preference(b)))
+            })
+            .unwrap();
+
+        let peak = correlation(best_lag);
+        if peak <= 0. {
+            return None;
+        }
+
+        // parabolic interpolation to get a more precise period than a whole envelope value
+        let (left, right) = (correlation(best_lag - 1), correlation(best_lag + 1));
+        let curvature = left - 2. * peak + right;
+        let offset = if curvature < 0. {
+            (0.5 * (left - right) / curvature).clamp(-0.5, 0.5)
+        } else {
+            0.
+        };
+
+        Some((
+            best_lag as f64 + offset as f64,
+            (peak / energy).clamp(0., 1.),
+        ))
+    }
+
+    /// Returns the phase of the latest envelope value for the given beat period (in envelope values).
+    fn estimate_phase(&self, period: f64) -> f64 {
+        let last_idx = self.envelope.len() - 1;
+
+        // the amount of envelope values since the last beat
+        let best_offset = (0..period.ceil() as usize)
+            .max_by(|&a, &b| {
+                let score = |offset: usize| {
+                    (0..)
+                        .map(|beat| (offset as f64 + beat as f64 * period).round() as usize)
+                        .take_while(|&distance| distance <= last_idx)
+                        .map(|distance| self.envelope[last_idx - distance])
+                        .sum::<f32>()
+                };
+
+                // prefer the most recent beat if the scores are equal
+                score(a).total_cmp(&score(b)).then(b.cmp(&a))
+            })
+            .unwrap();
+
+        (best_offset as f64 / period).fract()
+    }
+}
+
+/// Returns the beat period (in envelope values) of the slowest tempo of the range.
+fn max_lag(bpm_range: &Range<f32>) -> usize {
+    (60. * ENVELOPE_RATE / bpm_range.start as f64).ceil() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fetcher::{Signal, SignalFetcher},
+        test_util::{processor, FRAMES_PER_STEP, SAMPLE_RATE},
+    };
+
+    fn run(
+        processor: &mut SampleProcessor<SignalFetcher>,
+        tracker: &mut TempoTracker,
+        duration: Duration,
+    ) {
+        let amount_steps =
+            (duration.as_secs_f64() * SAMPLE_RATE as f64 / FRAMES_PER_STEP as f64) as usize;
+
+        for _ in 0..amount_steps {
+            processor.process_next_samples();
+            tracker.process_tempo(processor);
+        }
+    }
+
+    #[test]
+    fn rejects_windows_which_are_too_short() {
+        let processor = processor(vec![Signal::Silence]);
+
+        // two beats at 60 BPM need two seconds
+        let result = TempoTracker::new(
+            &processor,
+            TempoTrackerConfig {
+                window: Duration::from_millis(1500),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            result.err(),
+            Some(TempoTrackerError::WindowTooShort {
+                window: Duration::from_millis(1500),
+                min_window: Duration::from_secs(2),
+                min_bpm: 60.,
+            })
+        );
+
+        assert!(TempoTracker::new(
+            &processor,
+            TempoTrackerConfig {
+                window: Duration::from_secs(2),
+                ..Default::default()
+            },
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_bpm_ranges() {
+        let processor = processor(vec![Signal::Silence]);
+
+        let result = TempoTracker::new(
+            &processor,
+            TempoTrackerConfig {
+                bpm_range: 120. ..120.,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            result.err(),
+            Some(TempoTrackerError::InvalidBpmRange(120. ..120.))
+        );
+    }
+
+    #[test]
+    fn silence_has_no_tempo() {
+        let mut processor = processor(vec![Signal::Silence]);
+        let mut tracker = TempoTracker::new(&processor, TempoTrackerConfig::default()).unwrap();
+
+        run(&mut processor, &mut tracker, Duration::from_secs(5));
+
+        assert_eq!(tracker.bpm(), None);
+        assert_eq!(tracker.confidence(), 0.);
+        assert_eq!(tracker.beat_phase(), 0.);
+    }
+
+    #[test]
+    fn estimates_the_tempo_of_impulses() {
+        for expected_bpm in [90f32, 120., 150.] {
+            let mut processor = processor(vec![Signal::Impulses {
+                bpm: expected_bpm,
+                amplitude: 1.,
+            }]);
+            let mut tracker = TempoTracker::new(&processor, TempoTrackerConfig::default()).unwrap();
+
+            run(&mut processor, &mut tracker, Duration::from_secs(10));
+
+            let bpm = tracker.bpm().unwrap();
+            assert!(
+                (bpm - expected_bpm).abs() < 2.,
+                "Expected {} BPM, got {} BPM",
+                expected_bpm,
+                bpm
+            );
+            assert!(tracker.confidence() > 0.5);
+        }
+    }
+
+    #[test]
+    fn beat_phase_is_in_sync_with_the_impulses() {
+        // one beat every 30 steps
+        let mut processor = processor(vec![Signal::Impulses {
+            bpm: 120.,
+            amplitude: 1.,
+        }]);
+        let mut tracker = TempoTracker::new(&processor, TempoTrackerConfig::default()).unwrap();
+
+        run(&mut processor, &mut tracker, Duration::from_secs(10));
+        let prev_clock = tracker.beat_clock();
+
+        for step in 0..60 {
+            processor.process_next_samples();
+            tracker.process_tempo(&processor);
+
+            if step % 30 == 0 {
+                // right after an impulse
+                let phase = tracker.beat_phase();
+                let distance = phase.min(1. - phase);
+                assert!(distance < 0.1, "Phase of the beat: {}", phase);
+            }
+        }
+
+        assert!(tracker.beat_clock() > prev_clock);
+    }
+}
@@ -0,0 +1,60 @@
+//! Fixtures which are shared by the tests of the crate.
+use crate::{
+    fetcher::{Pacing, Signal, SignalFetcher, SignalFetcherDescriptor},
+    SampleProcessor, SampleProcessorConfig,
+};
+use cpal::SampleRate;
+use std::{
+    num::NonZero,
+    time::{Duration, Instant},
+};
+
+pub const SAMPLE_RATE: SampleRate = 44_100;
+// 60 frames per second
+pub const FRAMES_PER_STEP: usize = 735;
+
+/// Returns a fetcher which generates the given signals with `frames_per_step` frames per step.
+pub fn signal_fetcher(
+    signals: Vec<Signal>,
+    sample_rate: SampleRate,
+    amount_channels: u16,
+    frames_per_step: usize,
+) -> SignalFetcher {
+    SignalFetcher::new(&SignalFetcherDescriptor {
+        signals,
+        sample_rate,
+        amount_channels,
+        pacing: Pacing::Offline {
+            frames_per_step: NonZero::new(frames_per_step).unwrap(),
+        },
+    })
+    .unwrap()
+}
+
+/// Returns a processor of a single channel with the given signals and [FRAMES_PER_STEP] frames per step.
+pub fn processor(signals: Vec<Signal>) -> SampleProcessor<SignalFetcher> {
+    processor_with_config(signals, SampleProcessorConfig::default())
+}
+
+/// Same as [processor] but with the given config.
+pub fn processor_with_config(
+    signals: Vec<Signal>,
+    config: SampleProcessorConfig,
+) -> SampleProcessor<SignalFetcher> {
+    SampleProcessor::with_config(
+        signal_fetcher(signals, SAMPLE_RATE, 1, FRAMES_PER_STEP),
+        config,
+    )
+}
+
+/// Waits (up to 5 seconds) until the condition is met. Panics otherwise.
+pub fn wait_until(condition: impl Fn() -> bool) {
+    let start = Instant::now();
+    while !condition() {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "Condition wasn't met in time"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
@@ -108,6 +108,7 @@ impl<'a> State<'a> {
                 movement_speed: 0.005,
                 down: vibe_audio::default_down(),
                 up: vibe_audio::default_up(),
+                weighting: vibe_audio::Weighting::None,
                 init_norm_factor: vibe_audio::InitNormFactor::default(),
                 seed: None,
             })) as Box<dyn ComponentAudio<_>>),
@@ -352,6 +353,7 @@ impl<'a> State<'a> {
                     freq_range: NonZero::new(100).unwrap()..NonZero::new(250).unwrap(),
                     down: vibe_audio::default_down(),
                     up: vibe_audio::default_up(),
+                    weighting: vibe_audio::Weighting::None,
                     init_norm_factor: vibe_audio::InitNormFactor::default(),
                     texture_format: surface_config.format,
 
@@ -26,5 +26,6 @@ pub struct AurodioDescriptor<'a, F: Fetcher> {
     pub up: f32,
     pub down: f32,
 
+    pub weighting: vibe_audio::Weighting,
     pub init_norm_factor: vibe_audio::InitNormFactor,
 }
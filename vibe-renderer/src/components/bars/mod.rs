@@ -9,7 +9,7 @@ use crate::{
 };
 use cgmath::{Deg, Matrix2, Vector2};
 use std::num::NonZero;
-use vibe_audio::{fetcher::Fetcher, BarProcessor, CubicSplineInterpolation, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, BarProcessor, DynamicInterpolation, SampleProcessor};
 use wgpu::{include_wgsl, util::DeviceExt};
 
 // The actual column direction needs to be computed first after we know
@@ -91,7 +91,7 @@ struct RenderCtx {
 
 pub struct Bars {
     amount_bars: NonZero<u16>,
-    bar_processor: BarProcessor<CubicSplineInterpolation>,
+    bar_processor: BarProcessor<DynamicInterpolation>,
 
     // `left` and `right` share the same bind group 0
     bind_group0: wgpu::BindGroup,
@@ -352,7 +352,8 @@ impl<F: Fetcher> ComponentAudio<F> for Bars {
         );
 
         if let Some(right) = &self.right {
-            queue.write_buffer(&right.freq_buffer, 0, bytemuck::cast_slice(&bar_values[1]));
+            let right_values = super::utils::right_channel_values(bar_values);
+            queue.write_buffer(&right.freq_buffer, 0, bytemuck::cast_slice(right_values));
         }
     }
 }
@@ -1,7 +1,7 @@
 mod descriptor;
 
 pub use descriptor::*;
-use vibe_audio::{fetcher::Fetcher, CubicSplineInterpolation, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, DynamicInterpolation, SampleProcessor};
 
 use super::{Component, Mat2x2, Rgba, Vec2f};
 use crate::{components::ComponentAudio, util::SimpleRenderPipelineDescriptor, Renderable};
@@ -22,7 +22,7 @@ struct Data {
 }
 
 pub struct Circle {
-    bar_processor: vibe_audio::BarProcessor<CubicSplineInterpolation>,
+    bar_processor: vibe_audio::BarProcessor<DynamicInterpolation>,
 
     data_buffer: wgpu::Buffer,
     freq_buffer: wgpu::Buffer,
@@ -3,7 +3,7 @@ use crate::{components::ComponentAudio, Renderable, Renderer};
 use pollster::FutureExt;
 use std::borrow::Cow;
 use vibe_audio::{
-    fetcher::Fetcher, BarProcessor, BarProcessorConfig, CubicSplineInterpolation, SampleProcessor,
+    fetcher::Fetcher, BarProcessor, BarProcessorConfig, DynamicInterpolation, SampleProcessor,
 };
 use wgpu::include_wgsl;
 
@@ -27,7 +27,7 @@ struct TextureCtx {
 }
 
 pub struct FragmentCanvas {
-    bar_processor: BarProcessor<CubicSplineInterpolation>,
+    bar_processor: BarProcessor<DynamicInterpolation>,
 
     iresolution: wgpu::Buffer,
     freqs: wgpu::Buffer,
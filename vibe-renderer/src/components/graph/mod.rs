@@ -13,7 +13,7 @@ use crate::{
 use cgmath::{Deg, Matrix2, Vector2};
 use std::num::NonZero;
 use vibe_audio::{
-    fetcher::Fetcher, BarProcessor, BarProcessorConfig, CubicSplineInterpolation, SampleProcessor,
+    fetcher::Fetcher, BarProcessor, BarProcessorConfig, DynamicInterpolation, SampleProcessor,
 };
 use wgpu::{include_wgsl, util::DeviceExt};
 
@@ -78,7 +78,7 @@ struct FragmentParams {
 }
 
 pub struct Graph {
-    bar_processor: vibe_audio::BarProcessor<CubicSplineInterpolation>,
+    bar_processor: vibe_audio::BarProcessor<DynamicInterpolation>,
 
     bind_group0: wgpu::BindGroup,
     vertex_params_buffer: wgpu::Buffer,
@@ -353,7 +353,8 @@ impl<F: Fetcher> ComponentAudio<F> for Graph {
         );
 
         if let Some(right) = &self.right {
-            queue.write_buffer(&right.freqs_buffer, 0, bytemuck::cast_slice(&bar_values[1]));
+            let right_values = super::utils::right_channel_values(bar_values);
+            queue.write_buffer(&right.freqs_buffer, 0, bytemuck::cast_slice(right_values));
         }
     }
 }
@@ -78,6 +78,7 @@ impl LightSources {
                     freq_range: desc.freq_range.clone(),
                     up: desc.up,
                     down: desc.down,
+                    weighting: desc.weighting,
                     init_norm_factor: desc.init_norm_factor,
                     ..Default::default()
                 },
@@ -56,6 +56,7 @@ impl PulseEdges {
                 freq_range: desc.freq_range.clone(),
                 up: desc.up,
                 down: desc.down,
+                weighting: desc.weighting,
                 init_norm_factor: desc.init_norm_factor,
                 ..Default::default()
             },
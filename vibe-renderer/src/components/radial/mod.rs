@@ -5,7 +5,7 @@ pub use descriptor::*;
 use super::{Component, Rgba, Vec2f};
 use crate::{components::ComponentAudio, Renderable};
 use cgmath::{Deg, Matrix2, Rad, Vector2};
-use vibe_audio::{fetcher::Fetcher, BarProcessor, CubicSplineInterpolation, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, BarProcessor, DynamicInterpolation, SampleProcessor};
 use wgpu::{include_wgsl, util::DeviceExt};
 
 /// Entrypoints for the vertex shader
@@ -88,7 +88,7 @@ struct PipelineCtx {
 }
 
 pub struct Radial {
-    bar_processor: BarProcessor<CubicSplineInterpolation>,
+    bar_processor: BarProcessor<DynamicInterpolation>,
 
     bind_group0: wgpu::BindGroup,
     vertex_params_buffer: wgpu::Buffer,
@@ -366,7 +366,8 @@ impl<F: Fetcher> ComponentAudio<F> for Radial {
         );
 
         if let Some(right) = &self.right {
-            queue.write_buffer(&right.freq_buffer, 0, bytemuck::cast_slice(&bar_values[1]));
+            let right_values = super::utils::right_channel_values(bar_values);
+            queue.write_buffer(&right.freq_buffer, 0, bytemuck::cast_slice(right_values));
         }
     }
 }
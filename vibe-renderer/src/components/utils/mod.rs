@@ -3,3 +3,9 @@ pub mod wgsl_types;
 
 /// The x coords goes from -1 to 1.
 pub const VERTEX_SURFACE_WIDTH: f32 = 2.;
+
+/// Returns the bar values of the right channel.
+/// Mono audio only has one channel, so it's shown on both sides.
+pub fn right_channel_values(bar_values: &[Box<[f32]>]) -> &[f32] {
+    bar_values.last().unwrap()
+}
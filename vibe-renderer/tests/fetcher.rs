@@ -1,9 +1,7 @@
-use std::sync::{Arc, Mutex};
-
 use vibe_audio::fetcher::{Fetcher, SampleBuffer};
 
 pub struct TestFetcher {
-    buffer: Arc<Mutex<SampleBuffer>>,
+    buffer: SampleBuffer,
 }
 
 impl TestFetcher {
@@ -20,17 +18,15 @@ impl TestFetcher {
             buffer
         };
 
-        let mut buffer = SampleBuffer::new(vibe_audio::DEFAULT_SAMPLE_RATE);
-        buffer.push_before(&sample_snapshot);
+        let buffer = SampleBuffer::new(vibe_audio::DEFAULT_SAMPLE_RATE);
+        buffer.push(&sample_snapshot);
 
-        Self {
-            buffer: Arc::new(Mutex::new(buffer)),
-        }
+        Self { buffer }
     }
 }
 
 impl Fetcher for TestFetcher {
-    fn sample_buffer(&self) -> Arc<Mutex<SampleBuffer>> {
+    fn sample_buffer(&self) -> SampleBuffer {
         self.buffer.clone()
     }
 
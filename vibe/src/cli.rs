@@ -1,4 +1,6 @@
 use clap::Parser;
+use std::{path::PathBuf, str::FromStr};
+use vibe_audio::fetcher::PcmFormat;
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
@@ -7,6 +9,93 @@ pub struct Args {
     #[arg(long)]
     pub show_output_devices: bool,
 
+    #[command(flatten)]
+    pub audio: AudioArgs,
+
     /// The output name to start hot reloading the config of the given output.
     pub output_name: Option<String>,
 }
+
+/// The arguments which change where the audio comes from.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct AudioArgs {
+    /// Read raw, interleaved PCM data from stdin instead of the output device.
+    /// Example: `parec --raw --format=float32le | vibe --audio-stdin f32le:44100:2`
+    #[arg(long, value_name = "FORMAT:SAMPLE_RATE:CHANNELS")]
+    pub audio_stdin: Option<StdinAudio>,
+
+    /// Record the audio into the given file.
+    /// Attach it to a bug report, so the visualization can be reproduced with `--replay`.
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Play back a recording of `--record` instead of the audio source of the config.
+    /// By default each rendered frame gets exactly the audio of the recorded frame
+    /// (see `replay.mode` in the audio config).
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["audio_stdin", "record"])]
+    pub replay: Option<PathBuf>,
+}
+
+/// Describes the PCM data which is piped into stdin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StdinAudio {
+    pub format: PcmFormat,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl FromStr for StdinAudio {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [format, sample_rate, channels] = s.split(':').collect::<Vec<&str>>()[..] else {
+            return Err(format!(
+                "Expected '<format>:<sample rate>:<channels>' (for example 'f32le:44100:2') but got '{}'",
+                s
+            ));
+        };
+
+        Ok(Self {
+            format: PcmFormat::from_str(format).map_err(|err| err.to_string())?,
+            sample_rate: sample_rate
+                .parse()
+                .map_err(|err| format!("Invalid sample rate '{}': {}", sample_rate, err))?,
+            channels: channels
+                .parse()
+                .map_err(|err| format!("Invalid amount of channels '{}': {}", channels, err))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stdin_audio() {
+        assert_eq!(
+            StdinAudio::from_str("f32le:48000:2").unwrap(),
+            StdinAudio {
+                format: PcmFormat::F32Le,
+                sample_rate: 48_000,
+                channels: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn replay_conflicts_with_record() {
+        assert!(Args::try_parse_from(["vibe", "--record", "a.vrec"]).is_ok());
+        assert!(
+            Args::try_parse_from(["vibe", "--record", "a.vrec", "--replay", "b.vrec"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_invalid_stdin_audio() {
+        assert!(StdinAudio::from_str("f32le:48000").is_err());
+        assert!(StdinAudio::from_str("u8:48000:2").is_err());
+        assert!(StdinAudio::from_str("f32le:fast:2").is_err());
+        assert!(StdinAudio::from_str("f32le:48000:2:1").is_err());
+    }
+}
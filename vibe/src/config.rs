@@ -1,18 +1,28 @@
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
 use serde::{Deserialize, Serialize};
-use std::{io, str::FromStr};
+use std::{io, net::SocketAddr, num::NonZero, path::PathBuf, str::FromStr, time::Duration};
+use tracing::warn;
 use vibe_audio::{
     cpal::DeviceId,
-    fetcher::{SystemAudioFetcher, SystemAudioFetcherDescriptor},
+    fetcher::{
+        Fetcher, FifoFetcher, FifoFetcherDescriptor, FifoSource, MixFetcher, MixFetcherDescriptor,
+        MixSource, NetworkFetcher, NetworkFetcherDescriptor, NetworkProtocol, Pacing, PcmFormat,
+        RecordingFetcher, ReplayFetcher, ReplayFetcherDescriptor, ReplayMode, Signal,
+        SignalFetcher, SignalFetcherDescriptor, SystemAudioFetcher, SystemAudioFetcherDescriptor,
+    },
     util::DeviceType,
-    SampleProcessor,
+    ChannelMix, ConstantQConfig, Hop, MultiResolutionConfig, SampleProcessor,
+    SampleProcessorConfig, SilenceDetectorConfig, SpectralBackend, WindowFunction,
 };
 use vibe_renderer::RendererDescriptor;
 
-use crate::output::config::component;
+use crate::{cli::AudioArgs, output::config::component};
 
 const STEREO_AUDIO: u16 = 2;
 
+/// The fetcher which provides the audio for the components.
+pub type AudioFetcher = Box<dyn Fetcher>;
+
 #[derive(thiserror::Error, Debug)]
 pub enum ConfigError {
     #[error(transparent)]
@@ -53,6 +63,334 @@ impl From<&GraphicsConfig> for RendererDescriptor {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AudioConfig {
     pub output_device_id: Option<String>,
+
+    /// Read raw PCM data from a fifo instead of the output device.
+    pub fifo: Option<FifoConfig>,
+
+    /// Receive raw PCM data over the network instead of the output device.
+    pub network: Option<NetworkConfig>,
+
+    /// Decide how a recording is played back with `--replay`.
+    pub replay: Option<ReplayConfig>,
+
+    /// Tweak the fft which is applied to the audio.
+    pub fft: Option<FftConfig>,
+
+    /// Decide how the audio channels are mixed before they are passed to the components.
+    /// Sources with more than two channels are downmixed to stereo if it's not set.
+    pub channel_mix: Option<ChannelMixConfig>,
+
+    /// Input devices (for example a microphone) which are mixed into the audio source.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mix: Vec<MixSourceConfig>,
+
+    /// Stop rendering while the audio is silent (only for the outputs of the daemon).
+    pub pause_when_silent: Option<SilenceConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SilenceConfig {
+    /// The level (in dBFS) below which the audio counts as silent.
+    #[serde(default = "default_silence_threshold")]
+    pub threshold: f32,
+
+    /// How long (in seconds) the audio needs to be silent before the rendering stops.
+    #[serde(default = "default_silence_hold_time_secs")]
+    pub hold_time_secs: f32,
+}
+
+fn default_silence_threshold() -> f32 {
+    SilenceDetectorConfig::default().threshold
+}
+
+fn default_silence_hold_time_secs() -> f32 {
+    SilenceDetectorConfig::default().hold_time.as_secs_f32()
+}
+
+impl From<&SilenceConfig> for SilenceDetectorConfig {
+    fn from(conf: &SilenceConfig) -> Self {
+        Self {
+            threshold: conf.threshold,
+            hold_time: Duration::from_secs_f32(conf.hold_time_secs.max(0.)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixSourceConfig {
+    /// The default input device is used if it's not set.
+    pub input_device_id: Option<String>,
+
+    #[serde(default = "default_mix_gain")]
+    pub gain: f32,
+}
+
+fn default_mix_gain() -> f32 {
+    1.
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelMixConfig {
+    Passthrough,
+    Mono,
+    Stereo,
+    MidSide,
+    Swap,
+    Channel(u16),
+}
+
+impl From<ChannelMixConfig> for ChannelMix {
+    fn from(conf: ChannelMixConfig) -> Self {
+        match conf {
+            ChannelMixConfig::Passthrough => Self::Passthrough,
+            ChannelMixConfig::Mono => Self::Mono,
+            ChannelMixConfig::Stereo => Self::Stereo,
+            ChannelMixConfig::MidSide => Self::MidSide,
+            ChannelMixConfig::Swap => Self::Swap,
+            ChannelMixConfig::Channel(channel_idx) => Self::Channel(channel_idx),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FftConfig {
+    /// The amount of frames per channel which are analyzed at once.
+    /// A longer window improves the bass resolution but reacts slower.
+    pub window_len: Option<NonZero<usize>>,
+
+    /// The length of the fft including the zero padding.
+    pub fft_size: Option<NonZero<usize>>,
+
+    #[serde(default)]
+    pub window: WindowFunctionConfig,
+
+    /// How much two consecutive windows overlap (within `[0, 1]`).
+    /// A new fft is computed on every frame if it's not set.
+    pub overlap: Option<f32>,
+
+    /// Compute the bars with a constant-Q transform instead of the plain fft.
+    /// The bass gets long windows and the treble short ones, so each bar spans the same musical interval.
+    pub constant_q: Option<ConstantQTransformConfig>,
+
+    /// Compute the treble of the bars with additional ffts over shorter windows, so it reacts faster.
+    /// Ignored if `constant_q` is set.
+    pub multi_resolution: Option<MultiResolutionFftConfig>,
+}
+
+impl From<&FftConfig> for SampleProcessorConfig {
+    fn from(conf: &FftConfig) -> Self {
+        Self {
+            window_len: conf.window_len,
+            fft_size: conf.fft_size,
+            window: WindowFunction::from(conf.window),
+            hop: match conf.overlap {
+                Some(overlap) => Hop::Overlap(overlap),
+                None => Hop::EveryCall,
+            },
+            backend: match (&conf.constant_q, &conf.multi_resolution) {
+                (Some(constant_q), _) => {
+                    SpectralBackend::ConstantQ(ConstantQConfig::from(constant_q))
+                }
+                (None, Some(multi_resolution)) => {
+                    SpectralBackend::MultiResolution(MultiResolutionConfig::from(multi_resolution))
+                }
+                (None, None) => SpectralBackend::Fft,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstantQTransformConfig {
+    /// The lowest frequency (in Hz) of the transform.
+    #[serde(default = "default_constant_q_min_freq")]
+    pub min_freq: NonZero<u16>,
+
+    /// The highest frequency (in Hz) of the transform.
+    #[serde(default = "default_constant_q_max_freq")]
+    pub max_freq: NonZero<u16>,
+
+    /// The amount of bins within each octave.
+    #[serde(default = "default_constant_q_bins_per_octave")]
+    pub bins_per_octave: NonZero<u16>,
+
+    /// A bandwidth (in Hz) which is added to every bin.
+    /// Values above `0` shorten the windows of the bass, so it reacts faster.
+    #[serde(default)]
+    pub bandwidth_offset: f32,
+}
+
+fn default_constant_q_min_freq() -> NonZero<u16> {
+    ConstantQConfig::default().freq_range.start
+}
+
+fn default_constant_q_max_freq() -> NonZero<u16> {
+    ConstantQConfig::default().freq_range.end
+}
+
+fn default_constant_q_bins_per_octave() -> NonZero<u16> {
+    ConstantQConfig::default().bins_per_octave
+}
+
+impl From<&ConstantQTransformConfig> for ConstantQConfig {
+    fn from(conf: &ConstantQTransformConfig) -> Self {
+        Self {
+            freq_range: conf.min_freq..conf.max_freq,
+            bins_per_octave: conf.bins_per_octave,
+            bandwidth_offset: conf.bandwidth_offset.max(0.),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiResolutionFftConfig {
+    /// The amount of ffts, including the one over the whole window.
+    #[serde(default = "default_amount_resolutions")]
+    pub amount_resolutions: NonZero<u8>,
+
+    /// How many times shorter the window of each further fft is.
+    #[serde(default = "default_resolution_ratio")]
+    pub ratio: NonZero<u8>,
+}
+
+fn default_amount_resolutions() -> NonZero<u8> {
+    MultiResolutionConfig::default().amount_resolutions
+}
+
+fn default_resolution_ratio() -> NonZero<u8> {
+    MultiResolutionConfig::default().ratio
+}
+
+impl From<&MultiResolutionFftConfig> for MultiResolutionConfig {
+    fn from(conf: &MultiResolutionFftConfig) -> Self {
+        Self {
+            amount_resolutions: conf.amount_resolutions,
+            ratio: conf.ratio,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowFunctionConfig {
+    #[default]
+    Hann,
+    BlackmanHarris,
+    FlatTop,
+    Kaiser {
+        beta: f32,
+    },
+}
+
+impl From<WindowFunctionConfig> for WindowFunction {
+    fn from(conf: WindowFunctionConfig) -> Self {
+        match conf {
+            WindowFunctionConfig::Hann => Self::Hann,
+            WindowFunctionConfig::BlackmanHarris => Self::BlackmanHarris,
+            WindowFunctionConfig::FlatTop => Self::FlatTop,
+            WindowFunctionConfig::Kaiser { beta } => Self::Kaiser { beta },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FifoConfig {
+    pub path: PathBuf,
+    pub format: PcmFormatConfig,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl From<FifoConfig> for FifoFetcherDescriptor {
+    fn from(conf: FifoConfig) -> Self {
+        Self {
+            source: FifoSource::Path(conf.path),
+            format: PcmFormat::from(conf.format),
+            sample_rate: conf.sample_rate,
+            amount_channels: conf.channels,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    pub protocol: NetworkProtocolConfig,
+    pub address: SocketAddr,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl From<&NetworkConfig> for NetworkFetcherDescriptor {
+    fn from(conf: &NetworkConfig) -> Self {
+        Self {
+            protocol: NetworkProtocol::from(conf.protocol),
+            address: conf.address,
+            sample_rate: conf.sample_rate,
+            amount_channels: conf.channels,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkProtocolConfig {
+    Udp,
+    Tcp,
+}
+
+impl From<NetworkProtocolConfig> for NetworkProtocol {
+    fn from(conf: NetworkProtocolConfig) -> Self {
+        match conf {
+            NetworkProtocolConfig::Udp => Self::Udp,
+            NetworkProtocolConfig::Tcp => Self::Tcp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ReplayConfig {
+    #[serde(default)]
+    pub mode: ReplayModeConfig,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayModeConfig {
+    /// Each rendered frame gets exactly the audio of the recorded frame.
+    #[default]
+    FrameAccurate,
+    /// Play back the audio with the speed it got recorded with.
+    RealTime,
+}
+
+impl From<ReplayModeConfig> for ReplayMode {
+    fn from(conf: ReplayModeConfig) -> Self {
+        match conf {
+            ReplayModeConfig::FrameAccurate => Self::FrameAccurate,
+            ReplayModeConfig::RealTime => Self::RealTime,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[allow(clippy::enum_variant_names)]
+pub enum PcmFormatConfig {
+    S16Le,
+    S32Le,
+    F32Le,
+}
+
+impl From<PcmFormatConfig> for PcmFormat {
+    fn from(conf: PcmFormatConfig) -> Self {
+        match conf {
+            PcmFormatConfig::S16Le => Self::S16Le,
+            PcmFormatConfig::S32Le => Self::S32Le,
+            PcmFormatConfig::F32Le => Self::F32Le,
+        }
+    }
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
@@ -67,62 +405,229 @@ impl Config {
         std::fs::write(crate::get_config_path(), toml::to_string(self).unwrap())
     }
 
-    pub fn sample_processor(&self) -> anyhow::Result<SampleProcessor<SystemAudioFetcher>> {
-        let device = match self
-            .audio_config
-            .clone()
-            .unwrap_or_default()
-            .output_device_id
+    /// Creates the sample processor with the audio source of the config.
+    /// The audio source of the config is ignored if a replay or stdin is given by `audio_args`.
+    pub fn sample_processor(
+        &self,
+        audio_args: AudioArgs,
+    ) -> anyhow::Result<SampleProcessor<AudioFetcher>> {
+        let audio_config = self.audio_config.clone().unwrap_or_default();
+        let is_replay = audio_args.replay.is_some();
+
+        let fetcher: AudioFetcher = if let Some(path) = audio_args.replay {
+            Box::new(
+                ReplayFetcher::new(&ReplayFetcherDescriptor {
+                    path: path.clone(),
+                    mode: ReplayMode::from(audio_config.replay.unwrap_or_default().mode),
+                })
+                .with_context(|| format!("Couldn't replay '{}'", path.to_string_lossy()))?,
+            )
+        } else if let Some(stdin_audio) = audio_args.audio_stdin {
+            Box::new(
+                FifoFetcher::new(FifoFetcherDescriptor {
+                    source: FifoSource::Stdin,
+                    format: stdin_audio.format,
+                    sample_rate: stdin_audio.sample_rate,
+                    amount_channels: stdin_audio.channels,
+                })
+                .context("Couldn't read audio from stdin")?,
+            )
+        } else if let Some(fifo_config) = audio_config.fifo {
+            let path = fifo_config.path.clone();
+
+            Box::new(
+                FifoFetcher::new(FifoFetcherDescriptor::from(fifo_config)).with_context(|| {
+                    format!("Couldn't read from the fifo '{}'", path.to_string_lossy())
+                })?,
+            )
+        } else if let Some(network_config) = audio_config.network {
+            Box::new(
+                NetworkFetcher::new(&NetworkFetcherDescriptor::from(&network_config))
+                    .with_context(|| {
+                        format!("Couldn't listen for audio on '{}'", network_config.address)
+                    })?,
+            )
+        } else if audio_config.output_device_id.is_none()
+            && vibe_audio::util::get_default_device(DeviceType::Output).is_none()
         {
-            Some(output_device_id) => {
-                let device_id = DeviceId::from_str(&output_device_id).map_err(|err| {
-                    anyhow!(
-                        "Couldn't parse the device id from your config file (in '{}'):\n{}",
-                        crate::get_config_path().to_string_lossy(),
-                        err
-                    )
-                })?;
-
-                match vibe_audio::util::get_device(device_id, DeviceType::Output)? {
-                    Some(device) => device,
-                    None => {
-                        anyhow::bail!(
-                        concat![
-                            "Available output devices:\n\n{:#?}\n",
-                            "\nThere's no output device with the id \"{}\" as you've set in \"{}\"\n",
-                            "Please choose one from the list and add it to your config."
-                        ],
-                        vibe_audio::util::get_device_ids(DeviceType::Output)?,
-                        &output_device_id,
-                        crate::get_config_path().to_string_lossy()
-                    );
-                    }
-                }
+            warn!(concat![
+                "Couldn't find the default output device on your system. ",
+                "Starting in demo mode with generated audio instead."
+            ]);
+            Box::new(demo_fetcher()?)
+        } else {
+            Box::new(system_audio_fetcher(audio_config.output_device_id)?)
+        };
+
+        // a recording contains the mixed audio already
+        let fetcher: AudioFetcher = if audio_config.mix.is_empty() || is_replay {
+            fetcher
+        } else {
+            let mut sources = vec![MixSource { fetcher, gain: 1. }];
+            for mix_source in audio_config.mix.iter() {
+                sources.push(MixSource {
+                    fetcher: Box::new(input_fetcher(mix_source.input_device_id.as_deref())?),
+                    gain: mix_source.gain,
+                });
+            }
+
+            Box::new(MixFetcher::new(MixFetcherDescriptor {
+                sources,
+                sample_rate: None,
+            })?)
+        };
+
+        let fetcher: AudioFetcher = match audio_args.record {
+            Some(path) => Box::new(RecordingFetcher::new(fetcher, &path).with_context(|| {
+                format!(
+                    "Couldn't record the audio into '{}'",
+                    path.to_string_lossy()
+                )
+            })?),
+            None => fetcher,
+        };
+
+        let mut processor_config = audio_config
+            .fft
+            .as_ref()
+            .map(SampleProcessorConfig::from)
+            .unwrap_or_default();
+
+        processor_config.channel_mix = match audio_config.channel_mix {
+            Some(channel_mix) => ChannelMix::from(channel_mix),
+            None if fetcher.channels() > STEREO_AUDIO => ChannelMix::Stereo,
+            None => ChannelMix::Passthrough,
+        };
+
+        if let ChannelMix::Channel(channel_idx) = processor_config.channel_mix {
+            if channel_idx >= fetcher.channels() {
+                anyhow::bail!(
+                    "The audio source has only {} channels, so the channel with the index {} can't be picked.",
+                    fetcher.channels(),
+                    channel_idx
+                );
             }
-            None => match vibe_audio::util::get_default_device(DeviceType::Output) {
+        }
+
+        Ok(SampleProcessor::with_config(fetcher, processor_config))
+    }
+}
+
+fn system_audio_fetcher(output_device_id: Option<String>) -> anyhow::Result<SystemAudioFetcher> {
+    // switch to the new default device if the user didn't pick a specific one
+    let follow_default_device = output_device_id.is_none();
+
+    let device = match output_device_id {
+        Some(output_device_id) => {
+            let device_id = DeviceId::from_str(&output_device_id).map_err(|err| {
+                anyhow!(
+                    "Couldn't parse the device id from your config file (in '{}'):\n{}",
+                    crate::get_config_path().to_string_lossy(),
+                    err
+                )
+            })?;
+
+            match vibe_audio::util::get_device(device_id, DeviceType::Output)? {
                 Some(device) => device,
                 None => {
                     anyhow::bail!(
                         concat![
                             "Available output devices:\n\n{:#?}\n",
-                            "\nCouldn't find the default output device on your system.\n",
-                            "Please choose one from the list and add it to your config in \"{}\"."
+                            "\nThere's no output device with the id \"{}\" as you've set in \"{}\"\n",
+                            "Please choose one from the list and add it to your config."
                         ],
                         vibe_audio::util::get_device_ids(DeviceType::Output)?,
+                        &output_device_id,
                         crate::get_config_path().to_string_lossy()
                     );
                 }
-            },
-        };
+            }
+        }
+        None => match vibe_audio::util::get_default_device(DeviceType::Output) {
+            Some(device) => device,
+            None => {
+                anyhow::bail!(
+                    concat![
+                        "Available output devices:\n\n{:#?}\n",
+                        "\nCouldn't find the default output device on your system.\n",
+                        "Please choose one from the list and add it to your config in \"{}\"."
+                    ],
+                    vibe_audio::util::get_device_ids(DeviceType::Output)?,
+                    crate::get_config_path().to_string_lossy()
+                );
+            }
+        },
+    };
 
-        let system_audio_fetcher = SystemAudioFetcher::new(&SystemAudioFetcherDescriptor {
-            device,
-            amount_channels: Some(STEREO_AUDIO),
-            ..Default::default()
-        })?;
+    SystemAudioFetcher::new(&SystemAudioFetcherDescriptor {
+        device,
+        amount_channels: Some(STEREO_AUDIO),
+        follow_default_device,
+        ..Default::default()
+    })
+    .map_err(|err| err.into())
+}
 
-        Ok(SampleProcessor::new(system_audio_fetcher))
-    }
+fn input_fetcher(input_device_id: Option<&str>) -> anyhow::Result<SystemAudioFetcher> {
+    let device = match input_device_id {
+        Some(input_device_id) => {
+            let device_id = DeviceId::from_str(input_device_id).map_err(|err| {
+                anyhow!(
+                    "Couldn't parse the input device id from your config file (in '{}'):\n{}",
+                    crate::get_config_path().to_string_lossy(),
+                    err
+                )
+            })?;
+
+            vibe_audio::util::get_device(device_id, DeviceType::Input)?.with_context(|| {
+                format!(
+                    concat![
+                        "Available input devices:\n\n{:#?}\n",
+                        "\nThere's no input device with the id \"{}\" which should be mixed into the audio."
+                    ],
+                    vibe_audio::util::get_device_ids(DeviceType::Input).unwrap_or_default(),
+                    input_device_id
+                )
+            })?
+        }
+        None => vibe_audio::util::get_default_device(DeviceType::Input).context(
+            "Couldn't find the default input device which should be mixed into the audio.",
+        )?,
+    };
+
+    SystemAudioFetcher::new(&SystemAudioFetcherDescriptor {
+        device,
+        device_type: DeviceType::Input,
+        sample_rate: vibe_audio::DEFAULT_SAMPLE_RATE,
+        sample_format: None,
+        amount_channels: None,
+        follow_default_device: input_device_id.is_none(),
+    })
+    .context("Couldn't listen to the input device which should be mixed into the audio")
+}
+
+/// Generates a beat, a sweep and some noise so the components have something to show
+/// if there's no output device.
+fn demo_fetcher() -> anyhow::Result<SignalFetcher> {
+    SignalFetcher::new(&SignalFetcherDescriptor {
+        signals: vec![
+            Signal::Impulses {
+                bpm: 120.,
+                amplitude: 0.8,
+            },
+            Signal::Sweep {
+                from: 50.,
+                to: 10_000.,
+                duration: Duration::from_secs(8),
+                amplitude: 0.3,
+            },
+            Signal::PinkNoise { amplitude: 0.1 },
+        ],
+        sample_rate: vibe_audio::DEFAULT_SAMPLE_RATE,
+        amount_channels: STEREO_AUDIO,
+        pacing: Pacing::RealTime,
+    })
+    .map_err(|err| err.into())
 }
 
 pub fn load() -> Result<Config, ConfigError> {
@@ -7,7 +7,7 @@ mod window;
 
 use clap::Parser;
 use state::State;
-use std::{path::PathBuf, sync::OnceLock};
+use std::{path::PathBuf, sync::OnceLock, time::Duration};
 use tracing::{error, info};
 use tracing_indicatif::IndicatifLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
@@ -18,6 +18,9 @@ const APP_NAME: &str = env!("CARGO_PKG_NAME");
 const OUTPUT_CONFIG_DIR_NAME: &str = "output_configs";
 const CONFIG_FILE_NAME: &str = "config.toml";
 
+/// How often the audio is checked while the rendering is paused.
+const PAUSED_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 static XDG: OnceLock<BaseDirectories> = OnceLock::new();
 
 /// Returns the general `xdg` management for the app.
@@ -53,9 +56,9 @@ fn main() -> anyhow::Result<()> {
     }
 
     let result = if let Some(output_name) = args.output_name {
-        window::run(output_name)
+        window::run(output_name, args.audio)
     } else {
-        run_daemon()
+        run_daemon(args.audio)
     };
 
     if let Err(err) = result {
@@ -66,18 +69,26 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn run_daemon() -> anyhow::Result<()> {
+fn run_daemon(audio_args: cli::AudioArgs) -> anyhow::Result<()> {
     let (mut state, mut event_loop) = {
         let conn = Connection::connect_to_env()?;
         let (globals, event_loop) = registry_queue_init(&conn)?;
         let qh = event_loop.handle();
-        let state = State::new(&globals, &qh)?;
+        let state = State::new(&globals, &qh, audio_args)?;
 
         (state, event_loop)
     };
 
+    let qh = event_loop.handle();
     while state.run {
-        event_loop.blocking_dispatch(&mut state)?;
+        if state.is_paused() {
+            // no frames are requested while the rendering is paused, so check the audio by ourselves
+            std::thread::sleep(PAUSED_POLL_INTERVAL);
+            event_loop.roundtrip(&mut state)?;
+            state.poll_audio(&qh);
+        } else {
+            event_loop.blocking_dispatch(&mut state)?;
+        }
     }
 
     Ok(())
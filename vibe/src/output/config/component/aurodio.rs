@@ -1,4 +1,4 @@
-use super::{util::Rgb, ComponentConfig, FreqRange};
+use super::{util::Rgb, ComponentConfig, FreqRange, WeightingConfig};
 use serde::{Deserialize, Serialize};
 use vibe_audio::{fetcher::Fetcher, SampleProcessor};
 use vibe_renderer::{
@@ -38,6 +38,7 @@ impl ComponentConfig for AurodioConfig {
             movement_speed: self.movement_speed,
             up: self.audio_conf.up,
             down: self.audio_conf.down,
+            weighting: vibe_audio::Weighting::from(self.audio_conf.weighting),
             init_norm_factor: vibe_audio::InitNormFactor::default(),
             layers: &layers,
             seed: None,
@@ -55,6 +56,8 @@ pub struct AurodioAudioConfig {
     pub up: f32,
     #[serde(default = "vibe_audio::default_down")]
     pub down: f32,
+    #[serde(default)]
+    pub weighting: WeightingConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
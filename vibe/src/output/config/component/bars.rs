@@ -1,6 +1,6 @@
 use crate::output::config::component::ComponentConfig;
 
-use super::{FreqRange, Rgba};
+use super::{BarDistributionConfig, FreqRange, InterpolationConfig, Rgba, WeightingConfig};
 use serde::{Deserialize, Serialize};
 use std::num::NonZero;
 use vibe_audio::fetcher::Fetcher;
@@ -83,6 +83,9 @@ impl Default for BarsConfig {
                 freq_range: None,
                 up: vibe_audio::default_up(),
                 down: vibe_audio::default_down(),
+                weighting: WeightingConfig::default(),
+                interpolation: InterpolationConfig::default(),
+                bar_distribution: BarDistributionConfig::default(),
             },
             max_height: 0.75,
             variant: BarsVariantConfig::Color(turquoise),
@@ -101,6 +104,13 @@ pub struct BarsAudioConfig {
     pub up: f32,
     #[serde(default = "vibe_audio::default_down")]
     pub down: f32,
+    #[serde(default)]
+    pub weighting: WeightingConfig,
+    #[serde(default)]
+    pub interpolation: InterpolationConfig,
+    /// The amount of bars is ignored for the octave distributions.
+    #[serde(default)]
+    pub bar_distribution: BarDistributionConfig,
 }
 
 impl From<BarsAudioConfig> for vibe_audio::BarProcessorConfig {
@@ -112,7 +122,10 @@ impl From<BarsAudioConfig> for vibe_audio::BarProcessorConfig {
                 None => NonZero::new(50).unwrap()..NonZero::new(10_000).unwrap(),
             },
             down: conf.down,
+            weighting: vibe_audio::Weighting::from(conf.weighting),
+            interpolation: vibe_audio::InterpolationVariant::from(conf.interpolation),
             up: conf.up,
+            bar_distribution: vibe_audio::BarDistribution::from(conf.bar_distribution),
             ..Default::default()
         }
     }
@@ -1,6 +1,6 @@
 use crate::output::config::component::ComponentConfig;
 
-use super::FreqRange;
+use super::{FreqRange, WeightingConfig};
 use serde::{Deserialize, Serialize};
 use std::num::NonZero;
 use vibe_audio::{fetcher::Fetcher, BarProcessorConfig};
@@ -48,6 +48,8 @@ pub struct ChessyAudioConfig {
     pub up: f32,
     #[serde(default = "vibe_audio::default_down")]
     pub down: f32,
+    #[serde(default)]
+    pub weighting: WeightingConfig,
 }
 
 impl From<ChessyAudioConfig> for BarProcessorConfig {
@@ -57,6 +59,7 @@ impl From<ChessyAudioConfig> for BarProcessorConfig {
             freq_range: conf.freq_range.range(),
             up: conf.up,
             down: conf.down,
+            weighting: vibe_audio::Weighting::from(conf.weighting),
             ..Default::default()
         }
     }
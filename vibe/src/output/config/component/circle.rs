@@ -1,6 +1,6 @@
 use crate::output::config::component::ComponentConfig;
 
-use super::{FreqRange, Rgba};
+use super::{FreqRange, InterpolationConfig, Rgba, WeightingConfig};
 use serde::{Deserialize, Serialize};
 use std::num::NonZero;
 use vibe_audio::{fetcher::Fetcher, BarProcessorConfig};
@@ -57,6 +57,10 @@ pub struct CircleAudioConfig {
     pub up: f32,
     #[serde(default = "vibe_audio::default_down")]
     pub down: f32,
+    #[serde(default)]
+    pub weighting: WeightingConfig,
+    #[serde(default)]
+    pub interpolation: InterpolationConfig,
 }
 
 impl From<CircleAudioConfig> for BarProcessorConfig {
@@ -65,6 +69,8 @@ impl From<CircleAudioConfig> for BarProcessorConfig {
             amount_bars: conf.amount_bars,
             freq_range: conf.freq_range.range(),
             down: conf.down,
+            weighting: vibe_audio::Weighting::from(conf.weighting),
+            interpolation: vibe_audio::InterpolationVariant::from(conf.interpolation),
             up: conf.up,
 
             ..Default::default()
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use crate::output::config::component::ComponentConfig;
 
-use super::FreqRange;
+use super::{FreqRange, WeightingConfig};
 use image::ImageReader;
 use serde::{Deserialize, Serialize};
 use vibe_audio::fetcher::Fetcher;
@@ -48,6 +48,7 @@ impl ComponentConfig for WallpaperPulseEdgesConfig {
                 freq_range: self.audio_conf.freq_range.range(),
                 up: self.audio_conf.up,
                 down: self.audio_conf.down,
+                weighting: vibe_audio::Weighting::from(self.audio_conf.weighting),
                 init_norm_factor: vibe_audio::InitNormFactor::default(),
                 high_threshold_ratio,
                 low_threshold_ratio,
@@ -75,6 +76,8 @@ pub struct WallpaperPulseEdgesAudioConfig {
     pub up: f32,
     #[serde(default = "vibe_audio::default_down")]
     pub down: f32,
+    #[serde(default)]
+    pub weighting: WeightingConfig,
 }
 
 impl Default for WallpaperPulseEdgesAudioConfig {
@@ -83,6 +86,7 @@ impl Default for WallpaperPulseEdgesAudioConfig {
             freq_range: FreqRange::Bass,
             up: vibe_audio::default_up(),
             down: vibe_audio::default_down(),
+            weighting: WeightingConfig::default(),
         }
     }
 }
@@ -1,6 +1,6 @@
 use crate::output::config::component::{ComponentConfig, ConfigError};
 
-use super::FreqRange;
+use super::{FreqRange, InterpolationConfig, WeightingConfig};
 use image::{DynamicImage, ImageReader};
 use serde::{Deserialize, Serialize};
 use std::{num::NonZero, path::PathBuf};
@@ -107,6 +107,10 @@ pub struct FragmentCanvasAudioConfig {
     pub up: f32,
     #[serde(default = "vibe_audio::default_down")]
     pub down: f32,
+    #[serde(default)]
+    pub weighting: WeightingConfig,
+    #[serde(default)]
+    pub interpolation: InterpolationConfig,
 }
 
 impl Default for FragmentCanvasAudioConfig {
@@ -116,6 +120,8 @@ impl Default for FragmentCanvasAudioConfig {
             freq_range: FreqRange::Custom(NonZero::new(50).unwrap()..NonZero::new(10_000).unwrap()),
             up: vibe_audio::default_up(),
             down: vibe_audio::default_down(),
+            weighting: WeightingConfig::default(),
+            interpolation: InterpolationConfig::default(),
         }
     }
 }
@@ -127,6 +133,8 @@ impl From<FragmentCanvasAudioConfig> for BarProcessorConfig {
             freq_range: conf.freq_range.range(),
             up: conf.up,
             down: conf.down,
+            weighting: vibe_audio::Weighting::from(conf.weighting),
+            interpolation: vibe_audio::InterpolationVariant::from(conf.interpolation),
             ..Default::default()
         }
     }
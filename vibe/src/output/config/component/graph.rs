@@ -1,6 +1,6 @@
 use crate::output::config::component::ComponentConfig;
 
-use super::{FreqRange, Rgba};
+use super::{FreqRange, InterpolationConfig, Rgba, WeightingConfig};
 use cgmath::Deg;
 use serde::{Deserialize, Serialize};
 use std::num::NonZero;
@@ -76,6 +76,10 @@ pub struct GraphAudioConfig {
     pub up: f32,
     #[serde(default = "vibe_audio::default_down")]
     pub down: f32,
+    #[serde(default)]
+    pub weighting: WeightingConfig,
+    #[serde(default)]
+    pub interpolation: InterpolationConfig,
 }
 
 impl From<GraphAudioConfig> for vibe_audio::BarProcessorConfig {
@@ -83,6 +87,8 @@ impl From<GraphAudioConfig> for vibe_audio::BarProcessorConfig {
         Self {
             freq_range: conf.freq_range.range(),
             down: conf.down,
+            weighting: vibe_audio::Weighting::from(conf.weighting),
+            interpolation: vibe_audio::InterpolationVariant::from(conf.interpolation),
             up: conf.up,
             ..Default::default()
         }
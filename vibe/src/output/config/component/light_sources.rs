@@ -1,4 +1,4 @@
-use super::ConfigError;
+use super::{ConfigError, WeightingConfig};
 use crate::output::config::component::ComponentConfig;
 use image::ImageReader;
 use serde::{Deserialize, Serialize};
@@ -54,6 +54,7 @@ impl ComponentConfig for LightSourcesConfig {
             freq_range: self.audio_conf.freq_range.clone(),
             up: self.audio_conf.up,
             down: self.audio_conf.down,
+            weighting: vibe_audio::Weighting::from(self.audio_conf.weighting),
             init_norm_factor: vibe_audio::InitNormFactor::default(),
 
             wallpaper: img,
@@ -77,6 +78,8 @@ pub struct LightSourcesAudioConfig {
     pub up: f32,
     #[serde(default = "vibe_audio::default_down")]
     pub down: f32,
+    #[serde(default)]
+    pub weighting: WeightingConfig,
 }
 
 impl Default for LightSourcesAudioConfig {
@@ -85,6 +88,7 @@ impl Default for LightSourcesAudioConfig {
             freq_range: vibe_audio::default_freq_range(),
             up: vibe_audio::default_up(),
             down: vibe_audio::default_down(),
+            weighting: WeightingConfig::default(),
         }
     }
 }
@@ -46,6 +46,82 @@ impl FreqRange {
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum BarDistributionConfig {
+    #[default]
+    Uniform,
+    Natural,
+    Mel,
+    Bark,
+    Erb,
+    ThirdOctave,
+    SixthOctave,
+    TwelfthOctave,
+}
+
+impl From<BarDistributionConfig> for vibe_audio::BarDistribution {
+    fn from(conf: BarDistributionConfig) -> Self {
+        match conf {
+            BarDistributionConfig::Uniform => Self::Uniform,
+            BarDistributionConfig::Natural => Self::Natural,
+            BarDistributionConfig::Mel => Self::Mel,
+            BarDistributionConfig::Bark => Self::Bark,
+            BarDistributionConfig::Erb => Self::Erb,
+            BarDistributionConfig::ThirdOctave => Self::Octave(vibe_audio::OctaveFraction::Third),
+            BarDistributionConfig::SixthOctave => Self::Octave(vibe_audio::OctaveFraction::Sixth),
+            BarDistributionConfig::TwelfthOctave => {
+                Self::Octave(vibe_audio::OctaveFraction::Twelfth)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum InterpolationConfig {
+    None,
+    Linear,
+    #[default]
+    CubicSpline,
+    Pchip,
+    Akima,
+}
+
+impl From<InterpolationConfig> for vibe_audio::InterpolationVariant {
+    fn from(conf: InterpolationConfig) -> Self {
+        match conf {
+            InterpolationConfig::None => Self::None,
+            InterpolationConfig::Linear => Self::Linear,
+            InterpolationConfig::CubicSpline => Self::CubicSpline,
+            InterpolationConfig::Pchip => Self::Pchip,
+            InterpolationConfig::Akima => Self::Akima,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum WeightingConfig {
+    #[default]
+    None,
+    A,
+    C,
+    Itu468,
+    Tilt {
+        db_per_octave: f32,
+    },
+}
+
+impl From<WeightingConfig> for vibe_audio::Weighting {
+    fn from(conf: WeightingConfig) -> Self {
+        match conf {
+            WeightingConfig::None => Self::None,
+            WeightingConfig::A => Self::A,
+            WeightingConfig::C => Self::C,
+            WeightingConfig::Itu468 => Self::Itu468,
+            WeightingConfig::Tilt { db_per_octave } => Self::Tilt { db_per_octave },
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum ConfigError {
     #[error(transparent)]
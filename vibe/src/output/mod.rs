@@ -1,6 +1,9 @@
 pub mod config;
 
-use crate::{output::config::component::ComponentConfig, state::State, types::size::Size};
+use crate::{
+    config::AudioFetcher, output::config::component::ComponentConfig, state::State,
+    types::size::Size,
+};
 use config::OutputConfig;
 use smithay_client_toolkit::{
     output::OutputInfo,
@@ -10,20 +13,23 @@ use smithay_client_toolkit::{
     },
 };
 use tracing::error;
-use vibe_audio::{fetcher::SystemAudioFetcher, SampleProcessor};
+use vibe_audio::SampleProcessor;
 use vibe_renderer::{ComponentAudio, Renderer};
 use wayland_client::QueueHandle;
 use wgpu::{PresentMode, Surface, SurfaceConfiguration};
 
 /// Contains every relevant information for an output.
 pub struct OutputCtx {
-    pub components: Vec<Box<dyn ComponentAudio<SystemAudioFetcher>>>,
+    pub components: Vec<Box<dyn ComponentAudio<AudioFetcher>>>,
 
     // don't know if this is required, but better drop `surface` first before
     // `layer_surface`
     surface: Surface<'static>,
     layer_surface: LayerSurface,
     surface_config: SurfaceConfiguration,
+
+    // `true` if a frame callback got requested which hasn't been called yet
+    has_pending_frame: bool,
 }
 
 impl OutputCtx {
@@ -32,7 +38,7 @@ impl OutputCtx {
         surface: Surface<'static>,
         layer_surface: LayerSurface,
         renderer: &Renderer,
-        sample_processor: &SampleProcessor<SystemAudioFetcher>,
+        sample_processor: &SampleProcessor<AudioFetcher>,
         config: OutputConfig,
     ) -> Self {
         let size = Size::from(&info);
@@ -68,10 +74,18 @@ impl OutputCtx {
             surface,
             layer_surface,
             components,
+            has_pending_frame: false,
         }
     }
 
-    pub fn request_redraw(&self, qh: &QueueHandle<State>) {
+    /// Requests a frame callback unless one is pending already
+    /// (otherwise the output would be rendered twice per frame from now on).
+    pub fn request_redraw(&mut self, qh: &QueueHandle<State>) {
+        if self.has_pending_frame {
+            return;
+        }
+        self.has_pending_frame = true;
+
         let surface = self.layer_surface.wl_surface();
 
         let size = Size::from(&self.surface_config);
@@ -85,6 +99,11 @@ impl OutputCtx {
         self.layer_surface.commit();
     }
 
+    /// Has to be called as soon as the requested frame callback got called.
+    pub fn frame_done(&mut self) {
+        self.has_pending_frame = false;
+    }
+
     /// Update the internal data to the new output size.
     pub fn resize(&mut self, renderer: &Renderer, new_size: Size) {
         if new_size.width > 0 && new_size.height > 0 {
@@ -1,5 +1,6 @@
 use crate::{
-    config::ConfigError,
+    cli::AudioArgs,
+    config::{AudioFetcher, ConfigError},
     output::{
         config::{component::Config, OutputConfig},
         OutputCtx,
@@ -28,7 +29,7 @@ use smithay_client_toolkit::{
 };
 use std::{collections::HashMap, ptr::NonNull, time::Instant};
 use tracing::{debug, error, info, warn};
-use vibe_audio::{fetcher::SystemAudioFetcher, SampleProcessor};
+use vibe_audio::{SampleProcessor, SilenceDetector, SilenceDetectorConfig};
 use vibe_renderer::Renderer;
 use wayland_client::{
     globals::GlobalList,
@@ -48,7 +49,10 @@ pub struct State {
     compositor_state: CompositorState,
 
     renderer: Renderer,
-    sample_processor: SampleProcessor<SystemAudioFetcher>,
+    sample_processor: SampleProcessor<AudioFetcher>,
+    silence_detector: Option<SilenceDetector>,
+    // `true` if the rendering is paused because the audio is silent
+    is_paused: bool,
 
     time: Instant,
     pointer: Option<WlPointer>,
@@ -57,7 +61,11 @@ pub struct State {
 }
 
 impl State {
-    pub fn new(globals: &GlobalList, qh: &QueueHandle<Self>) -> anyhow::Result<Self> {
+    pub fn new(
+        globals: &GlobalList,
+        qh: &QueueHandle<Self>,
+        audio_args: AudioArgs,
+    ) -> anyhow::Result<Self> {
         if is_on_kde() {
             message_to_kde();
         }
@@ -120,7 +128,12 @@ impl State {
             default_config
         });
 
-        let sample_processor = vibe_config.sample_processor()?;
+        let sample_processor = vibe_config.sample_processor(audio_args)?;
+        let silence_detector = vibe_config
+            .audio_config
+            .as_ref()
+            .and_then(|audio_config| audio_config.pause_when_silent.as_ref())
+            .map(|conf| SilenceDetector::new(&sample_processor, SilenceDetectorConfig::from(conf)));
 
         let renderer = Renderer::new(&vibe_renderer::RendererDescriptor::from(
             &vibe_config.graphics_config,
@@ -139,6 +152,8 @@ impl State {
             pointer: None,
 
             sample_processor,
+            silence_detector,
+            is_paused: false,
 
             outputs: HashMap::new(),
 
@@ -169,7 +184,10 @@ impl State {
                     &output.components,
                 );
                 surface_texture.present();
-                output.request_redraw(qh);
+
+                if !self.is_paused {
+                    output.request_redraw(qh);
+                }
             }
             wgpu::CurrentSurfaceTexture::Timeout => {
                 error!("A frame took too long to be present")
@@ -190,6 +208,44 @@ impl State {
     }
 }
 
+/// Audio relevant methods
+impl State {
+    /// Returns `true` if the rendering is paused because the audio is silent.
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// Processes the audio while the rendering is paused and resumes it as soon as the audio is audible again.
+    pub fn poll_audio(&mut self, qh: &QueueHandle<Self>) {
+        self.process_next_samples();
+
+        if !self.is_paused {
+            // outputs whose frame callback is still pending keep rendering on their own
+            for output in self.outputs.values_mut() {
+                output.request_redraw(qh);
+            }
+        }
+    }
+
+    fn process_next_samples(&mut self) {
+        self.sample_processor.process_next_samples();
+
+        if let Some(silence_detector) = self.silence_detector.as_mut() {
+            let is_silent = silence_detector.process_samples(&self.sample_processor);
+
+            if is_silent != self.is_paused {
+                if is_silent {
+                    info!("The audio is silent. Pausing the rendering.");
+                } else {
+                    info!("The audio is audible again. Resuming the rendering.");
+                }
+            }
+
+            self.is_paused = is_silent;
+        }
+    }
+}
+
 delegate_output!(State);
 impl OutputHandler for State {
     fn output_state(&mut self) -> &mut OutputState {
@@ -344,7 +400,7 @@ impl CompositorHandler for State {
         surface: &WlSurface,
         _time: u32,
     ) {
-        self.sample_processor.process_next_samples();
+        self.process_next_samples();
 
         let key = self
             .outputs
@@ -353,6 +409,7 @@ impl CompositorHandler for State {
             .map(|(out, _ctx)| out.clone())
             .unwrap();
 
+        self.outputs.get_mut(&key).unwrap().frame_done();
         self.render(key, qh);
     }
 
@@ -383,7 +440,7 @@ impl LayerShellHandler for State {
 
     fn configure(
         &mut self,
-        conn: &Connection,
+        _conn: &Connection,
         qh: &QueueHandle<Self>,
         layer: &LayerSurface,
         configure: LayerSurfaceConfigure,
@@ -392,11 +449,11 @@ impl LayerShellHandler for State {
         let new_size = Size::from(configure.new_size);
         debug!("Configure new size: {:?}", new_size);
 
-        let (key, surface) = self
+        let key = self
             .outputs
             .iter()
             .find(|(_out, ctx)| ctx.layer_surface() == layer)
-            .map(|(out, ctx)| (out.clone(), ctx.layer_surface().wl_surface().clone()))
+            .map(|(out, _ctx)| out.clone())
             .unwrap();
 
         {
@@ -404,7 +461,9 @@ impl LayerShellHandler for State {
             output_mut.resize(&self.renderer, new_size);
         }
 
-        self.frame(conn, qh, &surface, 0);
+        // not a frame callback, so a pending one stays pending
+        self.process_next_samples();
+        self.render(key, qh);
     }
 }
 
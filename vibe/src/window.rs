@@ -7,7 +7,7 @@ use std::{
 use anyhow::{bail, Context};
 use notify::{INotifyWatcher, Watcher};
 use tracing::error;
-use vibe_audio::{fetcher::SystemAudioFetcher, SampleProcessor};
+use vibe_audio::SampleProcessor;
 use vibe_renderer::{components::ComponentAudio, Renderer, RendererDescriptor};
 use winit::{
     application::ApplicationHandler, dpi::PhysicalPosition, event::WindowEvent,
@@ -15,6 +15,8 @@ use winit::{
 };
 
 use crate::{
+    cli::AudioArgs,
+    config::AudioFetcher,
     output::config::{
         component::{ComponentConfig, Config, ConfigError},
         OutputConfig,
@@ -27,7 +29,7 @@ struct State<'a> {
     surface_config: wgpu::SurfaceConfiguration,
     window: Arc<Window>,
 
-    components: Vec<Box<dyn ComponentAudio<SystemAudioFetcher>>>,
+    components: Vec<Box<dyn ComponentAudio<AudioFetcher>>>,
 }
 
 impl State<'_> {
@@ -52,7 +54,7 @@ impl State<'_> {
     pub fn refresh_components(
         &mut self,
         renderer: &Renderer,
-        processor: &SampleProcessor<SystemAudioFetcher>,
+        processor: &SampleProcessor<AudioFetcher>,
         comp_configs: &[Config],
     ) -> Result<(), ConfigError> {
         let mut new_components = Vec::with_capacity(comp_configs.len());
@@ -123,7 +125,7 @@ impl State<'_> {
 }
 
 struct OutputRenderer<'a> {
-    processor: SampleProcessor<SystemAudioFetcher>,
+    processor: SampleProcessor<AudioFetcher>,
     renderer: Renderer,
     state: Option<State<'a>>,
 
@@ -136,11 +138,11 @@ struct OutputRenderer<'a> {
 }
 
 impl OutputRenderer<'_> {
-    pub fn new(output_name: String) -> anyhow::Result<Self> {
+    pub fn new(output_name: String, audio_args: AudioArgs) -> anyhow::Result<Self> {
         let config = crate::config::load()?;
 
         let renderer = Renderer::new(&RendererDescriptor::from(&config.graphics_config));
-        let processor = config.sample_processor()?;
+        let processor = config.sample_processor(audio_args)?;
 
         let (output_config_path, output_config) = {
             let Some((path, config)) = crate::output::config::load(&output_name) else {
@@ -337,8 +339,8 @@ impl ApplicationHandler for OutputRenderer<'_> {
     }
 }
 
-pub fn run(output_name: String) -> anyhow::Result<()> {
-    let mut app = OutputRenderer::new(output_name)?;
+pub fn run(output_name: String, audio_args: AudioArgs) -> anyhow::Result<()> {
+    let mut app = OutputRenderer::new(output_name, audio_args)?;
     let event_loop = EventLoop::new().unwrap();
     event_loop.run_app(&mut app)?;
     Ok(())